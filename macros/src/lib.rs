@@ -0,0 +1,60 @@
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, ItemFn};
+
+/// Dumps the transactions verified in a test when the test panics.
+///
+/// Every transaction verified through `ckb_debugger_dumper::DumpingVerifier` (or
+/// `verify_and_dump`) inside the function is recorded. If the function panics, the recorded
+/// transactions are dumped under names derived from the module path and the function name, and
+/// their ckb-debugger commands are printed to stderr. See `ckb_debugger_dumper::Recording`.
+///
+/// ```ignore
+/// #[test]
+/// #[dump_on_failure]
+/// fn test_transfer() {
+///     verify_and_dump(&resolved_tx, &data_loader, MAX_CYCLES).unwrap();
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn dump_on_failure(attr: TokenStream, item: TokenStream) -> TokenStream {
+    if !attr.is_empty() {
+        return syn::Error::new(
+            proc_macro2::TokenStream::from(attr)
+                .into_iter()
+                .next()
+                .unwrap()
+                .span(),
+            "dump_on_failure takes no arguments",
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    let ItemFn {
+        attrs,
+        vis,
+        sig,
+        block,
+    } = parse_macro_input!(item as ItemFn);
+    if let Some(asyncness) = &sig.asyncness {
+        return syn::Error::new(
+            asyncness.span,
+            "dump_on_failure does not support async functions",
+        )
+        .to_compile_error()
+        .into();
+    }
+    let name = &sig.ident;
+
+    quote!(
+        #(#attrs)*
+        #vis #sig {
+            let __ckb_debugger_dumper_recording = ::ckb_debugger_dumper::Recording::start(
+                concat!(module_path!(), "::", stringify!(#name))
+            );
+            #block
+        }
+    )
+    .into()
+}
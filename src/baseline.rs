@@ -0,0 +1,358 @@
+use ckb_script::{ScriptGroupType, TransactionScriptsVerifier};
+use ckb_types::{bytes::Bytes, core::Cycle, packed::Byte32, prelude::Entity};
+use json::JsonValue;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    fmt_vec,
+    minimize::with_bin,
+    mock_tx::{invalid, parse_array, parse_byte32, parse_str},
+    output::split_unique_name,
+    script_groups, write_atomic, Error, Manifest, ManifestGroup, MockTransaction,
+};
+
+/// The cycles of a script group of a dump, as recorded in a [`Baseline`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BaselineGroup {
+    /// The name the dump was written under, see
+    /// [`DumpDir::dump_name`](crate::DumpDir::dump_name).
+    pub name: String,
+    /// The file name of the dump in its directory.
+    pub tx_file: String,
+    pub group_index: usize,
+    pub group_type: ScriptGroupType,
+    pub script_hash: Byte32,
+    /// The error if the group fails.
+    pub cycles: Result<Cycle, String>,
+}
+
+/// The cycles of every script group of a directory of dumps, to catch changes in cycle usage
+/// between runs of a test suite, or when the dumps are run again with a rebuilt binary.
+///
+/// ```ignore
+/// let baseline = Baseline::read("cycles.json")?;
+/// let current = BaselineRunner::new("target/ckb-dumps").run()?;
+/// for change in baseline.compare(&current, 5.0) {
+///     println!("{}", change);
+/// }
+/// ```
+///
+/// Groups are matched by the name of their dump, which for dumps written by a
+/// [`DumpDir`](crate::DumpDir) leaves out the timestamp, pid and counter, and by their group
+/// type and script hash. A test that dumps the same group several times is matched in the
+/// order of its dumps.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Baseline {
+    /// Sorted by dump name and the order of the dumps, then group index.
+    pub groups: Vec<BaselineGroup>,
+}
+
+/// A group whose cycles differ from the [`Baseline`] by more than the threshold, or that was
+/// added, removed, or started or stopped failing.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CycleChange {
+    /// `None` if the group is new.
+    pub before: Option<BaselineGroup>,
+    /// `None` if the group is gone.
+    pub after: Option<BaselineGroup>,
+}
+
+fn fmt_cycles(group: &Option<BaselineGroup>) -> String {
+    match group.as_ref().map(|group| &group.cycles) {
+        Some(Ok(cycles)) => format!("{} cycles", cycles),
+        Some(Err(error)) => format!("failed ({})", error),
+        None => String::from("none"),
+    }
+}
+
+impl CycleChange {
+    fn group(&self) -> &BaselineGroup {
+        self.after
+            .as_ref()
+            .or(self.before.as_ref())
+            .expect("a change has a group")
+    }
+
+    /// The change in percent, if the group passes before and after.
+    pub fn percent(&self) -> Option<f64> {
+        match (&self.before, &self.after) {
+            (Some(before), Some(after)) => match (&before.cycles, &after.cycles) {
+                (Ok(before), Ok(after)) if *before > 0 => {
+                    Some((*after as f64 - *before as f64) * 100.0 / *before as f64)
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for CycleChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let group = self.group();
+        write!(
+            f,
+            "{} group #{} {} {}: {} -> {}",
+            group.tx_file,
+            group.group_index,
+            group.group_type,
+            fmt_vec(group.script_hash.as_slice()),
+            fmt_cycles(&self.before),
+            fmt_cycles(&self.after)
+        )?;
+        match self.percent() {
+            Some(percent) => write!(f, " ({:+.2}%)", percent),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Whether going from `before` to `after` cycles is a change of more than `threshold` percent.
+fn exceeds(before: Cycle, after: Cycle, threshold: f64) -> bool {
+    let delta = (after as f64 - before as f64).abs();
+    after != before && delta * 100.0 > threshold * before as f64
+}
+
+/// The key matching a group between baselines: dump name, group type, script hash, and how
+/// many groups with the same name, type and hash come before it.
+type GroupKey = (String, String, String, usize);
+
+fn keyed(groups: &[BaselineGroup]) -> Vec<(GroupKey, &BaselineGroup)> {
+    let mut seen: HashMap<(String, String, String), usize> = HashMap::new();
+    groups
+        .iter()
+        .map(|group| {
+            let id = (
+                group.name.clone(),
+                group.group_type.to_string(),
+                fmt_vec(group.script_hash.as_slice()),
+            );
+            let count = seen.entry(id.clone()).or_default();
+            let key = (id.0, id.1, id.2, *count);
+            *count += 1;
+            (key, group)
+        })
+        .collect()
+}
+
+impl Baseline {
+    /// The groups of `current` that changed from this baseline. Cycles changing by no more
+    /// than `threshold` percent are not a change, with `0.0` any difference is.
+    pub fn compare(&self, current: &Baseline, threshold: f64) -> Vec<CycleChange> {
+        let mut groups: BTreeMap<_, (Option<&BaselineGroup>, Option<&BaselineGroup>)> =
+            BTreeMap::new();
+        for (key, group) in keyed(&self.groups) {
+            groups.entry(key).or_default().0 = Some(group);
+        }
+        for (key, group) in keyed(&current.groups) {
+            groups.entry(key).or_default().1 = Some(group);
+        }
+
+        groups
+            .into_values()
+            .filter(|(before, after)| match (before, after) {
+                (Some(before), Some(after)) => match (&before.cycles, &after.cycles) {
+                    (Ok(before), Ok(after)) => exceeds(*before, *after, threshold),
+                    (Err(_), Err(_)) => false,
+                    _ => true,
+                },
+                _ => true,
+            })
+            .map(|(before, after)| CycleChange {
+                before: before.cloned(),
+                after: after.cloned(),
+            })
+            .collect()
+    }
+
+    pub fn to_json(&self) -> JsonValue {
+        let mut js = JsonValue::new_object();
+        js["groups"] = JsonValue::Array(
+            self.groups
+                .iter()
+                .map(|group| {
+                    let mut js_group = JsonValue::new_object();
+                    js_group["name"] = group.name.as_str().into();
+                    js_group["tx_file"] = group.tx_file.as_str().into();
+                    js_group["index"] = group.group_index.into();
+                    js_group["group_type"] = match group.group_type {
+                        ScriptGroupType::Lock => "lock".into(),
+                        ScriptGroupType::Type => "type".into(),
+                    };
+                    js_group["script_hash"] = fmt_vec(group.script_hash.as_slice()).into();
+                    match &group.cycles {
+                        Ok(cycles) => js_group["cycles"] = (*cycles).into(),
+                        Err(error) => js_group["error"] = error.as_str().into(),
+                    }
+                    js_group
+                })
+                .collect(),
+        );
+        js
+    }
+
+    /// Parses the JSON of [`Self::to_json`].
+    pub fn from_json(js: &JsonValue) -> Result<Self, Error> {
+        let groups = parse_array(&js["groups"], "groups")?
+            .iter()
+            .enumerate()
+            .map(|(i, js_group)| {
+                let path = format!("groups[{}]", i);
+                let field = |name: &str| format!("{}.{}", path, name);
+                let group_type = match parse_str(&js_group["group_type"], &field("group_type"))? {
+                    "lock" => ScriptGroupType::Lock,
+                    "type" => ScriptGroupType::Type,
+                    _ => return Err(invalid(&field("group_type"), "expected lock or type")),
+                };
+                let cycles = match &js_group["error"] {
+                    JsonValue::Null => Ok(js_group["cycles"]
+                        .as_u64()
+                        .ok_or_else(|| invalid(&field("cycles"), "expected a number"))?),
+                    error => Err(String::from(parse_str(error, &field("error"))?)),
+                };
+                Ok(BaselineGroup {
+                    name: String::from(parse_str(&js_group["name"], &field("name"))?),
+                    tx_file: String::from(parse_str(&js_group["tx_file"], &field("tx_file"))?),
+                    group_index: js_group["index"]
+                        .as_usize()
+                        .ok_or_else(|| invalid(&field("index"), "expected a number"))?,
+                    group_type,
+                    script_hash: parse_byte32(&js_group["script_hash"], &field("script_hash"))?,
+                    cycles,
+                })
+            })
+            .collect::<Result<_, Error>>()?;
+        Ok(Baseline { groups })
+    }
+
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let js = json::parse(&fs::read_to_string(path)?)
+            .map_err(|e| Error::InvalidJson(e.to_string()))?;
+        Self::from_json(&js)
+    }
+
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        write_atomic(path.as_ref(), self.to_json().pretty(2).as_bytes())?;
+        Ok(())
+    }
+}
+
+/// Collects the cycles of every script group of the dumps in a directory in a [`Baseline`].
+///
+/// Every `.json` file with a `mock_info` section is a dump, sidecars and baselines are skipped.
+/// The cycles a [`Manifest`] next to a dump recorded are taken from it. Groups without
+/// recorded cycles are run in-process, and so is every group with [`Self::rerun`] or
+/// [`Self::bin_path`].
+pub struct BaselineRunner {
+    dir: PathBuf,
+    bin_path: Option<String>,
+    max_cycles: Cycle,
+    rerun: bool,
+}
+
+impl BaselineRunner {
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        BaselineRunner {
+            dir: dir.into(),
+            bin_path: None,
+            max_cycles: Cycle::MAX,
+            rerun: false,
+        }
+    }
+
+    /// The binary to run in place of blanked cell deps, as with ckb-debugger's `--bin`. The
+    /// cycles in manifests were recorded with another build, so every group is run again.
+    pub fn bin_path(mut self, bin_path: &str) -> Self {
+        self.bin_path = Some(String::from(bin_path));
+        self
+    }
+
+    /// The cycle limit of each group run in-process.
+    pub fn max_cycles(mut self, max_cycles: Cycle) -> Self {
+        self.max_cycles = max_cycles;
+        self
+    }
+
+    /// Runs every group in-process, ignoring the cycles recorded in manifests.
+    pub fn rerun(mut self, rerun: bool) -> Self {
+        self.rerun = rerun;
+        self
+    }
+
+    pub fn run(&self) -> Result<Baseline, Error> {
+        let bin = self.bin_path.as_ref().map(fs::read).transpose()?;
+        let bin = bin.map(Bytes::from);
+        let rerun = self.rerun || bin.is_some();
+
+        let mut files = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.is_file() && path.extension().is_some_and(|ext| ext == "json") {
+                let stem = path.file_stem().unwrap().to_string_lossy().into_owned();
+                let (name, suffix) = split_unique_name(&stem);
+                files.push((String::from(name), suffix, path));
+            }
+        }
+        // In the order the dumps of each name were written.
+        files.sort();
+
+        let mut groups = Vec::new();
+        for (name, _, path) in files {
+            let tx_file = path.file_name().unwrap().to_string_lossy().into_owned();
+            let in_file = |e: Error| match e {
+                Error::InvalidJson(e) => Error::InvalidJson(format!("{}: {}", tx_file, e)),
+                e => e,
+            };
+            let js = json::parse(&fs::read_to_string(&path)?)
+                .map_err(|e| in_file(Error::InvalidJson(e.to_string())))?;
+            if js["mock_info"].is_null() {
+                continue;
+            }
+            let mock_tx = with_bin(&MockTransaction::from_json(&js).map_err(in_file)?, &bin);
+            let resolved_tx = mock_tx.resolve().map_err(in_file)?;
+            let manifest_path = Manifest::path_for(&path);
+            let manifest = if !rerun && manifest_path.is_file() {
+                Some(Manifest::read(&manifest_path).map_err(in_file)?)
+            } else {
+                None
+            };
+            let verifier = TransactionScriptsVerifier::new(&resolved_tx, &mock_tx.mock_info);
+            for (group_index, (script_hash, group)) in
+                script_groups(&resolved_tx).into_iter().enumerate()
+            {
+                let recorded = manifest
+                    .as_ref()
+                    .and_then(|manifest| manifest.groups.get(group_index))
+                    .filter(|recorded| {
+                        recorded.group_type == group.group_type
+                            && recorded.script_hash == script_hash
+                    });
+                let cycles = match recorded {
+                    Some(ManifestGroup {
+                        cycles: Some(cycles),
+                        ..
+                    }) => Ok(*cycles),
+                    Some(ManifestGroup {
+                        error: Some(error), ..
+                    }) => Err(error.clone()),
+                    _ => verifier
+                        .verify_single(group.group_type, &script_hash, self.max_cycles)
+                        .map_err(|e| e.source(&group).to_string()),
+                };
+                groups.push(BaselineGroup {
+                    name: name.clone(),
+                    tx_file: tx_file.clone(),
+                    group_index,
+                    group_type: group.group_type,
+                    script_hash,
+                    cycles,
+                });
+            }
+        }
+        Ok(Baseline { groups })
+    }
+}
@@ -0,0 +1,191 @@
+use ckb_types::{
+    core::ScriptHashType,
+    h256,
+    packed::{Byte32, CellOutput, OutPointVec, Script},
+    prelude::*,
+    H256,
+};
+use std::convert::TryInto;
+
+use crate::{error::fmt_out_point, fmt_vec};
+
+/// The `code_hash` of the Nervos DAO type script, deployed with `hash_type` type on mainnet and
+/// testnet alike.
+pub const DAO_CODE_HASH: H256 =
+    h256!("0x82d76d1b75fe2fd9a27dfbaa65a039221a380d76c926f378d3f81cf3e7e13f2e");
+/// The `code_hash` of the built-in type id script, `TYPE_ID` in ASCII.
+pub const TYPE_ID_CODE_HASH: H256 =
+    h256!("0x00000000000000000000000000000000000000000000000000545950455f4944");
+
+/// Renders the data of cells in a known format, such as token amounts or molecule tables.
+///
+/// Decoders are tried in order by a [`DecoderRegistry`], the first that recognizes a cell wins.
+pub trait CellDataDecoder {
+    /// A short name for the format, shown next to the decoded value.
+    fn name(&self) -> &str;
+
+    /// `None` if the cell is not in this format.
+    fn decode(&self, output: &CellOutput, data: &[u8]) -> Option<String>;
+}
+
+fn is_script(script: &Script, code_hash: &H256, hash_type: ScriptHashType) -> bool {
+    script.code_hash().as_slice() == code_hash.as_bytes() && script.hash_type() == hash_type.into()
+}
+
+/// sUDT and xUDT cells: a little endian `u128` amount in the first 16 bytes of data, under a
+/// type script whose args start with the 32 byte owner lock hash.
+///
+/// Without code hashes any cell of that shape matches. Add the code hashes of the deployed
+/// scripts to only match those.
+#[derive(Clone, Debug, Default)]
+pub struct UdtDecoder {
+    code_hashes: Vec<Byte32>,
+}
+
+impl UdtDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn code_hash(mut self, code_hash: Byte32) -> Self {
+        self.code_hashes.push(code_hash);
+        self
+    }
+}
+
+impl CellDataDecoder for UdtDecoder {
+    fn name(&self) -> &str {
+        "udt"
+    }
+
+    fn decode(&self, output: &CellOutput, data: &[u8]) -> Option<String> {
+        let type_ = output.type_().to_opt()?;
+        if type_.args().raw_data().len() < 32 || data.len() < 16 {
+            return None;
+        }
+        if !self.code_hashes.is_empty() && !self.code_hashes.contains(&type_.code_hash()) {
+            return None;
+        }
+        let amount = u128::from_le_bytes(data[..16].try_into().unwrap());
+        Some(format!(
+            "amount {}, owner lock hash {}",
+            amount,
+            fmt_vec(&type_.args().raw_data()[..32])
+        ))
+    }
+}
+
+/// Nervos DAO cells: 8 bytes of data, zero for a deposit or the block number of the deposit for
+/// a withdrawing cell.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DaoDecoder;
+
+impl CellDataDecoder for DaoDecoder {
+    fn name(&self) -> &str {
+        "dao"
+    }
+
+    fn decode(&self, output: &CellOutput, data: &[u8]) -> Option<String> {
+        let type_ = output.type_().to_opt()?;
+        if !is_script(&type_, &DAO_CODE_HASH, ScriptHashType::Type) {
+            return None;
+        }
+        let data: [u8; 8] = data.try_into().ok()?;
+        match u64::from_le_bytes(data) {
+            0 => Some(String::from("deposit")),
+            number => Some(format!("withdrawing, deposited in block {}", number)),
+        }
+    }
+}
+
+/// Dep group cells: an `OutPointVec` of the cells the group expands to.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DepGroupDecoder;
+
+impl CellDataDecoder for DepGroupDecoder {
+    fn name(&self) -> &str {
+        "dep_group"
+    }
+
+    fn decode(&self, _output: &CellOutput, data: &[u8]) -> Option<String> {
+        let out_points = OutPointVec::from_slice(data).ok()?;
+        if out_points.is_empty() {
+            return None;
+        }
+        Some(
+            out_points
+                .into_iter()
+                .map(|out_point| fmt_out_point(&out_point))
+                .collect::<Vec<_>>()
+                .join(", "),
+        )
+    }
+}
+
+/// Cells under the type id script, identified by its args.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TypeIdDecoder;
+
+impl CellDataDecoder for TypeIdDecoder {
+    fn name(&self) -> &str {
+        "type_id"
+    }
+
+    fn decode(&self, output: &CellOutput, _data: &[u8]) -> Option<String> {
+        let type_ = output.type_().to_opt()?;
+        if !is_script(&type_, &TYPE_ID_CODE_HASH, ScriptHashType::Type) {
+            return None;
+        }
+        Some(format!("id {}", fmt_vec(&type_.args().raw_data())))
+    }
+}
+
+/// The decoders the report and the command-line tool render cell data with.
+///
+/// ```ignore
+/// let decoders = DecoderRegistry::new().register(MyOrderDecoder);
+/// DumpingVerifier::new(&resolved_tx, &data_loader, dump_dir).decoders(decoders)
+/// ```
+pub struct DecoderRegistry {
+    decoders: Vec<Box<dyn CellDataDecoder>>,
+}
+
+impl Default for DecoderRegistry {
+    /// The built-in decoders, for type id, Nervos DAO, UDT and dep group cells in that order.
+    fn default() -> Self {
+        DecoderRegistry::empty()
+            .register(DepGroupDecoder)
+            .register(UdtDecoder::new())
+            .register(DaoDecoder)
+            .register(TypeIdDecoder)
+    }
+}
+
+impl DecoderRegistry {
+    /// A registry with the built-in decoders.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A registry without any decoder.
+    pub fn empty() -> Self {
+        DecoderRegistry {
+            decoders: Vec::new(),
+        }
+    }
+
+    /// Adds a decoder, tried before those already registered.
+    pub fn register<D: CellDataDecoder + 'static>(mut self, decoder: D) -> Self {
+        self.decoders.insert(0, Box::new(decoder));
+        self
+    }
+
+    /// The name and decoded value of the first decoder that recognizes the cell.
+    pub fn decode(&self, output: &CellOutput, data: &[u8]) -> Option<(&str, String)> {
+        self.decoders.iter().find_map(|decoder| {
+            decoder
+                .decode(output, data)
+                .map(|decoded| (decoder.name(), decoded))
+        })
+    }
+}
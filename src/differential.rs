@@ -0,0 +1,332 @@
+use ckb_script::{ScriptGroupType, TransactionScriptsVerifier};
+use ckb_traits::{CellDataProvider, HeaderProvider};
+use ckb_types::{
+    core::{cell::ResolvedTransaction, Cycle},
+    packed::Byte32,
+    prelude::Entity,
+};
+use std::{fmt, path::PathBuf, process::Command};
+
+use crate::{
+    fmt_vec, gen_cmd_args, script_groups, DebugCapture, DumpDir, DumpMode, DumpingVerifier, Error,
+    VerifyEnvironment,
+};
+
+/// Turns script output into the text that is compared, e.g. to drop lines that differ between
+/// the in-process run and ckb-debugger.
+pub struct Normalizer(Box<dyn Fn(&str) -> String>);
+
+impl Normalizer {
+    pub fn new<F: Fn(&str) -> String + 'static>(f: F) -> Self {
+        Normalizer(Box::new(f))
+    }
+
+    /// Compares the output unchanged.
+    pub fn identity() -> Self {
+        Self::new(|output| String::from(output))
+    }
+
+    /// Keeps the output up to and including the last `marker`, or all of it if there is none.
+    pub fn until_last_marker(marker: &str) -> Self {
+        let marker = String::from(marker);
+        Self::new(move |output| match output.rfind(&marker) {
+            Some(i) => String::from(&output[..i + marker.len()]),
+            None => String::from(output),
+        })
+    }
+
+    /// Drops the lines for which `f` returns `false`.
+    pub fn filter_lines<F: Fn(&str) -> bool + 'static>(f: F) -> Self {
+        Self::new(move |output| {
+            output
+                .lines()
+                .filter(|line| f(line))
+                .map(|line| format!("{}\n", line))
+                .collect()
+        })
+    }
+
+    pub fn normalize(&self, output: &str) -> String {
+        (self.0)(output)
+    }
+}
+
+impl Default for Normalizer {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+/// The normalized output and the outcome of a script group, in-process and in ckb-debugger.
+#[derive(Clone, Debug)]
+pub struct GroupComparison {
+    pub group_index: usize,
+    pub group_type: ScriptGroupType,
+    pub script_hash: Byte32,
+    /// The ckb-debugger arguments, without the program.
+    pub args: Vec<String>,
+    pub expected: String,
+    pub actual: String,
+    /// The cycles of the in-process run, or its error.
+    pub result: Result<Cycle, String>,
+    /// The exit code of ckb-debugger, `None` if it was killed by a signal.
+    pub exit_code: Option<i32>,
+    /// What ckb-debugger wrote to stderr, not normalized.
+    pub stderr: String,
+}
+
+impl GroupComparison {
+    /// Whether the group passed in ckb-debugger, that is exited with 0.
+    pub fn debugger_passed(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+
+    /// The output matches, and the group passed or failed in both runs.
+    pub fn is_match(&self) -> bool {
+        self.expected == self.actual && self.result.is_ok() == self.debugger_passed()
+    }
+
+    /// A line diff from the in-process output (`-`) to the ckb-debugger output (`+`).
+    pub fn diff(&self) -> String {
+        line_diff(&self.expected, &self.actual)
+    }
+}
+
+/// The result of a [`DifferentialRunner::run`].
+#[derive(Clone, Debug)]
+pub struct DiffReport {
+    pub tx_file: PathBuf,
+    /// In `group_index` order.
+    pub groups: Vec<GroupComparison>,
+}
+
+impl DiffReport {
+    pub fn is_match(&self) -> bool {
+        self.groups.iter().all(GroupComparison::is_match)
+    }
+
+    /// # Panics
+    ///
+    /// Panics with the diff of every mismatching group.
+    pub fn assert_match(&self) {
+        if !self.is_match() {
+            panic!("{}", self);
+        }
+    }
+}
+
+impl fmt::Display for DiffReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mismatches: Vec<&GroupComparison> = self
+            .groups
+            .iter()
+            .filter(|group| !group.is_match())
+            .collect();
+        if mismatches.is_empty() {
+            return write!(
+                f,
+                "all {} groups of {} match",
+                self.groups.len(),
+                self.tx_file.to_string_lossy()
+            );
+        }
+        write!(
+            f,
+            "{} of {} groups of {} differ from ckb-debugger",
+            mismatches.len(),
+            self.groups.len(),
+            self.tx_file.to_string_lossy()
+        )?;
+        for group in mismatches {
+            write!(
+                f,
+                "\n\ngroup {} ({} {}):",
+                group.group_index,
+                group.group_type,
+                fmt_vec(group.script_hash.as_slice()),
+            )?;
+            if group.result.is_ok() != group.debugger_passed() {
+                let in_process = match &group.result {
+                    Ok(cycles) => format!("passed with {} cycles", cycles),
+                    Err(e) => format!("failed: {}", e),
+                };
+                let debugger = match group.exit_code {
+                    Some(0) => String::from("passed"),
+                    Some(code) => format!("failed with exit code {}", code),
+                    None => String::from("was killed by a signal"),
+                };
+                write!(f, "\nin-process {}, ckb-debugger {}", in_process, debugger)?;
+            }
+            write!(
+                f,
+                "\n--- in-process\n+++ ckb-debugger {}\n{}",
+                group.args.join(" "),
+                group.diff()
+            )?;
+            if !group.stderr.is_empty() {
+                write!(f, "ckb-debugger stderr:\n{}", group.stderr)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Runs every script group of a transaction in-process and in ckb-debugger, and compares their
+/// normalized debug output and whether they pass.
+pub struct DifferentialRunner<'a, DL> {
+    resolved_tx: &'a ResolvedTransaction,
+    data_loader: &'a DL,
+    dump_dir: DumpDir,
+    debugger: String,
+    bin_path: Option<String>,
+    normalizer: Normalizer,
+    environment: Option<VerifyEnvironment>,
+    max_cycles: Cycle,
+}
+
+impl<'a, DL: CellDataProvider + HeaderProvider> DifferentialRunner<'a, DL> {
+    pub fn new(
+        resolved_tx: &'a ResolvedTransaction,
+        data_loader: &'a DL,
+        dump_dir: DumpDir,
+    ) -> Self {
+        DifferentialRunner {
+            resolved_tx,
+            data_loader,
+            dump_dir,
+            debugger: String::from("ckb-debugger"),
+            bin_path: None,
+            normalizer: Normalizer::default(),
+            environment: None,
+            max_cycles: Cycle::MAX,
+        }
+    }
+
+    /// The ckb-debugger executable, `ckb-debugger` from `PATH` by default.
+    pub fn debugger(mut self, debugger: &str) -> Self {
+        self.debugger = String::from(debugger);
+        self
+    }
+
+    /// See [`DumpingVerifier::bin_path`].
+    pub fn bin_path(mut self, bin_path: &str) -> Self {
+        self.bin_path = Some(String::from(bin_path));
+        self
+    }
+
+    pub fn normalizer(mut self, normalizer: Normalizer) -> Self {
+        self.normalizer = normalizer;
+        self
+    }
+
+    /// See [`DumpingVerifier::environment`]. ckb-debugger runs each group on the VM version
+    /// the environment selects.
+    pub fn environment(mut self, environment: VerifyEnvironment) -> Self {
+        self.environment = Some(environment);
+        self
+    }
+
+    /// The cycle limit of each group, run in-process and in ckb-debugger.
+    pub fn max_cycles(mut self, max_cycles: Cycle) -> Self {
+        self.max_cycles = max_cycles;
+        self
+    }
+
+    /// Dumps the transaction, then runs each group in-process and in ckb-debugger with the
+    /// environment and cycle limit of the dump. Groups are compared whether or not they pass.
+    pub fn run(&self) -> Result<DiffReport, Error> {
+        let mut dumper =
+            DumpingVerifier::new(self.resolved_tx, self.data_loader, self.dump_dir.clone())
+                .mode(DumpMode::Never)
+                .max_cycles(self.max_cycles);
+        if let Some(bin_path) = &self.bin_path {
+            dumper = dumper.bin_path(bin_path);
+        }
+        if let Some(environment) = &self.environment {
+            dumper = dumper.environment(environment.clone());
+        }
+        let manifest = dumper.dump()?;
+        let bin_path = self
+            .bin_path
+            .as_ref()
+            .map(std::fs::canonicalize)
+            .transpose()?;
+
+        let capture = DebugCapture::new();
+        let mut verifier = TransactionScriptsVerifier::new(self.resolved_tx, self.data_loader);
+        verifier.set_debug_printer(capture.printer());
+
+        let mut groups = Vec::new();
+        for (group_index, (script_hash, group)) in
+            script_groups(self.resolved_tx).into_iter().enumerate()
+        {
+            capture.set_group_type(group.group_type);
+            let result = verifier
+                .verify_single(group.group_type, &script_hash, self.max_cycles)
+                .map_err(|e| e.source(&group).to_string());
+            let args = gen_cmd_args(
+                &group,
+                bin_path.as_deref(),
+                &manifest.tx_file,
+                None,
+                self.environment.as_ref(),
+                Some(self.max_cycles),
+            )?;
+            let output = Command::new(&self.debugger).args(&args).output()?;
+            groups.push(GroupComparison {
+                group_index,
+                group_type: group.group_type,
+                expected: self
+                    .normalizer
+                    .normalize(&capture.output(group.group_type, &script_hash)),
+                actual: self
+                    .normalizer
+                    .normalize(&String::from_utf8_lossy(&output.stdout)),
+                result,
+                exit_code: output.status.code(),
+                stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+                script_hash,
+                args,
+            });
+        }
+        Ok(DiffReport {
+            tx_file: manifest.tx_file,
+            groups,
+        })
+    }
+}
+
+/// A unified-style line diff of `a` and `b`, based on their longest common subsequence.
+pub(crate) fn line_diff(a: &str, b: &str) -> String {
+    let a: Vec<&str> = a.lines().collect();
+    let b: Vec<&str> = b.lines().collect();
+
+    // lcs[i][j] is the length of the longest common subsequence of a[i..] and b[j..].
+    let mut lcs = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut diff = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            diff.push_str(&format!(" {}\n", a[i]));
+            i += 1;
+            j += 1;
+        } else if j < b.len() && (i == a.len() || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            diff.push_str(&format!("+{}\n", b[j]));
+            j += 1;
+        } else {
+            diff.push_str(&format!("-{}\n", a[i]));
+            i += 1;
+        }
+    }
+    diff
+}
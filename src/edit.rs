@@ -0,0 +1,220 @@
+use ckb_types::{
+    bytes::Bytes,
+    core::HeaderView,
+    packed::{Byte32, CellOutput, Script},
+    prelude::*,
+};
+
+use crate::{fmt_vec, list_script_groups, Error, MockTransaction, ScriptGroupInfo};
+
+/// A lock or type script of the transaction, for [`MockTransaction::set_script_args`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScriptTarget {
+    InputLock(usize),
+    InputType(usize),
+    OutputLock(usize),
+    OutputType(usize),
+}
+
+fn check_index(name: &str, index: usize, len: usize) -> Result<(), Error> {
+    if index < len {
+        Ok(())
+    } else {
+        Err(Error::InvalidEdit(format!(
+            "{}[{}] out of range, the transaction has {}",
+            name, index, len
+        )))
+    }
+}
+
+fn with_args(
+    output: &CellOutput,
+    target: ScriptTarget,
+    name: String,
+    args: Bytes,
+) -> Result<CellOutput, Error> {
+    let with = |script: Script| script.as_builder().args(args.pack()).build();
+    match target {
+        ScriptTarget::InputLock(_) | ScriptTarget::OutputLock(_) => Ok(output
+            .clone()
+            .as_builder()
+            .lock(with(output.lock()))
+            .build()),
+        ScriptTarget::InputType(_) | ScriptTarget::OutputType(_) => {
+            let type_ = output
+                .type_()
+                .to_opt()
+                .ok_or_else(|| Error::InvalidEdit(format!("{} has no type script", name)))?;
+            Ok(output
+                .clone()
+                .as_builder()
+                .type_(Some(with(type_)).pack())
+                .build())
+        }
+    }
+}
+
+/// Edits for what-if debugging: change one thing in a loaded or generated dump and run it again.
+///
+/// Every edit keeps `tx` and `mock_info` in step. Script hashes and groups are derived from the
+/// cells on demand, so [`MockTransaction::groups`] reflects the edits.
+///
+/// ```ignore
+/// let mut mock_tx = MockTransaction::read_from_file("tx.json")?;
+/// mock_tx
+///     .set_witness(0, Bytes::from(signature))
+///     .set_since(1, 0x8000_0000_0000_0010)?
+///     .set_script_args(ScriptTarget::OutputType(0), Bytes::from(owner))?;
+/// ```
+impl MockTransaction {
+    /// Replaces witness `index`, padding the witnesses with empty ones if there are fewer.
+    pub fn set_witness(&mut self, index: usize, witness: Bytes) -> &mut Self {
+        let mut witnesses: Vec<_> = self.tx.witnesses().into_iter().collect();
+        if witnesses.len() <= index {
+            witnesses.resize(index + 1, Bytes::new().pack());
+        }
+        witnesses[index] = witness.pack();
+        self.tx = self
+            .tx
+            .as_advanced_builder()
+            .set_witnesses(witnesses)
+            .build();
+        self
+    }
+
+    /// Replaces the `since` of input `index`.
+    pub fn set_since(&mut self, index: usize, since: u64) -> Result<&mut Self, Error> {
+        check_index("inputs", index, self.mock_info.inputs.len())?;
+        let input = self.mock_info.inputs[index]
+            .input
+            .clone()
+            .as_builder()
+            .since(since.pack())
+            .build();
+        let mut inputs: Vec<_> = self.tx.inputs().into_iter().collect();
+        check_index("tx.inputs", index, inputs.len())?;
+        inputs[index] = input.clone();
+        self.mock_info.inputs[index].input = input;
+        self.tx = self.tx.as_advanced_builder().set_inputs(inputs).build();
+        Ok(self)
+    }
+
+    /// Replaces the cell spent by input `index`, keeping its out point.
+    pub fn set_input_cell(
+        &mut self,
+        index: usize,
+        output: CellOutput,
+        data: Bytes,
+    ) -> Result<&mut Self, Error> {
+        check_index("inputs", index, self.mock_info.inputs.len())?;
+        let input = &mut self.mock_info.inputs[index];
+        input.output = output;
+        input.data = data;
+        Ok(self)
+    }
+
+    /// Replaces the data of the cell spent by input `index`.
+    pub fn set_input_data(&mut self, index: usize, data: Bytes) -> Result<&mut Self, Error> {
+        check_index("inputs", index, self.mock_info.inputs.len())?;
+        self.mock_info.inputs[index].data = data;
+        Ok(self)
+    }
+
+    /// Replaces output `index` and its data.
+    pub fn set_output(
+        &mut self,
+        index: usize,
+        output: CellOutput,
+        data: Bytes,
+    ) -> Result<&mut Self, Error> {
+        check_index("outputs", index, self.tx.outputs().len())?;
+        let mut outputs: Vec<_> = self.tx.outputs().into_iter().collect();
+        let mut outputs_data: Vec<_> = self.tx.outputs_data().into_iter().collect();
+        outputs[index] = output;
+        outputs_data[index] = data.pack();
+        self.tx = self
+            .tx
+            .as_advanced_builder()
+            .set_outputs(outputs)
+            .set_outputs_data(outputs_data)
+            .build();
+        Ok(self)
+    }
+
+    /// Replaces the data of the cell dep `index`, such as the binary a script runs.
+    pub fn set_cell_dep_data(&mut self, index: usize, data: Bytes) -> Result<&mut Self, Error> {
+        check_index("cell_deps", index, self.mock_info.cell_deps.len())?;
+        self.mock_info.cell_deps[index].data = data;
+        Ok(self)
+    }
+
+    /// Replaces the args of a lock or type script, which also changes its script hash and so
+    /// the group it belongs to.
+    pub fn set_script_args(
+        &mut self,
+        target: ScriptTarget,
+        args: Bytes,
+    ) -> Result<&mut Self, Error> {
+        match target {
+            ScriptTarget::InputLock(index) | ScriptTarget::InputType(index) => {
+                check_index("inputs", index, self.mock_info.inputs.len())?;
+                let input = &mut self.mock_info.inputs[index];
+                input.output =
+                    with_args(&input.output, target, format!("inputs[{}]", index), args)?;
+            }
+            ScriptTarget::OutputLock(index) | ScriptTarget::OutputType(index) => {
+                check_index("outputs", index, self.tx.outputs().len())?;
+                let output = self.tx.outputs().get(index).unwrap();
+                let output = with_args(&output, target, format!("outputs[{}]", index), args)?;
+                let mut outputs: Vec<_> = self.tx.outputs().into_iter().collect();
+                outputs[index] = output;
+                self.tx = self.tx.as_advanced_builder().set_outputs(outputs).build();
+            }
+        }
+        Ok(self)
+    }
+
+    /// Adds `header` to the header deps, or does nothing if it is already one.
+    pub fn add_header_dep(&mut self, header: HeaderView) -> &mut Self {
+        if self.tx.header_deps_iter().any(|hash| hash == header.hash()) {
+            return self;
+        }
+        self.tx = self
+            .tx
+            .as_advanced_builder()
+            .header_dep(header.hash())
+            .build();
+        self.mock_info.header_deps.push(header);
+        self
+    }
+
+    /// Removes the header dep with `hash`.
+    pub fn remove_header_dep(&mut self, hash: &Byte32) -> Result<&mut Self, Error> {
+        if !self.tx.header_deps_iter().any(|dep| dep == *hash) {
+            return Err(Error::InvalidEdit(format!(
+                "{} is not a header dep",
+                fmt_vec(hash.as_slice())
+            )));
+        }
+        self.tx = self
+            .tx
+            .as_advanced_builder()
+            .set_header_deps(
+                self.tx
+                    .header_deps_iter()
+                    .filter(|dep| dep != hash)
+                    .collect(),
+            )
+            .build();
+        self.mock_info
+            .header_deps
+            .retain(|header| header.hash() != *hash);
+        Ok(self)
+    }
+
+    /// The script groups of the transaction as edited, see [`list_script_groups`].
+    pub fn groups(&self, bin_path: Option<&str>) -> Result<Vec<ScriptGroupInfo>, Error> {
+        let resolved_tx = self.resolve()?;
+        Ok(list_script_groups(&self.mock_info, &resolved_tx, bin_path))
+    }
+}
@@ -0,0 +1,341 @@
+use ckb_script::{ScriptVersion, TxVerifyEnv};
+use ckb_types::{
+    core::{
+        hardfork::HardForkSwitch, BlockNumber, EpochNumber, EpochNumberWithFraction, HeaderView,
+        ScriptHashType,
+    },
+    packed::{Byte32, Script},
+    prelude::*,
+};
+use json::JsonValue;
+use std::{convert::TryFrom, fmt};
+
+use crate::{
+    fmt_vec,
+    mock_tx::{invalid, parse_str, parse_u64},
+    script_groups, MockTransaction, Since, SinceMetric,
+};
+
+/// The phase of the transaction, as in `TxVerifyEnv`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VerifyPhase {
+    Submitted,
+    /// Proposed this many blocks before the tip.
+    Proposed(BlockNumber),
+    Committed,
+}
+
+/// The `TxVerifyEnv` and hardfork switches a test verified a transaction in, recorded in the
+/// `extension` section of the dump.
+///
+/// `TxVerifyEnv` keeps its phase private, so the environment is built the same way instead and
+/// converted with [`Self::tx_verify_env`]:
+///
+/// ```ignore
+/// let environment = VerifyEnvironment::commit(&tip)
+///     .hardfork_switch(consensus.hardfork_switch().clone());
+/// let tx_env = environment.tx_verify_env();
+/// DumpingVerifier::new(&resolved_tx, &data_loader, dump_dir).environment(environment)
+/// ```
+///
+/// The `TransactionScriptsVerifier` of ckb-script 0.108 selects the VM version from the hash
+/// type alone, so the environment does not change in-process verification. Instead, the
+/// generated ckb-debugger commands pass the VM version it selects as `--script-version`, and
+/// [`Self::warnings`] tells where a reloaded dump would behave differently on chain.
+#[derive(Clone, Debug)]
+pub struct VerifyEnvironment {
+    pub phase: VerifyPhase,
+    /// The tip block number.
+    pub block_number: BlockNumber,
+    /// The tip epoch.
+    pub epoch: EpochNumberWithFraction,
+    /// `None` if the consensus of the test is unknown.
+    pub hardfork_switch: Option<HardForkSwitch>,
+}
+
+/// Where a transaction verified in-process would behave differently in its recorded
+/// [`VerifyEnvironment`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EnvironmentWarning {
+    /// The script runs on another VM version than the in-process verifier uses.
+    ScriptVersion {
+        script_hash: Byte32,
+        version: ScriptVersion,
+        in_process: ScriptVersion,
+    },
+    /// The `data1` hash type is used before rfc 0032 activates at `activation`.
+    Data1NotActive {
+        script_hash: Byte32,
+        activation: EpochNumber,
+    },
+    /// The absolute `since` of an input is not reached at the tip.
+    ImmatureSince { input: usize, since: Since },
+}
+
+impl fmt::Display for EnvironmentWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EnvironmentWarning::ScriptVersion {
+                script_hash,
+                version,
+                in_process,
+            } => write!(
+                f,
+                "script {} runs on VM version {} in the recorded environment, but version {} in-process",
+                fmt_vec(script_hash.as_slice()),
+                *version as u32,
+                *in_process as u32
+            ),
+            EnvironmentWarning::Data1NotActive {
+                script_hash,
+                activation,
+            } => write!(
+                f,
+                "script {} uses hash type data1, which is invalid before rfc 0032 activates at epoch {}",
+                fmt_vec(script_hash.as_slice()),
+                activation
+            ),
+            EnvironmentWarning::ImmatureSince { input, since } => {
+                write!(f, "since of input #{} ({}) is not reached at the tip", input, since)
+            }
+        }
+    }
+}
+
+fn fmt_phase(phase: VerifyPhase) -> String {
+    match phase {
+        VerifyPhase::Submitted => String::from("submitted"),
+        VerifyPhase::Proposed(blocks) => format!("proposed {} blocks ago", blocks),
+        VerifyPhase::Committed => String::from("committed"),
+    }
+}
+
+impl fmt::Display for VerifyEnvironment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}, tip block {}, epoch {} + {}/{}",
+            fmt_phase(self.phase),
+            self.block_number,
+            self.epoch.number(),
+            self.epoch.index(),
+            self.epoch.length()
+        )
+    }
+}
+
+/// The switches of `HardForkSwitch`, by name.
+fn switches(switch: &HardForkSwitch) -> [(&'static str, EpochNumber); 7] {
+    [
+        ("rfc_0028", switch.rfc_0028()),
+        ("rfc_0029", switch.rfc_0029()),
+        ("rfc_0030", switch.rfc_0030()),
+        ("rfc_0031", switch.rfc_0031()),
+        ("rfc_0032", switch.rfc_0032()),
+        ("rfc_0036", switch.rfc_0036()),
+        ("rfc_0038", switch.rfc_0038()),
+    ]
+}
+
+/// The version `TransactionScriptsVerifier::select_version` of ckb-script 0.108 picks.
+fn in_process_version(script: &Script) -> ScriptVersion {
+    match ScriptHashType::try_from(u8::from(script.hash_type())) {
+        Ok(ScriptHashType::Data) => ScriptVersion::V0,
+        _ => ScriptVersion::V1,
+    }
+}
+
+impl VerifyEnvironment {
+    fn new(phase: VerifyPhase, tip: &HeaderView) -> Self {
+        VerifyEnvironment {
+            phase,
+            block_number: tip.number(),
+            epoch: tip.epoch(),
+            hardfork_switch: None,
+        }
+    }
+
+    /// Like `TxVerifyEnv::new_submit`.
+    pub fn submit(tip: &HeaderView) -> Self {
+        Self::new(VerifyPhase::Submitted, tip)
+    }
+
+    /// Like `TxVerifyEnv::new_proposed`.
+    pub fn proposed(tip: &HeaderView, n_blocks: BlockNumber) -> Self {
+        Self::new(VerifyPhase::Proposed(n_blocks), tip)
+    }
+
+    /// Like `TxVerifyEnv::new_commit`.
+    pub fn commit(tip: &HeaderView) -> Self {
+        Self::new(VerifyPhase::Committed, tip)
+    }
+
+    /// The hardfork switches of the consensus, `consensus.hardfork_switch()`.
+    pub fn hardfork_switch(mut self, hardfork_switch: HardForkSwitch) -> Self {
+        self.hardfork_switch = Some(hardfork_switch);
+        self
+    }
+
+    /// The `TxVerifyEnv` of a tip with the recorded number and epoch. Other header fields,
+    /// such as the hashes, are not recorded and left empty.
+    pub fn tx_verify_env(&self) -> TxVerifyEnv {
+        let tip = HeaderView::new_advanced_builder()
+            .number(self.block_number.pack())
+            .epoch(self.epoch.pack())
+            .build();
+        match self.phase {
+            VerifyPhase::Submitted => TxVerifyEnv::new_submit(&tip),
+            VerifyPhase::Proposed(n_blocks) => TxVerifyEnv::new_proposed(&tip, n_blocks),
+            VerifyPhase::Committed => TxVerifyEnv::new_commit(&tip),
+        }
+    }
+
+    /// The epoch the hardfork switches are checked against, as a node does for script versions.
+    pub fn epoch_number(&self) -> EpochNumber {
+        self.tx_verify_env().epoch_number_without_proposal_window()
+    }
+
+    /// The VM version a node with the recorded consensus runs `script` on. `None` without
+    /// hardfork switches, or if the hash type is invalid in the environment.
+    pub fn script_version(&self, script: &Script) -> Option<ScriptVersion> {
+        let switch = self.hardfork_switch.as_ref()?;
+        let v1 = switch.is_vm_version_1_and_syscalls_2_enabled(self.epoch_number());
+        match ScriptHashType::try_from(u8::from(script.hash_type())).ok()? {
+            ScriptHashType::Data => Some(ScriptVersion::V0),
+            ScriptHashType::Data1 if v1 => Some(ScriptVersion::V1),
+            ScriptHashType::Data1 => None,
+            ScriptHashType::Type if v1 => Some(ScriptVersion::V1),
+            ScriptHashType::Type => Some(ScriptVersion::V0),
+        }
+    }
+
+    /// Where `mock_tx` verified in-process differs from a node in this environment: the script
+    /// groups running on another VM version, and the inputs whose absolute block or epoch
+    /// `since` is not reached at the tip.
+    pub fn warnings(&self, mock_tx: &MockTransaction) -> Vec<EnvironmentWarning> {
+        let mut warnings = Vec::new();
+        if let Some(switch) = &self.hardfork_switch {
+            let resolved_tx = match mock_tx.resolve() {
+                Ok(resolved_tx) => resolved_tx,
+                Err(_) => return warnings,
+            };
+            for (script_hash, group) in script_groups(&resolved_tx) {
+                let in_process = in_process_version(&group.script);
+                match self.script_version(&group.script) {
+                    Some(version) if version != in_process => {
+                        warnings.push(EnvironmentWarning::ScriptVersion {
+                            script_hash,
+                            version,
+                            in_process,
+                        })
+                    }
+                    Some(_) => {}
+                    None => warnings.push(EnvironmentWarning::Data1NotActive {
+                        script_hash,
+                        activation: switch.rfc_0032(),
+                    }),
+                }
+            }
+        }
+        for (input, cell_input) in mock_tx.tx.inputs().into_iter().enumerate() {
+            let since = Since(cell_input.since().unpack());
+            if since.is_relative() {
+                continue;
+            }
+            let reached = match since.metric() {
+                Some(SinceMetric::BlockNumber(number)) => self.block_number >= number,
+                Some(SinceMetric::Epoch(epoch)) => self.epoch >= epoch,
+                _ => true,
+            };
+            if !reached {
+                warnings.push(EnvironmentWarning::ImmatureSince { input, since });
+            }
+        }
+        warnings
+    }
+
+    pub fn to_json(&self) -> JsonValue {
+        let mut js = JsonValue::new_object();
+        js["phase"] = match self.phase {
+            VerifyPhase::Submitted => "submitted".into(),
+            VerifyPhase::Proposed(_) => "proposed".into(),
+            VerifyPhase::Committed => "committed".into(),
+        };
+        if let VerifyPhase::Proposed(n_blocks) = self.phase {
+            js["proposed_blocks"] = format!("{:#x}", n_blocks).into();
+        }
+        js["block_number"] = format!("{:#x}", self.block_number).into();
+        js["epoch"] = format!("{:#x}", self.epoch.full_value()).into();
+        if let Some(switch) = &self.hardfork_switch {
+            let mut js_switch = JsonValue::new_object();
+            for (name, epoch) in switches(switch).iter() {
+                js_switch[*name] = format!("{:#x}", epoch).into();
+            }
+            js["hardfork_switch"] = js_switch;
+        }
+        js
+    }
+
+    /// Parses the environment written by [`Self::to_json`], `path` locates `js` in errors.
+    pub fn from_json(js: &JsonValue, path: &str) -> Result<Self, crate::Error> {
+        let field = |name: &str| format!("{}.{}", path, name);
+        let phase = match parse_str(&js["phase"], &field("phase"))? {
+            "submitted" => VerifyPhase::Submitted,
+            "proposed" => VerifyPhase::Proposed(parse_u64(
+                &js["proposed_blocks"],
+                &field("proposed_blocks"),
+            )?),
+            "committed" => VerifyPhase::Committed,
+            _ => {
+                return Err(invalid(
+                    &field("phase"),
+                    "expected submitted, proposed or committed",
+                ))
+            }
+        };
+        let hardfork_switch = match &js["hardfork_switch"] {
+            JsonValue::Null => None,
+            js_switch => {
+                let epoch = |name: &str| {
+                    parse_u64(
+                        &js_switch[name],
+                        &format!("{}.hardfork_switch.{}", path, name),
+                    )
+                };
+                let switch = HardForkSwitch::new_builder()
+                    .rfc_0028(epoch("rfc_0028")?)
+                    .rfc_0029(epoch("rfc_0029")?)
+                    .rfc_0030(epoch("rfc_0030")?)
+                    .rfc_0031(epoch("rfc_0031")?)
+                    .rfc_0032(epoch("rfc_0032")?)
+                    .rfc_0036(epoch("rfc_0036")?)
+                    .rfc_0038(epoch("rfc_0038")?)
+                    .build()
+                    .map_err(|e| invalid(&field("hardfork_switch"), &e))?;
+                Some(switch)
+            }
+        };
+        Ok(VerifyEnvironment {
+            phase,
+            block_number: parse_u64(&js["block_number"], &field("block_number"))?,
+            epoch: EpochNumberWithFraction::from_full_value_unchecked(parse_u64(
+                &js["epoch"],
+                &field("epoch"),
+            )?),
+            hardfork_switch,
+        })
+    }
+
+    /// The hardfork switches by name, with their activation epoch or `None` if disabled.
+    pub fn hardforks(&self) -> Vec<(&'static str, Option<EpochNumber>)> {
+        self.hardfork_switch
+            .as_ref()
+            .map(|switch| {
+                switches(switch)
+                    .iter()
+                    .map(|(name, epoch)| (*name, Some(*epoch).filter(|e| *e != EpochNumber::MAX)))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
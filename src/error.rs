@@ -0,0 +1,78 @@
+use ckb_types::{
+    packed::{Byte32, OutPoint},
+    prelude::Entity,
+};
+use std::{fmt, io};
+
+use crate::{fmt_u32, fmt_vec};
+
+#[derive(Debug)]
+pub enum Error {
+    /// Inputs or cell deps (including the members of dep groups) that could not be resolved.
+    UnresolvedOutPoints(Vec<OutPoint>),
+    /// A dep group cell whose data is not an `OutPointVec`.
+    InvalidDepGroup(OutPoint),
+    /// An out point spent twice by the transaction.
+    DuplicatedInput(OutPoint),
+    /// An out point reported as already spent, other than an input spent twice.
+    DeadCell(OutPoint),
+    /// A header dep that could not be found.
+    UnknownHeader(Byte32),
+    /// Any other resolving error reported by `ckb_types`.
+    Resolve(String),
+    /// A transaction file that cannot be parsed, with the path of the offending field.
+    InvalidJson(String),
+    /// A script group index past the groups of the transaction.
+    UnknownScriptGroup(usize),
+    /// A binary to run from `--bin` that is empty.
+    EmptyBin(String),
+    /// An edit of a mock transaction that refers to a part it does not have.
+    InvalidEdit(String),
+    Io(io::Error),
+}
+
+/// Formats an out point as `<tx hash>:<index>`, as errors and diffs print it.
+pub fn fmt_out_point(out_point: &OutPoint) -> String {
+    format!(
+        "{}:{}",
+        fmt_vec(out_point.tx_hash().as_slice()),
+        fmt_u32(out_point.index().as_slice())
+    )
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::UnresolvedOutPoints(out_points) => {
+                let out_points: Vec<String> = out_points.iter().map(fmt_out_point).collect();
+                write!(f, "unresolved out points: {}", out_points.join(", "))
+            }
+            Error::InvalidDepGroup(out_point) => {
+                write!(f, "invalid dep group: {}", fmt_out_point(out_point))
+            }
+            Error::DuplicatedInput(out_point) => {
+                write!(f, "duplicated input: {}", fmt_out_point(out_point))
+            }
+            Error::DeadCell(out_point) => {
+                write!(f, "dead cell: {}", fmt_out_point(out_point))
+            }
+            Error::UnknownHeader(hash) => {
+                write!(f, "unknown header dep: {}", fmt_vec(hash.as_slice()))
+            }
+            Error::Resolve(e) => write!(f, "resolve transaction failed: {}", e),
+            Error::InvalidJson(e) => write!(f, "invalid transaction file: {}", e),
+            Error::UnknownScriptGroup(index) => write!(f, "no script group {}", index),
+            Error::EmptyBin(path) => write!(f, "empty bin file: {}", path),
+            Error::InvalidEdit(e) => write!(f, "invalid edit: {}", e),
+            Error::Io(e) => write!(f, "io error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
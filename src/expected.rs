@@ -0,0 +1,139 @@
+use ckb_script::ScriptGroupType;
+use ckb_types::{core::Cycle, packed::Byte32, prelude::Entity};
+use json::JsonValue;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use crate::{fmt_vec, write_atomic, Error};
+
+/// Collects the debug output of scripts, keyed by script group type and hash.
+///
+/// The debug printer of ckb-script only passes the script hash, so a script used as both a lock
+/// and a type would print into one entry. Verify group by group and tell the capture which
+/// type of group runs next:
+///
+/// ```ignore
+/// let capture = DebugCapture::new();
+/// verifier.set_debug_printer(capture.printer());
+/// capture.set_group_type(group.group_type);
+/// verifier.verify_single(group.group_type, &script_hash, max_cycles)?;
+/// println!("{}", capture.output(group.group_type, &script_hash));
+/// ```
+///
+/// Until [`Self::set_group_type`] is called, output is recorded for both group types.
+#[derive(Clone, Debug, Default)]
+pub struct DebugCapture {
+    output: Arc<Mutex<HashMap<(ScriptGroupType, Byte32), String>>>,
+    group_type: Arc<Mutex<Option<ScriptGroupType>>>,
+}
+
+impl DebugCapture {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A printer for `set_debug_printer` that appends to this capture.
+    pub fn printer(&self) -> impl Fn(&Byte32, &str) + 'static {
+        let output = Arc::clone(&self.output);
+        let group_type = Arc::clone(&self.group_type);
+        move |script_hash: &Byte32, msg: &str| {
+            let group_types = match *group_type.lock().unwrap() {
+                Some(group_type) => vec![group_type],
+                None => vec![ScriptGroupType::Lock, ScriptGroupType::Type],
+            };
+            let mut output = output.lock().unwrap();
+            for group_type in group_types {
+                output
+                    .entry((group_type, script_hash.clone()))
+                    .or_default()
+                    .push_str(msg)
+            }
+        }
+    }
+
+    /// Records the output printed from now on for groups of `group_type`.
+    pub fn set_group_type(&self, group_type: ScriptGroupType) {
+        *self.group_type.lock().unwrap() = Some(group_type);
+    }
+
+    /// Everything printed by the group of `group_type` and `script_hash` so far.
+    pub fn output(&self, group_type: ScriptGroupType, script_hash: &Byte32) -> String {
+        self.output
+            .lock()
+            .unwrap()
+            .get(&(group_type, script_hash.clone()))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Forgets the output, and the group type set by [`Self::set_group_type`].
+    pub fn clear(&self) {
+        self.output.lock().unwrap().clear();
+        *self.group_type.lock().unwrap() = None;
+    }
+}
+
+/// What a script group did when the transaction was verified in-process.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GroupOutput {
+    pub group_type: ScriptGroupType,
+    pub script_hash: Byte32,
+    /// `None` if the group failed or was not run because an earlier group failed.
+    pub cycles: Option<Cycle>,
+    pub debug_output: String,
+    pub error: Option<String>,
+}
+
+/// The per-group outcome of an in-process verification, written next to a dump as
+/// `<name>.expected.json` so a later ckb-debugger run can be compared against it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Expected {
+    /// In `group_index` order.
+    pub groups: Vec<GroupOutput>,
+}
+
+impl Expected {
+    /// The sidecar path for `tx_file`: `a/b.json` becomes `a/b.expected.json`.
+    pub fn path_for(tx_file: &Path) -> PathBuf {
+        tx_file.with_extension("expected.json")
+    }
+
+    pub fn to_json(&self) -> JsonValue {
+        let mut js = JsonValue::new_object();
+        js["groups"] = JsonValue::Array(
+            self.groups
+                .iter()
+                .enumerate()
+                .map(|(index, group)| {
+                    let mut js_group = JsonValue::new_object();
+                    js_group["index"] = index.into();
+                    js_group["group_type"] = match group.group_type {
+                        ScriptGroupType::Lock => "lock".into(),
+                        ScriptGroupType::Type => "type".into(),
+                    };
+                    js_group["script_hash"] = fmt_vec(group.script_hash.as_slice()).into();
+                    js_group["cycles"] = match group.cycles {
+                        Some(cycles) => cycles.into(),
+                        None => JsonValue::Null,
+                    };
+                    js_group["debug_output"] = group.debug_output.as_str().into();
+                    if let Some(error) = &group.error {
+                        js_group["error"] = error.as_str().into();
+                    }
+                    js_group
+                })
+                .collect(),
+        );
+        js
+    }
+
+    /// Writes the sidecar next to `tx_file` and returns its path.
+    pub fn write(&self, tx_file: &Path) -> Result<PathBuf, Error> {
+        let path = Self::path_for(tx_file);
+        write_atomic(&path, self.to_json().pretty(2).as_bytes())?;
+        Ok(path)
+    }
+}
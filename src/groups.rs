@@ -0,0 +1,182 @@
+use ckb_script::{ScriptGroup, ScriptGroupType};
+use ckb_traits::CellDataProvider;
+use ckb_types::{
+    core::{cell::ResolvedTransaction, ScriptHashType},
+    packed::{Byte32, OutPoint, Script},
+    prelude::Entity,
+};
+use std::{collections::BTreeMap, convert::TryFrom, fmt, path::Path};
+
+use crate::{fmt_vec, gen_cmd_line, get_bin_hash, VerifyEnvironment};
+
+/// Computes the script groups of `resolved_tx` the same way `TransactionScriptsVerifier` does.
+///
+/// Lock groups come first, then type groups, each ordered by script hash, so an index into the
+/// returned vector is interchangeable with an index into `verifier.groups()`.
+pub fn script_groups(resolved_tx: &ResolvedTransaction) -> Vec<(Byte32, ScriptGroup)> {
+    let mut lock_groups: BTreeMap<Byte32, ScriptGroup> = BTreeMap::new();
+    let mut type_groups: BTreeMap<Byte32, ScriptGroup> = BTreeMap::new();
+
+    for (i, cell) in resolved_tx.resolved_inputs.iter().enumerate() {
+        let output = &cell.cell_output;
+        lock_groups
+            .entry(output.calc_lock_hash())
+            .or_insert_with(|| ScriptGroup::from_lock_script(&output.lock()))
+            .input_indices
+            .push(i);
+        if let Some(t) = output.type_().to_opt() {
+            type_groups
+                .entry(t.calc_script_hash())
+                .or_insert_with(|| ScriptGroup::from_type_script(&t))
+                .input_indices
+                .push(i);
+        }
+    }
+    for (i, output) in resolved_tx.transaction.outputs().into_iter().enumerate() {
+        if let Some(t) = output.type_().to_opt() {
+            type_groups
+                .entry(t.calc_script_hash())
+                .or_insert_with(|| ScriptGroup::from_type_script(&t))
+                .output_indices
+                .push(i);
+        }
+    }
+
+    lock_groups.into_iter().chain(type_groups).collect()
+}
+
+/// The cell dep that provides the code of a script group.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CodeDep {
+    /// Index into `resolved_tx.resolved_cell_deps`.
+    pub index: usize,
+    pub out_point: OutPoint,
+    /// Whether the dep holds the binary passed as `bin_path`, whose data is replaced by `0x` in
+    /// the dumped json.
+    pub is_bin: bool,
+}
+
+/// A script group of a transaction, together with what ckb-debugger needs to run it.
+#[derive(Clone, Debug)]
+pub struct ScriptGroupInfo {
+    pub group_type: ScriptGroupType,
+    pub script: Script,
+    pub script_hash: Byte32,
+    /// `None` if no cell dep matches the script's `code_hash`.
+    pub code_dep: Option<CodeDep>,
+    pub input_indices: Vec<usize>,
+    pub output_indices: Vec<usize>,
+    /// Witnesses the group reads through `Source::GroupInput` and `Source::GroupOutput`.
+    pub witness_indices: Vec<usize>,
+}
+
+impl fmt::Display for ScriptGroupInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} {} inputs: {:?}, outputs: {:?}, witnesses: {:?}, code: ",
+            self.group_type,
+            fmt_vec(self.script_hash.as_slice()),
+            self.input_indices,
+            self.output_indices,
+            self.witness_indices,
+        )?;
+        match &self.code_dep {
+            Some(dep) if dep.is_bin => write!(f, "cell_deps[{}] (bin)", dep.index),
+            Some(dep) => write!(f, "cell_deps[{}]", dep.index),
+            None => write!(f, "not found"),
+        }
+    }
+}
+
+impl ScriptGroupInfo {
+    /// The ckb-debugger command line that runs this group from `tx_file`.
+    pub fn command(&self, tx_file: &Path, bin_path: Option<&Path>) -> String {
+        self.command_with_environment(tx_file, bin_path, None)
+    }
+
+    /// Same as [`Self::command`], on the VM version `environment` selects for the script.
+    pub fn command_with_environment(
+        &self,
+        tx_file: &Path,
+        bin_path: Option<&Path>,
+        environment: Option<&VerifyEnvironment>,
+    ) -> String {
+        let group = ScriptGroup {
+            script: self.script.clone(),
+            group_type: self.group_type,
+            input_indices: self.input_indices.clone(),
+            output_indices: self.output_indices.clone(),
+        };
+        gen_cmd_line(&group, bin_path, tx_file, None, environment, None)
+            .expect("cannot get absolute path of the tx file")
+    }
+}
+
+pub(crate) fn find_code_dep<DL: CellDataProvider>(
+    data_loader: &DL,
+    resolved_tx: &ResolvedTransaction,
+    script: &Script,
+    bin_hash: &Option<Byte32>,
+) -> Option<CodeDep> {
+    let hash_type = ScriptHashType::try_from(u8::from(script.hash_type())).ok()?;
+    resolved_tx
+        .resolved_cell_deps
+        .iter()
+        .enumerate()
+        .find(|(_, cell)| match hash_type {
+            ScriptHashType::Type => cell
+                .cell_output
+                .type_()
+                .to_opt()
+                .map(|t| t.calc_script_hash() == script.code_hash())
+                .unwrap_or(false),
+            ScriptHashType::Data | ScriptHashType::Data1 => {
+                data_loader.load_cell_data_hash(cell) == Some(script.code_hash())
+            }
+        })
+        .map(|(index, cell)| CodeDep {
+            index,
+            out_point: cell.out_point.clone(),
+            is_bin: bin_hash.is_some() && data_loader.load_cell_data_hash(cell) == *bin_hash,
+        })
+}
+
+/// Lists the script groups of `resolved_tx`, in the order of [`script_groups`].
+///
+/// The position of a group in the returned vector is the `group_index` to pass to the `gen_json`
+/// functions. If `bin_path` is given, the dep holding that binary is flagged by
+/// [`CodeDep::is_bin`].
+pub fn list_script_groups<DL: CellDataProvider>(
+    data_loader: &DL,
+    resolved_tx: &ResolvedTransaction,
+    bin_path: Option<&str>,
+) -> Vec<ScriptGroupInfo> {
+    let bin_hash = bin_path.map(|path| get_bin_hash(path).expect("read bin file failed"));
+    let witness_count = resolved_tx.transaction.witnesses().len();
+
+    script_groups(resolved_tx)
+        .into_iter()
+        .map(|(script_hash, group)| {
+            let mut witness_indices: Vec<usize> = group
+                .input_indices
+                .iter()
+                .chain(group.output_indices.iter())
+                .cloned()
+                .filter(|i| *i < witness_count)
+                .collect();
+            witness_indices.sort_unstable();
+            witness_indices.dedup();
+
+            ScriptGroupInfo {
+                group_type: group.group_type,
+                code_dep: find_code_dep(data_loader, resolved_tx, &group.script, &bin_hash),
+                script: group.script,
+                script_hash,
+                input_indices: group.input_indices,
+                output_indices: group.output_indices,
+                witness_indices,
+            }
+        })
+        .collect()
+}
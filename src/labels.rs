@@ -0,0 +1,151 @@
+use ckb_types::{
+    packed::{Byte32, OutPoint, Script},
+    prelude::*,
+};
+use json::JsonValue;
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    fmt_vec,
+    mock_tx::{gen_json_outpoint, parse_array, parse_byte32, parse_json_outpoint, parse_str},
+    write_atomic, Error,
+};
+
+/// Human readable names for the cells and scripts of a transaction, such as `alice's sUDT cell`
+/// or `compact_udt_lock`, shown next to out points and script hashes in dumps.
+///
+/// ```ignore
+/// let labels = Labels::new()
+///     .out_point(alice_cell, "alice's sUDT cell")
+///     .script(&lock, "compact_udt_lock");
+/// DumpingVerifier::new(&resolved_tx, &data_loader, dump_dir).labels(labels)
+/// ```
+///
+/// Written next to a dump as `<name>.labels.json` when not empty.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Labels {
+    pub out_points: HashMap<OutPoint, String>,
+    /// Keyed by script hash.
+    pub scripts: HashMap<Byte32, String>,
+}
+
+impl Labels {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Labels the cell at `out_point`, as an input or a cell dep.
+    pub fn out_point(mut self, out_point: OutPoint, label: &str) -> Self {
+        self.out_points.insert(out_point, String::from(label));
+        self
+    }
+
+    /// Labels a lock or type script, and so the script group it forms.
+    pub fn script(self, script: &Script, label: &str) -> Self {
+        self.script_hash(script.calc_script_hash(), label)
+    }
+
+    pub fn script_hash(mut self, script_hash: Byte32, label: &str) -> Self {
+        self.scripts.insert(script_hash, String::from(label));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.out_points.is_empty() && self.scripts.is_empty()
+    }
+
+    pub fn get_out_point(&self, out_point: &OutPoint) -> Option<&str> {
+        self.out_points.get(out_point).map(String::as_str)
+    }
+
+    pub fn get_script(&self, script_hash: &Byte32) -> Option<&str> {
+        self.scripts.get(script_hash).map(String::as_str)
+    }
+
+    /// The sidecar path for `tx_file`: `a/b.json` becomes `a/b.labels.json`.
+    pub fn path_for(tx_file: &Path) -> PathBuf {
+        tx_file.with_extension("labels.json")
+    }
+
+    pub fn to_json(&self) -> JsonValue {
+        let mut out_points: Vec<_> = self.out_points.iter().collect();
+        out_points.sort_by(|(a, _), (b, _)| a.as_slice().cmp(b.as_slice()));
+        let mut scripts: Vec<_> = self.scripts.iter().collect();
+        scripts.sort();
+
+        let mut js = JsonValue::new_object();
+        js["out_points"] = JsonValue::Array(
+            out_points
+                .into_iter()
+                .map(|(out_point, label)| {
+                    let mut js_label = JsonValue::new_object();
+                    js_label["out_point"] = gen_json_outpoint(out_point);
+                    js_label["label"] = label.as_str().into();
+                    js_label
+                })
+                .collect(),
+        );
+        js["scripts"] = JsonValue::Array(
+            scripts
+                .into_iter()
+                .map(|(script_hash, label)| {
+                    let mut js_label = JsonValue::new_object();
+                    js_label["script_hash"] = fmt_vec(script_hash.as_slice()).into();
+                    js_label["label"] = label.as_str().into();
+                    js_label
+                })
+                .collect(),
+        );
+        js
+    }
+
+    /// Parses the JSON of [`Self::to_json`]. Missing sections are treated as empty.
+    pub fn from_json(js: &JsonValue) -> Result<Self, Error> {
+        let mut labels = Labels::new();
+        if !js["out_points"].is_null() {
+            for (i, js_label) in parse_array(&js["out_points"], "out_points")?
+                .iter()
+                .enumerate()
+            {
+                let path = format!("out_points[{}]", i);
+                labels = labels.out_point(
+                    parse_json_outpoint(&js_label["out_point"], &format!("{}.out_point", path))?,
+                    parse_str(&js_label["label"], &format!("{}.label", path))?,
+                );
+            }
+        }
+        if !js["scripts"].is_null() {
+            for (i, js_label) in parse_array(&js["scripts"], "scripts")?.iter().enumerate() {
+                let path = format!("scripts[{}]", i);
+                labels = labels.script_hash(
+                    parse_byte32(&js_label["script_hash"], &format!("{}.script_hash", path))?,
+                    parse_str(&js_label["label"], &format!("{}.label", path))?,
+                );
+            }
+        }
+        Ok(labels)
+    }
+
+    /// Reads the sidecar of `tx_file`, or returns no labels if there is none.
+    pub fn read(tx_file: &Path) -> Result<Self, Error> {
+        match fs::read_to_string(Self::path_for(tx_file)) {
+            Ok(s) => {
+                let js = json::parse(&s).map_err(|e| Error::InvalidJson(e.to_string()))?;
+                Self::from_json(&js)
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Writes the sidecar next to `tx_file` and returns its path.
+    pub fn write(&self, tx_file: &Path) -> Result<PathBuf, Error> {
+        let path = Self::path_for(tx_file);
+        write_atomic(&path, self.to_json().pretty(2).as_bytes())?;
+        Ok(path)
+    }
+}
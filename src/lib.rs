@@ -1,19 +1,67 @@
 use ckb_script::{ScriptGroup, ScriptGroupType, TransactionScriptsVerifier};
 use ckb_traits::{CellDataProvider, HeaderProvider};
 use ckb_types::{
-    core::{
-        cell::{CellMeta, ResolvedTransaction},
-        HeaderView, ScriptHashType,
-    },
-    packed::{Byte, Byte32, CellOutput, OutPoint, Script, Uint32},
-    prelude::{Builder, Entity},
+    core::{cell::ResolvedTransaction, Cycle, HeaderView},
+    packed::{Byte32, CellOutput},
 };
-use json::{self, JsonValue};
-use std::{
-    collections::HashMap,
-    convert::{TryFrom, TryInto},
-    fs::File,
-    io::Read,
+use std::{collections::HashMap, convert::TryInto, fs::File, io::Read, path::Path};
+
+mod baseline;
+mod decoder;
+mod differential;
+mod edit;
+mod environment;
+mod error;
+mod expected;
+mod groups;
+mod labels;
+mod loader;
+mod manifest;
+mod minimize;
+mod mock_tx;
+mod output;
+mod recorder;
+mod rehash;
+mod report;
+mod resolve;
+mod since;
+#[cfg(feature = "testkit")]
+pub mod testkit;
+mod tx_diff;
+mod verifier;
+mod witness;
+
+pub use baseline::{Baseline, BaselineGroup, BaselineRunner, CycleChange};
+pub use ckb_debugger_dumper_macros::dump_on_failure;
+pub use decoder::{
+    CellDataDecoder, DaoDecoder, DecoderRegistry, DepGroupDecoder, TypeIdDecoder, UdtDecoder,
+    DAO_CODE_HASH, TYPE_ID_CODE_HASH,
+};
+pub use differential::{DiffReport, DifferentialRunner, GroupComparison, Normalizer};
+pub use edit::ScriptTarget;
+pub use environment::{EnvironmentWarning, VerifyEnvironment, VerifyPhase};
+pub use error::{fmt_out_point, Error};
+pub use expected::{DebugCapture, Expected, GroupOutput};
+pub use groups::{list_script_groups, script_groups, CodeDep, ScriptGroupInfo};
+pub use labels::Labels;
+pub use loader::{Accessed, RecordingDataLoader, Undeclared};
+pub use manifest::{Manifest, ManifestGroup};
+pub use minimize::{Minimized, Minimizer};
+pub use mock_tx::{parse_byte32, JsonFormat, MockCellDep, MockInfo, MockInput, MockTransaction};
+pub use output::{write_atomic, Dump, DumpDir, DumpOptions};
+pub use recorder::Recording;
+pub use rehash::{Rehash, Rehashed};
+pub use report::Report;
+pub use resolve::{gen_json_from_tx, resolve_transaction, CellLookup, ProviderLookup};
+pub use since::{Since, SinceMetric, SinceWarning};
+pub use tx_diff::{diff_mock_tx, Change, ChangeKind, TxDiff};
+pub use verifier::{
+    verify_and_dump, DumpMode, DumpingVerifier, VerifyError, DUMP_DIR_VAR, DUMP_MODE_VAR,
+    DUMP_REPORT_VAR,
+};
+pub use witness::{
+    annotate_witnesses, WitnessAnnotation, WitnessAnnotations, WitnessConsumer, WitnessField,
+    WitnessProblem,
 };
 
 fn vec_to_slice<T, const N: usize>(v: Vec<T>) -> [T; N] {
@@ -39,299 +87,186 @@ fn fmt_u64(d: &[u8]) -> String {
     format!("0x{:x}", bytes_to_u64(d))
 }
 
-fn fmt_vec(d: &[u8]) -> String {
+/// Formats bytes as `0x` prefixed hex, as dumps write hashes and data.
+pub fn fmt_vec(d: &[u8]) -> String {
     let mut s = String::from("0x");
-    for i in 0..d.len() {
-        s.push_str(&String::from(format!("{:02x}", d[i])));
+    for b in d {
+        s.push_str(&format!("{:02x}", b));
     }
     s
 }
 
-fn u32_to_uint32(d: u32) -> Uint32 {
-    let b = Uint32::new_builder();
-    let d: Vec<Byte> = d
-        .to_le_bytes()
-        .to_vec()
-        .iter()
-        .map(|f| f.clone().into())
-        .collect();
-    let d: [Byte; 4] = vec_to_slice(d);
-    let b = b.set(d);
-    b.build()
-}
+fn get_bin_hash(path: &str) -> Result<Byte32, Error> {
+    let mut file = File::open(path)?;
 
-fn gen_json_script(sc: &Script) -> JsonValue {
-    let mut json = JsonValue::new_object();
-    json["code_hash"] = fmt_vec(sc.code_hash().as_slice()).into();
-    json["hash_type"] = {
-        let t: u8 = sc.hash_type().into();
-        let t = ScriptHashType::try_from(t).clone().unwrap();
-        match t {
-            ScriptHashType::Data => "data".into(),
-            ScriptHashType::Type => "type".into(),
-            ScriptHashType::Data1 => "data1".into(),
-        }
-    };
-    json["args"] = fmt_vec(sc.args().raw_data().to_vec().as_slice()).into();
+    let mut file_buf: Vec<u8> = Vec::new();
+    let size = file.read_to_end(&mut file_buf)?;
+    if size == 0 {
+        return Err(Error::EmptyBin(String::from(path)));
+    }
 
-    json
+    Ok(CellOutput::calc_data_hash(file_buf.as_slice()))
 }
 
-fn gen_json_outpoint(sc: &OutPoint) -> JsonValue {
-    let mut js = JsonValue::new_object();
-    js["index"] = fmt_u32(&sc.index().as_slice()).into();
-    js["tx_hash"] = fmt_vec(sc.tx_hash().as_slice()).into();
-    js
-}
+/// The ckb-debugger arguments that run `script_group` from the json file, on the VM version
+/// `environment` selects and with a cycle limit if given.
+fn gen_cmd_args(
+    script_group: &ScriptGroup,
+    bin_path: Option<&Path>,
+    json_file_name: &Path,
+    dbg_addr: Option<&str>,
+    environment: Option<&VerifyEnvironment>,
+    max_cycles: Option<Cycle>,
+) -> Result<Vec<String>, Error> {
+    let group_type = {
+        match script_group.group_type {
+            ScriptGroupType::Lock => "lock",
+            ScriptGroupType::Type => "type",
+        }
+    };
+    let json_file_name = std::fs::canonicalize(json_file_name)?;
+    let (cell_index, cell_type) = {
+        if !script_group.input_indices.is_empty() {
+            (script_group.input_indices[0], "input")
+        } else {
+            (script_group.output_indices[0], "output")
+        }
+    };
 
-fn gen_json_output(d: &CellOutput) -> JsonValue {
-    let mut js = JsonValue::new_object();
-    js["capacity"] = fmt_u64(&d.capacity().as_slice()).into();
-    js["lock"] = gen_json_script(&d.lock());
-    if d.type_().is_some() {
-        js["type"] = gen_json_script(&d.type_().to_opt().unwrap())
+    let mut args = Vec::new();
+    if let Some(path) = bin_path {
+        args.push(String::from("--bin"));
+        args.push(String::from(path.to_str().unwrap()));
+    }
+    args.extend(
+        [
+            "--tx-file",
+            json_file_name.to_str().unwrap(),
+            "--cell-index",
+            &cell_index.to_string(),
+            "--script-group-type",
+            group_type,
+            "--cell-type",
+            cell_type,
+        ]
+        .iter()
+        .map(|arg| String::from(*arg)),
+    );
+    if let Some(version) = environment.and_then(|env| env.script_version(&script_group.script)) {
+        args.push(String::from("--script-version"));
+        args.push((version as u32).to_string());
+    }
+    if let Some(max_cycles) = max_cycles {
+        args.push(String::from("--max-cycles"));
+        args.push(max_cycles.to_string());
     }
-    js
+    if let Some(addr) = dbg_addr {
+        args.push(String::from("--mode"));
+        args.push(String::from("gdb"));
+        args.push(String::from("--gdb-listen"));
+        args.push(String::from(addr));
+    }
+    Ok(args)
 }
 
-fn gen_json_cell_dep(cell: &CellMeta) -> JsonValue {
-    let mut js_cell = JsonValue::new_object();
-
-    js_cell["out_point"] = gen_json_outpoint(&cell.out_point);
-    js_cell["dep_type"] = "code".into();
-    js_cell
+fn gen_cmd_line(
+    script_group: &ScriptGroup,
+    bin_path: Option<&Path>,
+    json_file_name: &Path,
+    dbg_addr: Option<&str>,
+    environment: Option<&VerifyEnvironment>,
+    max_cycles: Option<Cycle>,
+) -> Result<String, Error> {
+    let mut cmd_line = String::from("ckb-debugger");
+    for arg in gen_cmd_args(
+        script_group,
+        bin_path,
+        json_file_name,
+        dbg_addr,
+        environment,
+        max_cycles,
+    )? {
+        cmd_line.push(' ');
+        cmd_line.push_str(&arg);
+    }
+    Ok(cmd_line)
 }
 
-fn gen_json_cell_dep_group(cell: &CellMeta) -> JsonValue {
-    let mut js_cell = JsonValue::new_object();
-
-    js_cell["out_point"] = gen_json_outpoint(&cell.out_point);
-    js_cell["dep_type"] = "dep_group".into();
-    js_cell
+pub(crate) fn dump_to_path<DL: CellDataProvider + HeaderProvider>(
+    data_loader: Option<&DL>,
+    resolved_tx: &ResolvedTransaction,
+    header_deps: Option<HashMap<Byte32, HeaderView>>,
+    script_group: &ScriptGroup,
+    bin_path: Option<&str>,
+    json_file_name: &Path,
+    options: &DumpOptions,
+) -> Result<String, Error> {
+    let bin_path = bin_path.map(std::fs::canonicalize).transpose()?;
+
+    let mut mock_tx = MockTransaction::build(resolved_tx, &header_deps, data_loader);
+    if let Some(bin_path) = &bin_path {
+        mock_tx.blank_bin_hash(&get_bin_hash(bin_path.to_str().unwrap())?);
+    }
+    mock_tx.environment = options.environment.clone();
+    write_atomic(
+        json_file_name,
+        mock_tx.to_json_string(JsonFormat::Pretty).as_bytes(),
+    )?;
+
+    gen_cmd_line(
+        script_group,
+        bin_path.as_deref(),
+        json_file_name,
+        options.dbg_addr.as_deref(),
+        options.environment.as_ref(),
+        options.max_cycles,
+    )
 }
 
-fn gen_json_data(
+fn dump<DL: CellDataProvider + HeaderProvider>(
+    data_loader: Option<&DL>,
     resolved_tx: &ResolvedTransaction,
-    bin_hash: &Byte32,
-    header_deps: &Option<HashMap<Byte32, HeaderView>>,
-) -> JsonValue {
-    let mut js_root: JsonValue = JsonValue::new_object();
-    js_root["mock_info"] = {
-        let mut js = JsonValue::new_object();
-        js["inputs"] = {
-            let mut js_inputs: Vec<JsonValue> = Vec::new();
-            let mut index: usize = 0;
-            for cell in &resolved_tx.resolved_inputs {
-                js_inputs.push({
-                    let mut js_cell = JsonValue::new_object();
-                    js_cell["input"] = {
-                        let mut js = JsonValue::new_object();
-                        js["since"] = {
-                            let input = resolved_tx.transaction.inputs().get(index).unwrap();
-                            let since = input.since();
-                            fmt_u64(since.as_slice()).into()
-                        };
-                        js["previous_output"] = gen_json_outpoint(&cell.out_point);
-                        js
-                    };
-                    js_cell["output"] = gen_json_output(&cell.cell_output);
-                    js_cell["data"] =
-                        fmt_vec(cell.mem_cell_data.clone().unwrap().to_vec().as_slice()).into();
-                    js_cell
-                });
-                index += 1;
-            }
-            JsonValue::Array(js_inputs)
-        };
-        js["cell_deps"] = {
-            let mut js_celldeps: Vec<JsonValue> = Vec::new();
-            for cell in &resolved_tx.resolved_cell_deps {
-                js_celldeps.push({
-                    let mut js_cell_dep = JsonValue::new_object();
-                    js_cell_dep["cell_dep"] = gen_json_cell_dep(cell);
-                    js_cell_dep["output"] = gen_json_output(&cell.cell_output);
-                    if *bin_hash == cell.mem_cell_data_hash.clone().unwrap() {
-                        js_cell_dep["data"] = "0x".into();
-                    } else {
-                        js_cell_dep["data"] =
-                            fmt_vec(cell.mem_cell_data.clone().unwrap().to_vec().as_slice()).into();
-                    }
-                    js_cell_dep
-                });
-            }
-            for cell in &resolved_tx.resolved_dep_groups {
-                js_celldeps.push({
-                    let mut js_cell_dep = JsonValue::new_object();
-                    js_cell_dep["cell_dep"] = gen_json_cell_dep_group(cell);
-                    js_cell_dep["output"] = gen_json_output(&cell.cell_output);
-                    if *bin_hash == cell.mem_cell_data_hash.clone().unwrap() {
-                        js_cell_dep["data"] = "0x".into();
-                    } else {
-                        js_cell_dep["data"] =
-                            fmt_vec(cell.mem_cell_data.clone().unwrap().to_vec().as_slice()).into();
-                    }
-                    js_cell_dep
-                });
-            }
-            JsonValue::Array(js_celldeps)
-        };
-        let mut js_header_vec: Vec<JsonValue> = Vec::new();
-
-        for i in 0..resolved_tx.transaction.header_deps().len() {
-            js_header_vec.push({
-                let hash = resolved_tx.transaction.header_deps().get(i).unwrap();
-
-                let mut js = JsonValue::new_object();
-                js["hash"] = fmt_vec(hash.as_slice()).into();
-
-                let headers = header_deps.clone().unwrap();
-                let header_data = headers.get(&hash).unwrap();
-                js["version"] =
-                    fmt_u32(header_data.version().to_le_bytes().to_vec().as_slice()).into();
-                js["compact_target"] = fmt_u32(
-                    header_data
-                        .compact_target()
-                        .to_le_bytes()
-                        .to_vec()
-                        .as_slice(),
-                )
-                .into();
-                js["timestamp"] =
-                    fmt_vec(header_data.timestamp().to_le_bytes().to_vec().as_slice()).into();
-                js["number"] =
-                    fmt_u64(header_data.number().to_le_bytes().to_vec().as_slice()).into();
-                js["epoch"] = fmt_u64(&header_data.epoch().index().to_le_bytes()).into();
-                js["parent_hash"] = fmt_vec(header_data.parent_hash().as_slice()).into();
-                js["transactions_root"] =
-                    fmt_vec(header_data.transactions_root().as_slice()).into();
-                js["proposals_hash"] = fmt_vec(header_data.proposals_hash().as_slice()).into();
-                js["extra_hash"] = fmt_vec(header_data.extra_hash().as_slice()).into();
-                js["dao"] = fmt_vec(header_data.dao().as_slice()).into();
-                js["nonce"] = fmt_vec(header_data.nonce().to_le_bytes().to_vec().as_slice()).into();
-
-                js
-            });
-        }
-        js["header_deps"] = JsonValue::Array(js_header_vec);
-
-        js
-    };
-    js_root["tx"] = {
-        let mut js_tx = JsonValue::new_object();
-        js_tx["version"] = fmt_u32(&resolved_tx.transaction.version().to_le_bytes()).into();
-
-        resolved_tx.transaction.version();
-
-        js_tx["cell_deps"] = {
-            let mut cell_deps: Vec<JsonValue> = Vec::new();
-            for cell in &resolved_tx.resolved_cell_deps {
-                let js_cell = gen_json_cell_dep(cell);
-                cell_deps.push(js_cell);
-            }
-            for cell in &resolved_tx.resolved_dep_groups {
-                let js_cell = gen_json_cell_dep_group(cell);
-                cell_deps.push(js_cell);
-            }
-            JsonValue::Array(cell_deps)
-        };
-        let mut js_header_vec: Vec<JsonValue> = Vec::new();
-        for i in 0..resolved_tx.transaction.header_deps().len() {
-            js_header_vec.push(
-                fmt_vec(
-                    resolved_tx
-                        .transaction
-                        .header_deps()
-                        .get(i)
-                        .unwrap()
-                        .as_slice(),
-                )
-                .into(),
-            );
-        }
-        js_tx["header_deps"] = JsonValue::Array(js_header_vec);
-
-        js_tx["inputs"] = {
-            let mut js_inputs: Vec<JsonValue> = Vec::new();
-            let mut index: usize = 0;
-            for cell in &resolved_tx.resolved_inputs {
-                js_inputs.push({
-                    let mut js = JsonValue::new_object();
-                    js["since"] = {
-                        let input = resolved_tx.transaction.inputs().get(index).unwrap();
-                        let since = input.since();
-                        fmt_u64(since.as_slice()).into()
-                    };
-                    js["previous_output"] = gen_json_outpoint(&cell.out_point);
-                    js
-                });
-                index += 1;
-            }
-            JsonValue::Array(js_inputs)
-        };
-        js_tx["outputs"] = {
-            let mut js_output: Vec<JsonValue> = Vec::new();
-
-            let tx_hash = resolved_tx.transaction.hash();
-            let outputs: Vec<CellMeta> = resolved_tx
-                .transaction
-                .outputs_with_data_iter()
-                .enumerate()
-                .map(|(output_index, (cell_output, data))| {
-                    let out_point = OutPoint::new_builder()
-                        .tx_hash(tx_hash.clone())
-                        .index(u32_to_uint32(output_index as u32))
-                        .build();
-                    let data_hash = CellOutput::calc_data_hash(&data);
-                    CellMeta {
-                        cell_output,
-                        out_point,
-                        transaction_info: None,
-                        data_bytes: data.len() as u64,
-                        mem_cell_data: Some(data),
-                        mem_cell_data_hash: Some(data_hash),
-                    }
-                })
-                .collect();
-
-            for cell in &outputs {
-                js_output.push(gen_json_output(&cell.cell_output));
-            }
-            JsonValue::Array(js_output)
-        };
-        js_tx["outputs_data"] = {
-            let mut js_data: Vec<JsonValue> = Vec::new();
-            let output_datas = &resolved_tx.transaction.outputs_data();
-            for i in 0..output_datas.len() {
-                let data = output_datas.get(i).unwrap();
-                js_data.push(fmt_vec(data.as_slice().to_vec().split_at(4).1).into());
-            }
-            JsonValue::Array(js_data)
-        };
-        js_tx["witnesses"] = {
-            let mut js_witness: Vec<JsonValue> = Vec::new();
-            for data in resolved_tx.transaction.witnesses() {
-                let data = data.as_bytes().to_vec().split_at(4).1.to_vec();
-                js_witness.push(fmt_vec(data.as_slice()).into());
-            }
-            JsonValue::Array(js_witness)
-        };
-
-        js_tx
-    };
-
-    js_root
+    header_deps: Option<HashMap<Byte32, HeaderView>>,
+    script_group: &ScriptGroup,
+    bin_path: &str,
+    json_file_name: &str,
+    options: &DumpOptions,
+) -> String {
+    dump_to_path(
+        data_loader,
+        resolved_tx,
+        header_deps,
+        script_group,
+        Some(bin_path),
+        Path::new(json_file_name),
+        options,
+    )
+    .expect("write json failed")
 }
 
-fn get_bin_hash(path: &str) -> Byte32 {
-    let mut file = File::open(path).expect("open bin file failed");
-
-    let mut file_buf: Vec<u8> = Vec::new();
-    let size = file
-        .read_to_end(&mut file_buf)
-        .expect("read bin file failed");
-    assert!(size != 0, "");
-
-    CellOutput::calc_data_hash(file_buf.as_slice())
+fn dump_to_dir<DL: CellDataProvider + HeaderProvider>(
+    data_loader: Option<&DL>,
+    resolved_tx: &ResolvedTransaction,
+    header_deps: Option<HashMap<Byte32, HeaderView>>,
+    script_group: &ScriptGroup,
+    bin_path: Option<&str>,
+    dump_dir: &DumpDir,
+    options: &DumpOptions,
+) -> Result<Dump, Error> {
+    let tx_file = dump_dir.unique_path(Some(&script_group.script.calc_script_hash()), "json");
+    let command = dump_to_path(
+        data_loader,
+        resolved_tx,
+        header_deps,
+        script_group,
+        bin_path,
+        &tx_file,
+        options,
+    )?;
+    Ok(Dump {
+        tx_file: std::fs::canonicalize(tx_file)?,
+        command,
+    })
 }
 
 pub fn gen_json<'a, DL: CellDataProvider + HeaderProvider>(
@@ -343,57 +278,94 @@ pub fn gen_json<'a, DL: CellDataProvider + HeaderProvider>(
     json_file_name: &str,
     dbg_addr: Option<&str>,
 ) -> String {
-    let bin_path = std::fs::canonicalize(bin_path).expect("cannot get absolute path");
-
-    let bin_hash = get_bin_hash(bin_path.to_str().unwrap());
-    let js_root = gen_json_data(resolved_tx, &bin_hash, &header_deps);
-    let path = String::from(json_file_name);
-    let mut fs = File::create(path).expect("create json file failed");
-    js_root.write_pretty(&mut fs, 2).expect("write json failed");
+    gen_json_with_options(
+        verifier,
+        resolved_tx,
+        header_deps,
+        group_index,
+        bin_path,
+        json_file_name,
+        &DumpOptions {
+            dbg_addr: dbg_addr.map(String::from),
+            ..Default::default()
+        },
+    )
+}
 
-    let groups_info: Vec<(ScriptGroupType, &'_ ScriptGroup)> = verifier
-        .groups()
-        .map(|(_f1, f2)| (f2.group_type, f2))
-        .collect();
+/// Same as [`gen_json`], but the command also gets the cycle limit and the VM version of the
+/// environment in `options`, and the environment is recorded in the dump.
+pub fn gen_json_with_options<'a, DL: CellDataProvider + HeaderProvider>(
+    verifier: &TransactionScriptsVerifier<'a, DL>,
+    resolved_tx: &ResolvedTransaction,
+    header_deps: Option<HashMap<Byte32, HeaderView>>,
+    group_index: usize,
+    bin_path: &str,
+    json_file_name: &str,
+    options: &DumpOptions,
+) -> String {
+    let (_, script_group) = verifier.groups().nth(group_index).unwrap();
+    dump(
+        None::<&DL>,
+        resolved_tx,
+        header_deps,
+        script_group,
+        bin_path,
+        json_file_name,
+        options,
+    )
+}
 
-    let (group_script_type, script_group) = {
-        let (t, s) = groups_info.get(group_index).unwrap();
-        (*t, *s)
-    };
-    // assert_eq!(
-    //     script_group.script.code_hash(),
-    //     bin_hash,
-    //     "group_index is not bin_path"
-    // );
-    let group_type = {
-        match group_script_type {
-            ScriptGroupType::Lock => "lock",
-            ScriptGroupType::Type => "type",
-        }
-    };
-    let json_file_name = std::fs::canonicalize(json_file_name).expect("cannot get absolute path");
-    let (cell_index, cell_type) = {
-        if script_group.input_indices.len() > 0 {
-            (script_group.input_indices[0], "input")
-        } else {
-            (script_group.output_indices[0], "output")
-        }
-    };
-    let ckb_dbg_str: String = {
-        if dbg_addr.is_none() {
-            String::new()
-        } else {
-            format!(" --mode gdb --gdb-listen {}", dbg_addr.unwrap())
-        }
-    };
+/// Same as [`gen_json`], but without a `TransactionScriptsVerifier`: the script groups are
+/// computed from `resolved_tx` by [`script_groups`], and cell data or header deps that
+/// `resolved_tx` does not carry are loaded from `data_loader`.
+pub fn gen_json_with_data_loader<DL: CellDataProvider + HeaderProvider>(
+    data_loader: &DL,
+    resolved_tx: &ResolvedTransaction,
+    group_index: usize,
+    bin_path: &str,
+    json_file_name: &str,
+    dbg_addr: Option<&str>,
+) -> String {
+    let groups = script_groups(resolved_tx);
+    let (_, script_group) = groups.get(group_index).unwrap();
+    dump(
+        Some(data_loader),
+        resolved_tx,
+        None,
+        script_group,
+        bin_path,
+        json_file_name,
+        &DumpOptions {
+            dbg_addr: dbg_addr.map(String::from),
+            ..Default::default()
+        },
+    )
+}
 
-    String::from(format!(
-        "ckb-debugger --bin {} --tx-file {} --cell-index {} --script-group-type {} --cell-type {}{}",
-        bin_path.to_str().unwrap(),
-        json_file_name.to_str().unwrap(),
-        cell_index,
-        group_type,
-        cell_type,
-        ckb_dbg_str,
-    ))
+/// Same as [`gen_json_with_data_loader`], but the json file is written into `dump_dir` under a
+/// generated unique name, see [`DumpDir`].
+pub fn gen_json_to_dir<DL: CellDataProvider + HeaderProvider>(
+    data_loader: &DL,
+    resolved_tx: &ResolvedTransaction,
+    group_index: usize,
+    bin_path: &str,
+    dump_dir: &DumpDir,
+    dbg_addr: Option<&str>,
+) -> Result<Dump, Error> {
+    let groups = script_groups(resolved_tx);
+    let (_, script_group) = groups
+        .get(group_index)
+        .ok_or(Error::UnknownScriptGroup(group_index))?;
+    dump_to_dir(
+        Some(data_loader),
+        resolved_tx,
+        None,
+        script_group,
+        Some(bin_path),
+        dump_dir,
+        &DumpOptions {
+            dbg_addr: dbg_addr.map(String::from),
+            ..Default::default()
+        },
+    )
 }
@@ -0,0 +1,268 @@
+use ckb_script::{ScriptError, TransactionScriptError, TransactionScriptsVerifier};
+use ckb_traits::{CellDataProvider, HeaderProvider};
+use ckb_types::{
+    bytes::Bytes,
+    core::{cell::CellMeta, cell::ResolvedTransaction, Capacity, Cycle, DepType, HeaderView},
+    packed::{Byte32, CellDep, CellOutput, OutPoint},
+    prelude::*,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    sync::Mutex,
+};
+
+use crate::{
+    error::fmt_out_point, fmt_vec, groups::find_code_dep, script_groups, MockCellDep,
+    MockTransaction,
+};
+
+/// The cells and headers loaded through a [`RecordingDataLoader`], in first access order.
+#[derive(Clone, Debug, Default)]
+pub struct Accessed {
+    pub cells: Vec<(OutPoint, Bytes)>,
+    pub headers: Vec<HeaderView>,
+}
+
+/// A cell or header a script loaded although the transaction does not declare it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Undeclared {
+    /// Neither an input, a cell dep nor a member of a dep group.
+    Cell(OutPoint),
+    /// Not in `header_deps`.
+    Header(Byte32),
+}
+
+impl fmt::Display for Undeclared {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Undeclared::Cell(out_point) => write!(
+                f,
+                "loaded cell {} is not an input or a cell dep of the transaction",
+                fmt_out_point(out_point)
+            ),
+            Undeclared::Header(hash) => write!(
+                f,
+                "loaded header {} is not a header dep of the transaction",
+                fmt_vec(hash.as_slice())
+            ),
+        }
+    }
+}
+
+/// Wraps a data loader and records every cell and header the verifier requests from it.
+///
+/// Verify with [`Self::verify`], then use [`Self::mock_transaction`] to dump exactly what the
+/// scripts loaded, and [`Self::undeclared`] to find what a script loaded that ckb-debugger will
+/// not see.
+pub struct RecordingDataLoader<'a, DL> {
+    inner: &'a DL,
+    accessed: Mutex<Accessed>,
+    /// The outputs of recorded cells loaded from a `CellMeta`.
+    outputs: Mutex<HashMap<OutPoint, CellOutput>>,
+}
+
+impl<'a, DL: CellDataProvider + HeaderProvider> RecordingDataLoader<'a, DL> {
+    pub fn new(inner: &'a DL) -> Self {
+        RecordingDataLoader {
+            inner,
+            accessed: Mutex::new(Accessed::default()),
+            outputs: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn inner(&self) -> &'a DL {
+        self.inner
+    }
+
+    pub fn accessed(&self) -> Accessed {
+        self.accessed.lock().unwrap().clone()
+    }
+
+    /// Forgets what was recorded so far.
+    pub fn clear(&self) {
+        *self.accessed.lock().unwrap() = Accessed::default();
+        self.outputs.lock().unwrap().clear();
+    }
+
+    /// Verifies `resolved_tx` through the wrapper, group by group with the same cycle
+    /// accounting as `TransactionScriptsVerifier::verify`, and records only what the groups
+    /// loaded.
+    ///
+    /// Creating a `TransactionScriptsVerifier` hashes the data of every cell dep, so what it
+    /// loads is forgotten before the groups run. ckb-script does not request the code of a
+    /// group from the data loader when the resolved cell dep carries its data, so the code dep
+    /// of each group is recorded as it runs.
+    pub fn verify(
+        &self,
+        resolved_tx: &ResolvedTransaction,
+        max_cycles: Cycle,
+    ) -> Result<Cycle, TransactionScriptError> {
+        let verifier = TransactionScriptsVerifier::new(resolved_tx, self);
+        self.clear();
+
+        let mut cycles: Cycle = 0;
+        for (script_hash, group) in script_groups(resolved_tx) {
+            if let Some(code_dep) = find_code_dep(self.inner, resolved_tx, &group.script, &None) {
+                self.load_cell_data(&resolved_tx.resolved_cell_deps[code_dep.index]);
+            }
+            let used = verifier
+                .verify_single(group.group_type, &script_hash, max_cycles - cycles)
+                .map_err(|e| e.source(&group))?;
+            cycles = cycles
+                .checked_add(used)
+                .ok_or_else(|| ScriptError::CyclesOverflow(cycles, used).source(&group))?;
+        }
+        Ok(cycles)
+    }
+
+    fn record_cell_meta(&self, cell: &CellMeta, data: &Option<Bytes>) {
+        if data.is_some() {
+            self.outputs
+                .lock()
+                .unwrap()
+                .insert(cell.out_point.clone(), cell.cell_output.clone());
+        }
+        self.record_cell(&cell.out_point, data);
+    }
+
+    fn record_cell(&self, out_point: &OutPoint, data: &Option<Bytes>) {
+        if let Some(data) = data {
+            let mut accessed = self.accessed.lock().unwrap();
+            if !accessed.cells.iter().any(|(o, _)| o == out_point) {
+                accessed.cells.push((out_point.clone(), data.clone()));
+            }
+        }
+    }
+
+    fn record_header(&self, header: &Option<HeaderView>) {
+        if let Some(header) = header {
+            let mut accessed = self.accessed.lock().unwrap();
+            if !accessed.headers.iter().any(|h| h.hash() == header.hash()) {
+                accessed.headers.push(header.clone());
+            }
+        }
+    }
+
+    /// The recorded cells that `resolved_tx` does not declare, leaving out its own outputs.
+    fn undeclared_cells(&self, resolved_tx: &ResolvedTransaction) -> Vec<(OutPoint, Bytes)> {
+        let declared: HashSet<OutPoint> = resolved_tx
+            .resolved_inputs
+            .iter()
+            .chain(resolved_tx.resolved_cell_deps.iter())
+            .chain(resolved_tx.resolved_dep_groups.iter())
+            .map(|cell| cell.out_point.clone())
+            .collect();
+        let tx_hash = resolved_tx.transaction.hash();
+
+        self.accessed
+            .lock()
+            .unwrap()
+            .cells
+            .iter()
+            .filter(|(out_point, _)| {
+                !declared.contains(out_point) && out_point.tx_hash() != tx_hash
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// The recorded cells and headers that `resolved_tx` does not declare.
+    pub fn undeclared(&self, resolved_tx: &ResolvedTransaction) -> Vec<Undeclared> {
+        let declared_headers: HashSet<Byte32> =
+            resolved_tx.transaction.header_deps_iter().collect();
+
+        let cells = self.undeclared_cells(resolved_tx);
+        let accessed = self.accessed.lock().unwrap();
+        cells
+            .into_iter()
+            .map(|(out_point, _)| Undeclared::Cell(out_point))
+            .chain(
+                accessed
+                    .headers
+                    .iter()
+                    .filter(|header| !declared_headers.contains(&header.hash()))
+                    .map(|header| Undeclared::Header(header.hash())),
+            )
+            .collect()
+    }
+
+    /// Builds the mock transaction of `resolved_tx`, taking the headers from what was recorded.
+    ///
+    /// Recorded cells and headers that the transaction does not declare are appended to
+    /// `mock_info.cell_deps` and `mock_info.header_deps`, but not to `tx`, so ckb-debugger can
+    /// serve them too. A cell loaded by out point alone gets an output with just the capacity its
+    /// data occupies. Data of cells or headers that no script loaded comes from the wrapped data
+    /// loader.
+    pub fn mock_transaction(&self, resolved_tx: &ResolvedTransaction) -> MockTransaction {
+        let accessed = self.accessed();
+        let headers: HashMap<Byte32, HeaderView> = accessed
+            .headers
+            .iter()
+            .map(|header| (header.hash(), header.clone()))
+            .collect();
+
+        let mut mock_tx = MockTransaction::build(resolved_tx, &Some(headers), Some(self.inner));
+        let declared_headers: HashSet<Byte32> =
+            resolved_tx.transaction.header_deps_iter().collect();
+        mock_tx.mock_info.header_deps.extend(
+            accessed
+                .headers
+                .into_iter()
+                .filter(|header| !declared_headers.contains(&header.hash())),
+        );
+        let outputs = self.outputs.lock().unwrap();
+        mock_tx
+            .mock_info
+            .cell_deps
+            .extend(
+                self.undeclared_cells(resolved_tx)
+                    .into_iter()
+                    .map(|(out_point, data)| MockCellDep {
+                        cell_dep: CellDep::new_builder()
+                            .out_point(out_point.clone())
+                            .dep_type(DepType::Code.into())
+                            .build(),
+                        output: outputs.get(&out_point).cloned().unwrap_or_else(|| {
+                            CellOutput::new_builder()
+                                .build_exact_capacity(Capacity::bytes(data.len()).unwrap())
+                                .unwrap()
+                        }),
+                        data,
+                    }),
+            );
+        mock_tx
+    }
+}
+
+impl<'a, DL: CellDataProvider + HeaderProvider> CellDataProvider for RecordingDataLoader<'a, DL> {
+    fn load_cell_data(&self, cell: &CellMeta) -> Option<Bytes> {
+        let data = self.inner.load_cell_data(cell);
+        self.record_cell_meta(cell, &data);
+        data
+    }
+
+    fn load_cell_data_hash(&self, cell: &CellMeta) -> Option<Byte32> {
+        self.load_cell_data(cell)
+            .map(|data| CellOutput::calc_data_hash(&data))
+    }
+
+    fn get_cell_data(&self, out_point: &OutPoint) -> Option<Bytes> {
+        let data = self.inner.get_cell_data(out_point);
+        self.record_cell(out_point, &data);
+        data
+    }
+
+    fn get_cell_data_hash(&self, out_point: &OutPoint) -> Option<Byte32> {
+        self.get_cell_data(out_point)
+            .map(|data| CellOutput::calc_data_hash(&data))
+    }
+}
+
+impl<'a, DL: CellDataProvider + HeaderProvider> HeaderProvider for RecordingDataLoader<'a, DL> {
+    fn get_header(&self, hash: &Byte32) -> Option<HeaderView> {
+        let header = self.inner.get_header(hash);
+        self.record_header(&header);
+        header
+    }
+}
@@ -0,0 +1,663 @@
+use ckb_debugger_dumper::{
+    annotate_witnesses, diff_mock_tx, fmt_out_point, fmt_vec, list_script_groups, parse_byte32,
+    write_atomic, Baseline, BaselineRunner, DecoderRegistry, JsonFormat, Labels, Minimizer,
+    MockTransaction, Rehash, Report, Since,
+};
+use ckb_types::{
+    bytes::Bytes,
+    core::{DepType, ScriptHashType},
+    packed::{Byte32, CellOutput, Script},
+    prelude::*,
+};
+use std::{
+    collections::HashMap,
+    convert::TryFrom,
+    env, fs,
+    path::{Path, PathBuf},
+    process,
+};
+
+const USAGE: &str = "\
+Usage: ckb-debugger-dumper <command> [args]
+
+Commands:
+    show <tx-file>                          Print the transaction as a tree
+    groups <tx-file> [--bin <path>]         List the script groups and their ckb-debugger commands
+    extract-bin <tx-file> <dep-index> <output>
+                                            Write the data of a cell dep to a file
+    replace-bin <tx-file> <dep-index> [<bin>] [--output <path>]
+                                            Replace the data of a cell dep with a binary, or blank
+                                            it to 0x without <bin>. Writes in place by default
+    validate <tx-file> [--bin <path>]       Check that the file can be loaded by ckb-debugger
+    report <tx-file> [--output <path>]      Print a Markdown report of the transaction, or write it
+                                            to <path>
+    diff <before> <after>                   Compare two transaction files field by field. Exits
+                                            with 1 if they differ
+    minimize <tx-file> <group-index> [--bin <path>] [--output <path>]
+                                            Remove what the group does not need to reproduce its
+                                            result. Writes <tx-file>.min.json by default
+    rehash <tx-file> <dep-index> <bin> [--old-hash <hash>] [--rewrite-args] [--output <path>]
+                                            Put a rebuilt binary in a cell dep and point the
+                                            scripts running the old one at it. --rewrite-args
+                                            also updates script hashes embedded in args. Writes
+                                            in place by default
+    baseline <dir> [--bin <path>] [--rerun] [--output <path>]
+                                            Print the cycles of every group of the dumps in <dir>
+                                            as a baseline, or write it to <path>. Cycles recorded
+                                            in manifests are used unless --bin or --rerun is
+                                            given, other groups are run in-process
+    compare-cycles <dir> <baseline> [--bin <path>] [--rerun] [--threshold <percent>]
+                                            Collect the cycles of the dumps in <dir> the same way
+                                            and list the groups whose cycles changed from
+                                            <baseline> by more than <percent>, 0 by default.
+                                            Exits with 1 if any did
+
+show, groups and report name cells and scripts after <tx-file>.labels.json, if present.
+show prints the verification environment recorded in the dump, groups runs scripts on the VM
+version it selects, and validate warns where it differs from in-process verification.
+";
+
+/// ` "label"` if there is one.
+fn fmt_label(label: Option<&str>) -> String {
+    label
+        .map(|label| format!(" {:?}", label))
+        .unwrap_or_default()
+}
+
+fn fmt_script(script: &Script, labels: &Labels) -> String {
+    let hash_type = match ScriptHashType::try_from(u8::from(script.hash_type())) {
+        Ok(ScriptHashType::Data) => "data",
+        Ok(ScriptHashType::Type) => "type",
+        Ok(ScriptHashType::Data1) => "data1",
+        Err(_) => "unknown",
+    };
+    format!(
+        "{}{} (code_hash {}, hash_type {}, args {})",
+        fmt_vec(script.calc_script_hash().as_slice()),
+        fmt_label(labels.get_script(&script.calc_script_hash())),
+        fmt_vec(script.code_hash().as_slice()),
+        hash_type,
+        fmt_vec(&script.args().raw_data())
+    )
+}
+
+fn fmt_data(data: &[u8]) -> String {
+    if data.is_empty() {
+        String::from("0 bytes")
+    } else {
+        format!(
+            "{} bytes, hash {}",
+            data.len(),
+            fmt_vec(CellOutput::calc_data_hash(data).as_slice())
+        )
+    }
+}
+
+fn print_cell(output: &CellOutput, data: &[u8], labels: &Labels, decoders: &DecoderRegistry) {
+    println!(
+        "      capacity {}",
+        Unpack::<u64>::unpack(&output.capacity())
+    );
+    println!("      lock {}", fmt_script(&output.lock(), labels));
+    if let Some(type_) = output.type_().to_opt() {
+        println!("      type {}", fmt_script(&type_, labels));
+    }
+    println!("      data {}", fmt_data(data));
+    if let Some((name, decoded)) = decoders.decode(output, data) {
+        println!("        {} {}", name, decoded);
+    }
+}
+
+/// Splits `args` into positional arguments, `--name value` options and `--name` flags. Flags
+/// are returned as options with an empty value.
+fn parse_args(
+    args: &[String],
+    options: &[&str],
+    flags: &[&str],
+) -> Result<(Vec<String>, HashMap<String, String>), String> {
+    let mut positional = Vec::new();
+    let mut values = HashMap::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if let Some(name) = arg.strip_prefix("--") {
+            if flags.contains(&name) {
+                values.insert(String::from(name), String::new());
+                continue;
+            }
+            if !options.contains(&name) {
+                return Err(format!("unknown option --{}", name));
+            }
+            let value = iter
+                .next()
+                .ok_or_else(|| format!("missing value for --{}", name))?;
+            values.insert(String::from(name), value.clone());
+        } else {
+            positional.push(arg.clone());
+        }
+    }
+    Ok((positional, values))
+}
+
+fn load(tx_file: &str) -> Result<MockTransaction, String> {
+    MockTransaction::read_from_file(tx_file).map_err(|e| format!("{}: {}", tx_file, e))
+}
+
+/// The labels dumped next to `tx_file`, if any.
+fn load_labels(tx_file: &str) -> Result<Labels, String> {
+    Labels::read(Path::new(tx_file)).map_err(|e| {
+        format!(
+            "{}: {}",
+            Labels::path_for(Path::new(tx_file)).to_string_lossy(),
+            e
+        )
+    })
+}
+
+fn parse_dep_index(mock_tx: &MockTransaction, index: &str) -> Result<usize, String> {
+    let index: usize = index
+        .parse()
+        .map_err(|_| format!("invalid dep index {}", index))?;
+    if index >= mock_tx.mock_info.cell_deps.len() {
+        return Err(format!(
+            "dep index {} out of range, the transaction has {} cell deps",
+            index,
+            mock_tx.mock_info.cell_deps.len()
+        ));
+    }
+    Ok(index)
+}
+
+fn canonicalize(path: &str) -> Result<PathBuf, String> {
+    fs::canonicalize(path).map_err(|e| format!("{}: {}", path, e))
+}
+
+fn show(tx_file: &str) -> Result<i32, String> {
+    let mock_tx = load(tx_file)?;
+    let labels = load_labels(tx_file)?;
+    let decoders = DecoderRegistry::new();
+    let tx = &mock_tx.tx;
+
+    println!("transaction {}", fmt_vec(tx.hash().as_slice()));
+    if let Some(environment) = &mock_tx.environment {
+        println!("environment: {}", environment);
+        for (name, epoch) in environment.hardforks() {
+            match epoch {
+                Some(epoch) => println!("  {} from epoch {}", name, epoch),
+                None => println!("  {} disabled", name),
+            }
+        }
+    }
+    println!("inputs:");
+    for (i, input) in mock_tx.mock_info.inputs.iter().enumerate() {
+        let since = Since(input.input.since().unpack());
+        println!(
+            "  #{} {}{} since {:#x} ({})",
+            i,
+            fmt_out_point(&input.input.previous_output()),
+            fmt_label(labels.get_out_point(&input.input.previous_output())),
+            since.0,
+            since
+        );
+        for warning in since.warnings() {
+            println!("    warning: {}", warning);
+        }
+        print_cell(&input.output, &input.data, &labels, &decoders);
+    }
+    println!("cell_deps:");
+    for (i, dep) in mock_tx.mock_info.cell_deps.iter().enumerate() {
+        let dep_type = if dep.cell_dep.dep_type() == DepType::DepGroup.into() {
+            "dep_group"
+        } else {
+            "code"
+        };
+        println!(
+            "  #{} {} {}{}",
+            i,
+            dep_type,
+            fmt_out_point(&dep.cell_dep.out_point()),
+            fmt_label(labels.get_out_point(&dep.cell_dep.out_point()))
+        );
+        print_cell(&dep.output, &dep.data, &labels, &decoders);
+    }
+    println!("header_deps:");
+    for (i, header) in mock_tx.mock_info.header_deps.iter().enumerate() {
+        println!(
+            "  #{} {} number {} epoch {} timestamp {}",
+            i,
+            fmt_vec(header.hash().as_slice()),
+            header.number(),
+            header.epoch(),
+            header.timestamp()
+        );
+    }
+    println!("outputs:");
+    for (i, (output, data)) in tx.outputs_with_data_iter().enumerate() {
+        println!("  #{}", i);
+        print_cell(&output, &data, &labels, &decoders);
+    }
+    println!("witnesses:");
+    match mock_tx.resolve() {
+        Ok(resolved_tx) => {
+            let annotations = annotate_witnesses(&resolved_tx);
+            for witness in &annotations.witnesses {
+                let data = tx.witnesses().get(witness.index).unwrap().raw_data();
+                println!("  #{} {}", witness.index, fmt_data(&data));
+                if let Some(args) = &witness.witness_args {
+                    for (name, field) in [
+                        ("lock", args.lock()),
+                        ("input_type", args.input_type()),
+                        ("output_type", args.output_type()),
+                    ] {
+                        if let Some(field) = field.to_opt() {
+                            println!("      {} {}", name, fmt_data(&field.raw_data()));
+                        }
+                    }
+                }
+                for consumer in &witness.consumers {
+                    println!(
+                        "      read as {} by group #{} {}{}",
+                        consumer.field,
+                        consumer.group_index,
+                        fmt_vec(consumer.script_hash.as_slice()),
+                        fmt_label(labels.get_script(&consumer.script_hash))
+                    );
+                }
+            }
+            for problem in &annotations.problems {
+                println!("  warning: {}", problem);
+            }
+        }
+        Err(_) => {
+            for (i, witness) in tx.witnesses().into_iter().enumerate() {
+                println!("  #{} {}", i, fmt_data(&witness.raw_data()));
+            }
+        }
+    }
+    Ok(0)
+}
+
+fn groups(tx_file: &str, bin_path: Option<&str>) -> Result<i32, String> {
+    let mock_tx = load(tx_file)?;
+    let labels = load_labels(tx_file)?;
+    let resolved_tx = mock_tx.resolve().map_err(|e| e.to_string())?;
+    let bin_path = bin_path.map(canonicalize).transpose()?;
+    let tx_file = canonicalize(tx_file)?;
+
+    let groups = list_script_groups(
+        &mock_tx.mock_info,
+        &resolved_tx,
+        bin_path.as_ref().map(|path| path.to_str().unwrap()),
+    );
+    for (i, group) in groups.iter().enumerate() {
+        let label = labels.get_script(&group.script_hash);
+        println!("#{} {}{}", i, group, fmt_label(label));
+        let command = group.command_with_environment(
+            &tx_file,
+            bin_path.as_deref(),
+            mock_tx.environment.as_ref(),
+        );
+        match label {
+            Some(label) => println!("    {} # {}", command, label),
+            None => println!("    {}", command),
+        }
+    }
+    Ok(0)
+}
+
+fn extract_bin(tx_file: &str, dep_index: &str, output: &str) -> Result<i32, String> {
+    let mock_tx = load(tx_file)?;
+    let dep = &mock_tx.mock_info.cell_deps[parse_dep_index(&mock_tx, dep_index)?];
+    if dep.data.is_empty() {
+        return Err(format!("cell dep {} is blank", dep_index));
+    }
+    fs::write(output, &dep.data).map_err(|e| format!("{}: {}", output, e))?;
+    Ok(0)
+}
+
+fn replace_bin(
+    tx_file: &str,
+    dep_index: &str,
+    bin_path: Option<&str>,
+    output: Option<&str>,
+) -> Result<i32, String> {
+    let mut mock_tx = load(tx_file)?;
+    let index = parse_dep_index(&mock_tx, dep_index)?;
+    let data = match bin_path {
+        Some(bin_path) => {
+            Bytes::from(fs::read(bin_path).map_err(|e| format!("{}: {}", bin_path, e))?)
+        }
+        None => Bytes::new(),
+    };
+    mock_tx
+        .set_cell_dep_data(index, data)
+        .map_err(|e| e.to_string())?;
+    let output = output.unwrap_or(tx_file);
+    write_atomic(
+        Path::new(output),
+        mock_tx.to_json_string(JsonFormat::Pretty).as_bytes(),
+    )
+    .map_err(|e| format!("{}: {}", output, e))?;
+    Ok(0)
+}
+
+fn validate(tx_file: &str, bin_path: Option<&str>) -> Result<i32, String> {
+    let mut mock_tx = load(tx_file)?;
+    // ckb-debugger runs --bin in place of the code of the group under debug, so blank deps
+    // are considered to hold it.
+    if let Some(bin_path) = bin_path {
+        let bin = Bytes::from(fs::read(bin_path).map_err(|e| format!("{}: {}", bin_path, e))?);
+        for dep in mock_tx.mock_info.cell_deps.iter_mut() {
+            if dep.data.is_empty() {
+                dep.data = bin.clone();
+            }
+        }
+    }
+
+    let mut problems = Vec::new();
+    if mock_tx.mock_info.inputs.len() != mock_tx.tx.inputs().len() {
+        problems.push(format!(
+            "mock_info has {} inputs but tx has {}",
+            mock_tx.mock_info.inputs.len(),
+            mock_tx.tx.inputs().len()
+        ));
+    }
+    for (i, (input, mock_input)) in mock_tx
+        .tx
+        .inputs()
+        .into_iter()
+        .zip(mock_tx.mock_info.inputs.iter())
+        .enumerate()
+    {
+        if input.as_slice() != mock_input.input.as_slice() {
+            problems.push(format!("input #{} differs between tx and mock_info", i));
+        }
+        for warning in Since(input.since().unpack()).warnings() {
+            println!("warning: since of input #{}: {}", i, warning);
+        }
+    }
+
+    match mock_tx.resolve() {
+        Ok(resolved_tx) => {
+            let groups = list_script_groups(&mock_tx.mock_info, &resolved_tx, None);
+            for (i, group) in groups.iter().enumerate() {
+                if group.code_dep.is_none() {
+                    problems.push(format!(
+                        "no cell dep provides the code of group #{} {}",
+                        i, group
+                    ));
+                }
+            }
+            for problem in annotate_witnesses(&resolved_tx).problems {
+                println!("warning: {}", problem);
+            }
+            if let Some(environment) = &mock_tx.environment {
+                for warning in environment.warnings(&mock_tx) {
+                    println!("warning: {}", warning);
+                }
+            }
+            if problems.is_empty() {
+                println!(
+                    "ok: {} inputs, {} cell deps, {} header deps, {} script groups",
+                    resolved_tx.resolved_inputs.len(),
+                    mock_tx.mock_info.cell_deps.len(),
+                    mock_tx.mock_info.header_deps.len(),
+                    groups.len()
+                );
+                return Ok(0);
+            }
+        }
+        Err(e) => problems.push(e.to_string()),
+    }
+
+    for problem in problems {
+        println!("error: {}", problem);
+    }
+    Ok(1)
+}
+
+fn minimize(
+    tx_file: &str,
+    group_index: &str,
+    bin_path: Option<&str>,
+    output: Option<&str>,
+) -> Result<i32, String> {
+    let mock_tx = load(tx_file)?;
+    let group_index: usize = group_index
+        .parse()
+        .map_err(|_| format!("invalid group index {}", group_index))?;
+    let mut minimizer = Minimizer::new(&mock_tx, group_index);
+    if let Some(bin_path) = bin_path {
+        minimizer = minimizer.bin_path(bin_path);
+    }
+    let minimized = minimizer.run().map_err(|e| e.to_string())?;
+
+    let output = match output {
+        Some(output) => PathBuf::from(output),
+        None => Path::new(tx_file).with_extension("min.json"),
+    };
+    write_atomic(
+        &output,
+        minimized
+            .mock_tx
+            .to_json_string(JsonFormat::Pretty)
+            .as_bytes(),
+    )
+    .map_err(|e| format!("{}: {}", output.to_string_lossy(), e))?;
+
+    println!(
+        "removed {} parts, group #{} {}",
+        minimized.removed, minimized.group_index, minimized.group
+    );
+    match &minimized.error {
+        Some(error) => println!("fails with: {}", error),
+        None => println!("passes"),
+    }
+    let bin_path = bin_path.map(canonicalize).transpose()?;
+    println!(
+        "{}",
+        minimized.command(
+            &canonicalize(&output.to_string_lossy())?,
+            bin_path.as_deref()
+        )
+    );
+    Ok(0)
+}
+
+fn report(tx_file: &str, output: Option<&str>) -> Result<i32, String> {
+    let mock_tx = load(tx_file)?;
+    let resolved_tx = mock_tx.resolve().map_err(|e| e.to_string())?;
+    let report = Report::with_labels(&mock_tx.mock_info, &resolved_tx, &load_labels(tx_file)?);
+    match output {
+        Some(output) => write_atomic(Path::new(output), report.markdown.as_bytes())
+            .map_err(|e| format!("{}: {}", output, e))?,
+        None => print!("{}", report),
+    }
+    Ok(0)
+}
+
+fn diff(before: &str, after: &str) -> Result<i32, String> {
+    let diff = diff_mock_tx(&load(before)?, &load(after)?);
+    println!("{}", diff);
+    Ok(if diff.is_empty() { 0 } else { 1 })
+}
+
+fn run_baseline(dir: &str, bin_path: Option<&str>, rerun: bool) -> Result<Baseline, String> {
+    let mut runner = BaselineRunner::new(dir).rerun(rerun);
+    if let Some(bin_path) = bin_path {
+        runner = runner.bin_path(bin_path);
+    }
+    runner.run().map_err(|e| format!("{}: {}", dir, e))
+}
+
+fn baseline(
+    dir: &str,
+    bin_path: Option<&str>,
+    rerun: bool,
+    output: Option<&str>,
+) -> Result<i32, String> {
+    let baseline = run_baseline(dir, bin_path, rerun)?;
+    match output {
+        Some(output) => baseline
+            .write(output)
+            .map_err(|e| format!("{}: {}", output, e))?,
+        None => println!("{}", baseline.to_json().pretty(2)),
+    }
+    Ok(0)
+}
+
+fn compare_cycles(
+    dir: &str,
+    baseline_file: &str,
+    bin_path: Option<&str>,
+    rerun: bool,
+    threshold: Option<&str>,
+) -> Result<i32, String> {
+    let threshold = match threshold {
+        Some(threshold) => threshold
+            .parse::<f64>()
+            .ok()
+            .filter(|threshold| *threshold >= 0.0)
+            .ok_or_else(|| format!("invalid threshold {}", threshold))?,
+        None => 0.0,
+    };
+    let baseline =
+        Baseline::read(baseline_file).map_err(|e| format!("{}: {}", baseline_file, e))?;
+    let changes = baseline.compare(&run_baseline(dir, bin_path, rerun)?, threshold);
+    if changes.is_empty() {
+        println!("no changes");
+        return Ok(0);
+    }
+    for change in &changes {
+        println!("{}", change);
+    }
+    Ok(1)
+}
+
+fn parse_hash(hash: &str) -> Result<Byte32, String> {
+    parse_byte32(&hash.into(), "hash").map_err(|_| format!("invalid hash {}", hash))
+}
+
+fn rehash(
+    tx_file: &str,
+    dep_index: &str,
+    bin_path: &str,
+    old_hash: Option<&str>,
+    rewrite_args: bool,
+    output: Option<&str>,
+) -> Result<i32, String> {
+    let mut mock_tx = load(tx_file)?;
+    let index = parse_dep_index(&mock_tx, dep_index)?;
+    let old_hash = match old_hash {
+        Some(old_hash) => parse_hash(old_hash)?,
+        None if mock_tx.mock_info.cell_deps[index].data.is_empty() => {
+            return Err(format!(
+                "cell dep {} is blank, pass the code hash it had with --old-hash",
+                dep_index
+            ))
+        }
+        None => CellOutput::calc_data_hash(&mock_tx.mock_info.cell_deps[index].data),
+    };
+    let bin = Bytes::from(fs::read(bin_path).map_err(|e| format!("{}: {}", bin_path, e))?);
+
+    let rehashed = Rehash::new(old_hash.clone(), bin)
+        .dep(index)
+        .rewrite_args(rewrite_args)
+        .apply(&mut mock_tx)
+        .map_err(|e| e.to_string())?;
+    let output = output.unwrap_or(tx_file);
+    write_atomic(
+        Path::new(output),
+        mock_tx.to_json_string(JsonFormat::Pretty).as_bytes(),
+    )
+    .map_err(|e| format!("{}: {}", output, e))?;
+
+    println!(
+        "code hash {} -> {}",
+        fmt_vec(old_hash.as_slice()),
+        fmt_vec(rehashed.new_code_hash.as_slice())
+    );
+    println!("cell deps {:?}", rehashed.deps);
+    for (old, new) in &rehashed.script_hashes {
+        println!(
+            "script {} -> {}",
+            fmt_vec(old.as_slice()),
+            fmt_vec(new.as_slice())
+        );
+    }
+    Ok(0)
+}
+
+fn run(args: &[String]) -> Result<i32, String> {
+    let command = match args.first() {
+        Some(command) => command.as_str(),
+        None => {
+            eprint!("{}", USAGE);
+            return Ok(2);
+        }
+    };
+    let (positional, options) = parse_args(
+        &args[1..],
+        &["bin", "output", "old-hash", "threshold"],
+        &["rewrite-args", "rerun"],
+    )?;
+    let arg = |i: usize| -> Result<&str, String> {
+        positional
+            .get(i)
+            .map(String::as_str)
+            .ok_or_else(|| format!("missing arguments for {}\n\n{}", command, USAGE))
+    };
+    let option = |name: &str| options.get(name).map(String::as_str);
+
+    match command {
+        "show" => show(arg(0)?),
+        "groups" => groups(arg(0)?, option("bin")),
+        "extract-bin" => extract_bin(arg(0)?, arg(1)?, arg(2)?),
+        "replace-bin" => replace_bin(
+            arg(0)?,
+            arg(1)?,
+            positional.get(2).map(String::as_str),
+            option("output"),
+        ),
+        "validate" => validate(arg(0)?, option("bin")),
+        "report" => report(arg(0)?, option("output")),
+        "diff" => diff(arg(0)?, arg(1)?),
+        "minimize" => minimize(arg(0)?, arg(1)?, option("bin"), option("output")),
+        "rehash" => rehash(
+            arg(0)?,
+            arg(1)?,
+            arg(2)?,
+            option("old-hash"),
+            option("rewrite-args").is_some(),
+            option("output"),
+        ),
+        "baseline" => baseline(
+            arg(0)?,
+            option("bin"),
+            option("rerun").is_some(),
+            option("output"),
+        ),
+        "compare-cycles" => compare_cycles(
+            arg(0)?,
+            arg(1)?,
+            option("bin"),
+            option("rerun").is_some(),
+            option("threshold"),
+        ),
+        "help" | "--help" | "-h" => {
+            print!("{}", USAGE);
+            Ok(0)
+        }
+        _ => Err(format!("unknown command {}\n\n{}", command, USAGE)),
+    }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    match run(&args) {
+        Ok(code) => process::exit(code),
+        Err(e) => {
+            eprintln!("error: {}", e);
+            process::exit(1);
+        }
+    }
+}
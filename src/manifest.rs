@@ -0,0 +1,154 @@
+use ckb_script::ScriptGroupType;
+use ckb_types::{core::Cycle, packed::Byte32, prelude::Entity};
+use json::JsonValue;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    fmt_vec,
+    mock_tx::{invalid, parse_array, parse_byte32, parse_str},
+    write_atomic, Error,
+};
+
+/// A script group of a dumped transaction and how to run it in ckb-debugger.
+#[derive(Clone, Debug)]
+pub struct ManifestGroup {
+    pub group_type: ScriptGroupType,
+    pub script_hash: Byte32,
+    pub command: String,
+    /// The verification error of the group, if it failed.
+    pub error: Option<String>,
+    /// The [`Labels`](crate::Labels) entry of the group's script.
+    pub label: Option<String>,
+    /// The cycles the group consumed in-process, `None` if it failed or was not verified.
+    pub cycles: Option<Cycle>,
+    /// The cycles left for the group by the limit of the transaction, passed to ckb-debugger
+    /// as `--max-cycles`.
+    pub max_cycles: Option<Cycle>,
+}
+
+impl ManifestGroup {
+    /// The command with the label and error of the group, if any, as a trailing shell comment.
+    pub fn commented_command(&self) -> String {
+        let comment: Vec<String> = self
+            .label
+            .iter()
+            .cloned()
+            .chain(self.error.iter().map(|error| format!("failed: {}", error)))
+            .collect();
+        if comment.is_empty() {
+            self.command.clone()
+        } else {
+            format!("{} # {}", self.command, comment.join(", "))
+        }
+    }
+}
+
+/// Describes a dumped transaction file, written next to it as `<name>.manifest.json`.
+#[derive(Clone, Debug)]
+pub struct Manifest {
+    pub tx_file: PathBuf,
+    /// In `group_index` order.
+    pub groups: Vec<ManifestGroup>,
+}
+
+impl Manifest {
+    /// The manifest path for `tx_file`: `a/b.json` becomes `a/b.manifest.json`.
+    pub fn path_for(tx_file: &Path) -> PathBuf {
+        tx_file.with_extension("manifest.json")
+    }
+
+    pub fn to_json(&self) -> JsonValue {
+        let mut js = JsonValue::new_object();
+        js["tx_file"] = self.tx_file.to_string_lossy().as_ref().into();
+        js["groups"] = JsonValue::Array(
+            self.groups
+                .iter()
+                .enumerate()
+                .map(|(index, group)| {
+                    let mut js_group = JsonValue::new_object();
+                    js_group["index"] = index.into();
+                    js_group["group_type"] = match group.group_type {
+                        ScriptGroupType::Lock => "lock".into(),
+                        ScriptGroupType::Type => "type".into(),
+                    };
+                    js_group["script_hash"] = fmt_vec(group.script_hash.as_slice()).into();
+                    js_group["command"] = group.command.as_str().into();
+                    if let Some(label) = &group.label {
+                        js_group["label"] = label.as_str().into();
+                    }
+                    if let Some(cycles) = group.cycles {
+                        js_group["cycles"] = cycles.into();
+                    }
+                    if let Some(max_cycles) = group.max_cycles {
+                        js_group["max_cycles"] = max_cycles.into();
+                    }
+                    if let Some(error) = &group.error {
+                        js_group["error"] = error.as_str().into();
+                    }
+                    js_group
+                })
+                .collect(),
+        );
+        js
+    }
+
+    /// Parses the JSON of [`Self::to_json`].
+    pub fn from_json(js: &JsonValue) -> Result<Self, Error> {
+        let groups = parse_array(&js["groups"], "groups")?
+            .iter()
+            .enumerate()
+            .map(|(i, js_group)| {
+                let field = |name: &str| format!("groups[{}].{}", i, name);
+                let optional_str = |name: &str| -> Result<Option<String>, Error> {
+                    match &js_group[name] {
+                        JsonValue::Null => Ok(None),
+                        js => Ok(Some(String::from(parse_str(js, &field(name))?))),
+                    }
+                };
+                let optional_u64 = |name: &str| -> Result<Option<Cycle>, Error> {
+                    match &js_group[name] {
+                        JsonValue::Null => Ok(None),
+                        js => js
+                            .as_u64()
+                            .map(Some)
+                            .ok_or_else(|| invalid(&field(name), "expected a number")),
+                    }
+                };
+                Ok(ManifestGroup {
+                    group_type: match parse_str(&js_group["group_type"], &field("group_type"))? {
+                        "lock" => ScriptGroupType::Lock,
+                        "type" => ScriptGroupType::Type,
+                        _ => return Err(invalid(&field("group_type"), "expected lock or type")),
+                    },
+                    script_hash: parse_byte32(&js_group["script_hash"], &field("script_hash"))?,
+                    command: String::from(parse_str(&js_group["command"], &field("command"))?),
+                    error: optional_str("error")?,
+                    label: optional_str("label")?,
+                    cycles: optional_u64("cycles")?,
+                    max_cycles: optional_u64("max_cycles")?,
+                })
+            })
+            .collect::<Result<_, Error>>()?;
+        Ok(Manifest {
+            tx_file: PathBuf::from(parse_str(&js["tx_file"], "tx_file")?),
+            groups,
+        })
+    }
+
+    /// Reads a manifest written by [`Self::write`].
+    pub fn read(path: &Path) -> Result<Self, Error> {
+        let js = json::parse(&fs::read_to_string(path)?)
+            .map_err(|e| Error::InvalidJson(e.to_string()))?;
+        Self::from_json(&js)
+    }
+
+    /// Writes the manifest next to its tx file and returns the manifest path.
+    pub fn write(&self) -> Result<PathBuf, Error> {
+        let path = Self::path_for(&self.tx_file);
+        write_atomic(&path, self.to_json().pretty(2).as_bytes())?;
+        Ok(path)
+    }
+}
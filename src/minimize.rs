@@ -0,0 +1,290 @@
+use ckb_script::{ScriptGroupType, TransactionScriptsVerifier};
+use ckb_types::{
+    bytes::Bytes,
+    core::{Cycle, DepType, TransactionView},
+    packed::{Byte32, CellOutput, OutPoint, OutPointVec},
+    prelude::*,
+};
+use std::{collections::HashSet, fs, path::Path};
+
+use crate::{list_script_groups, script_groups, Error, MockInfo, MockTransaction, ScriptGroupInfo};
+
+/// Whether a group passed, or the error it failed with.
+type Outcome = Result<(), String>;
+
+/// A part of the transaction the minimizer tries to remove, by index into `tx`.
+#[derive(Clone, Copy, Debug)]
+enum Part {
+    Input(usize),
+    Output(usize),
+    Witness(usize),
+    CellDep(usize),
+    HeaderDep(usize),
+}
+
+/// Removes the inputs, outputs, witnesses, cell deps and header deps of a transaction that one
+/// script group does not need to reproduce its result.
+///
+/// A part is dropped when the group, run in-process on the transaction without it, still passes
+/// or still fails with the same error. The inputs and outputs of the group itself are always
+/// kept.
+pub struct Minimizer<'a> {
+    mock_tx: &'a MockTransaction,
+    group_index: usize,
+    bin_path: Option<String>,
+    max_cycles: Cycle,
+}
+
+/// The result of [`Minimizer::run`].
+#[derive(Clone, Debug)]
+pub struct Minimized {
+    pub mock_tx: MockTransaction,
+    /// The index of the group in the minimized transaction.
+    pub group_index: usize,
+    pub group: ScriptGroupInfo,
+    /// The number of inputs, outputs, witnesses, cell deps and header deps removed.
+    pub removed: usize,
+    /// `None` if the group fails.
+    pub cycles: Option<Cycle>,
+    /// The error the group fails with.
+    pub error: Option<String>,
+}
+
+impl Minimized {
+    /// The ckb-debugger command line that runs the group from `tx_file`.
+    pub fn command(&self, tx_file: &Path, bin_path: Option<&Path>) -> String {
+        self.group
+            .command_with_environment(tx_file, bin_path, self.mock_tx.environment.as_ref())
+    }
+}
+
+impl<'a> Minimizer<'a> {
+    /// `group_index` selects the group like the `group_index` of [`gen_json`](crate::gen_json).
+    pub fn new(mock_tx: &'a MockTransaction, group_index: usize) -> Self {
+        Minimizer {
+            mock_tx,
+            group_index,
+            bin_path: None,
+            max_cycles: Cycle::MAX,
+        }
+    }
+
+    /// The binary to run in place of blanked cell deps, as with ckb-debugger's `--bin`.
+    pub fn bin_path(mut self, bin_path: &str) -> Self {
+        self.bin_path = Some(String::from(bin_path));
+        self
+    }
+
+    pub fn max_cycles(mut self, max_cycles: Cycle) -> Self {
+        self.max_cycles = max_cycles;
+        self
+    }
+
+    pub fn run(&self) -> Result<Minimized, Error> {
+        let bin = self.bin_path.as_ref().map(fs::read).transpose()?;
+        let bin = bin.map(Bytes::from);
+
+        let resolved_tx = with_bin(self.mock_tx, &bin).resolve()?;
+        let (script_hash, group) = script_groups(&resolved_tx)
+            .into_iter()
+            .nth(self.group_index)
+            .ok_or(Error::UnknownScriptGroup(self.group_index))?;
+        let group_type = group.group_type;
+        let expected = self
+            .outcome(self.mock_tx, &bin, group_type, &script_hash)
+            .expect("the group resolves");
+
+        // The cells of the group are what is being debugged.
+        let kept_inputs: HashSet<OutPoint> = group
+            .input_indices
+            .iter()
+            .map(|i| resolved_tx.resolved_inputs[*i].out_point.clone())
+            .collect();
+
+        let mut mock_tx = self.mock_tx.clone();
+        let mut removed = 0;
+        loop {
+            let mut changed = false;
+            let tx = &mock_tx.tx;
+            // From the last index down, so a removal does not shift the parts still to try.
+            let parts = (0..tx.inputs().len())
+                .rev()
+                .filter(|i| {
+                    let input = tx.inputs().get(*i).unwrap();
+                    !kept_inputs.contains(&input.previous_output())
+                })
+                .map(Part::Input)
+                .chain(
+                    (0..tx.outputs().len())
+                        .rev()
+                        .filter(|i| !has_type(&tx.outputs().get(*i).unwrap(), &script_hash))
+                        .map(Part::Output),
+                )
+                .chain((0..tx.witnesses().len()).rev().map(Part::Witness))
+                .chain((0..tx.cell_deps().len()).rev().map(Part::CellDep))
+                .chain((0..tx.header_deps().len()).rev().map(Part::HeaderDep))
+                .collect::<Vec<_>>();
+            for part in parts {
+                let candidate = remove(&mock_tx, part);
+                if self.outcome(&candidate, &bin, group_type, &script_hash)
+                    == Some(expected.clone())
+                {
+                    mock_tx = candidate;
+                    removed += 1;
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+
+        let verified = with_bin(&mock_tx, &bin);
+        let resolved_tx = verified.resolve()?;
+        let (group_index, group) =
+            list_script_groups(&verified.mock_info, &resolved_tx, self.bin_path.as_deref())
+                .into_iter()
+                .enumerate()
+                .find(|(_, group)| {
+                    group.group_type == group_type && group.script_hash == script_hash
+                })
+                .expect("the group is kept");
+        let cycles = TransactionScriptsVerifier::new(&resolved_tx, &verified.mock_info)
+            .verify_single(group_type, &script_hash, self.max_cycles)
+            .ok();
+
+        Ok(Minimized {
+            mock_tx,
+            group_index,
+            group,
+            removed,
+            cycles,
+            error: expected.err(),
+        })
+    }
+
+    /// Runs the group on `mock_tx`, or returns `None` if the transaction no longer resolves or
+    /// no longer has the group.
+    fn outcome(
+        &self,
+        mock_tx: &MockTransaction,
+        bin: &Option<Bytes>,
+        group_type: ScriptGroupType,
+        script_hash: &Byte32,
+    ) -> Option<Outcome> {
+        let mock_tx = with_bin(mock_tx, bin);
+        let resolved_tx = mock_tx.resolve().ok()?;
+        if !script_groups(&resolved_tx)
+            .iter()
+            .any(|(hash, group)| hash == script_hash && group.group_type == group_type)
+        {
+            return None;
+        }
+        let verifier = TransactionScriptsVerifier::new(&resolved_tx, &mock_tx.mock_info);
+        Some(
+            verifier
+                .verify_single(group_type, script_hash, self.max_cycles)
+                .map(|_| ())
+                .map_err(|e| e.to_string()),
+        )
+    }
+}
+
+/// `mock_tx` with the data of its blanked cell deps replaced by `bin`.
+pub(crate) fn with_bin(mock_tx: &MockTransaction, bin: &Option<Bytes>) -> MockTransaction {
+    let mut mock_tx = mock_tx.clone();
+    if let Some(bin) = bin {
+        for dep in mock_tx.mock_info.cell_deps.iter_mut() {
+            if dep.data.is_empty() {
+                dep.data = bin.clone();
+            }
+        }
+    }
+    mock_tx
+}
+
+fn has_type(output: &CellOutput, script_hash: &Byte32) -> bool {
+    output
+        .type_()
+        .to_opt()
+        .map(|t| t.calc_script_hash() == *script_hash)
+        .unwrap_or(false)
+}
+
+fn remove_at<T>(items: impl Iterator<Item = T>, index: usize) -> Vec<T> {
+    items
+        .enumerate()
+        .filter(|(i, _)| *i != index)
+        .map(|(_, item)| item)
+        .collect()
+}
+
+/// `mock_tx` without `part`.
+fn remove(mock_tx: &MockTransaction, part: Part) -> MockTransaction {
+    let tx = &mock_tx.tx;
+    let builder = tx.as_advanced_builder();
+    let tx = match part {
+        Part::Input(i) => builder
+            .set_inputs(remove_at(tx.inputs().into_iter(), i))
+            .build(),
+        Part::Output(i) => builder
+            .set_outputs(remove_at(tx.outputs().into_iter(), i))
+            .set_outputs_data(remove_at(tx.outputs_data().into_iter(), i))
+            .build(),
+        Part::Witness(i) => builder
+            .set_witnesses(remove_at(tx.witnesses().into_iter(), i))
+            .build(),
+        Part::CellDep(i) => builder
+            .set_cell_deps(remove_at(tx.cell_deps().into_iter(), i))
+            .build(),
+        Part::HeaderDep(i) => builder
+            .set_header_deps(remove_at(tx.header_deps().into_iter(), i))
+            .build(),
+    };
+    MockTransaction {
+        mock_info: prune(&mock_tx.mock_info, &tx),
+        tx,
+        environment: mock_tx.environment.clone(),
+    }
+}
+
+/// Drops the cells and headers of `mock_info` that `tx` no longer refers to.
+fn prune(mock_info: &MockInfo, tx: &TransactionView) -> MockInfo {
+    let inputs: HashSet<OutPoint> = tx.input_pts_iter().collect();
+    let mut deps: HashSet<OutPoint> = tx.cell_deps_iter().map(|dep| dep.out_point()).collect();
+    for dep in tx.cell_deps_iter() {
+        if dep.dep_type() != DepType::DepGroup.into() {
+            continue;
+        }
+        let members = mock_info
+            .cell_deps
+            .iter()
+            .find(|mock_dep| mock_dep.cell_dep.out_point() == dep.out_point())
+            .and_then(|mock_dep| OutPointVec::from_slice(&mock_dep.data).ok());
+        if let Some(members) = members {
+            deps.extend(members);
+        }
+    }
+    let headers: HashSet<Byte32> = tx.header_deps_iter().collect();
+
+    MockInfo {
+        inputs: mock_info
+            .inputs
+            .iter()
+            .filter(|input| inputs.contains(&input.input.previous_output()))
+            .cloned()
+            .collect(),
+        cell_deps: mock_info
+            .cell_deps
+            .iter()
+            .filter(|dep| deps.contains(&dep.cell_dep.out_point()))
+            .cloned()
+            .collect(),
+        header_deps: mock_info
+            .header_deps
+            .iter()
+            .filter(|header| headers.contains(&header.hash()))
+            .cloned()
+            .collect(),
+    }
+}
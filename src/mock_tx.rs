@@ -0,0 +1,731 @@
+use ckb_traits::{CellDataProvider, HeaderProvider};
+use ckb_types::{
+    bytes::Bytes,
+    core::{
+        cell::{CellMeta, ResolvedTransaction},
+        DepType, HeaderView, ScriptHashType, TransactionView,
+    },
+    packed::{
+        Byte32, CellDep, CellInput, CellOutput, Header, OutPoint, RawHeader, RawTransaction,
+        Script, Transaction,
+    },
+    prelude::*,
+};
+use json::{self, JsonValue};
+use std::{collections::HashMap, convert::TryFrom, fs, io, path::Path};
+
+use crate::{
+    fmt_u32, fmt_u64, fmt_vec, get_bin_hash, resolve_transaction, CellLookup, Error,
+    VerifyEnvironment,
+};
+
+/// An input of the mock transaction, together with the cell it spends.
+#[derive(Clone, Debug)]
+pub struct MockInput {
+    pub input: CellInput,
+    pub output: CellOutput,
+    pub data: Bytes,
+}
+
+/// A cell dep of the mock transaction, together with the cell it refers to.
+#[derive(Clone, Debug)]
+pub struct MockCellDep {
+    pub cell_dep: CellDep,
+    pub output: CellOutput,
+    pub data: Bytes,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct MockInfo {
+    pub inputs: Vec<MockInput>,
+    pub cell_deps: Vec<MockCellDep>,
+    pub header_deps: Vec<HeaderView>,
+}
+
+/// The transaction file consumed by ckb-debugger's `--tx-file`.
+///
+/// `tx.cell_deps` lists the same deps as `mock_info.cell_deps`: the cells resolved from dep
+/// groups as `code` deps, followed by the dep group cells themselves.
+#[derive(Clone, Debug)]
+pub struct MockTransaction {
+    pub mock_info: MockInfo,
+    pub tx: TransactionView,
+    /// The environment the transaction was verified in, kept in the `extension` section of the
+    /// file.
+    pub environment: Option<VerifyEnvironment>,
+}
+
+/// How [`MockTransaction`] is serialized.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JsonFormat {
+    Compact,
+    /// Indented by 2 spaces, which is what `gen_json` writes.
+    Pretty,
+}
+
+fn load_cell_data<DL: CellDataProvider>(cell: &CellMeta, data_loader: Option<&DL>) -> Bytes {
+    cell.mem_cell_data
+        .clone()
+        .or_else(|| data_loader.and_then(|dl| dl.load_cell_data(cell)))
+        .expect("cannot load cell data")
+}
+
+fn load_header<DL: HeaderProvider>(
+    hash: &Byte32,
+    header_deps: &Option<HashMap<Byte32, HeaderView>>,
+    data_loader: Option<&DL>,
+) -> HeaderView {
+    header_deps
+        .as_ref()
+        .and_then(|headers| headers.get(hash).cloned())
+        .or_else(|| data_loader.and_then(|dl| dl.get_header(hash)))
+        .expect("cannot find header dep")
+}
+
+fn gen_json_script(sc: &Script) -> JsonValue {
+    let mut json = JsonValue::new_object();
+    json["code_hash"] = fmt_vec(sc.code_hash().as_slice()).into();
+    json["hash_type"] = {
+        let t: u8 = sc.hash_type().into();
+        let t = ScriptHashType::try_from(t).unwrap();
+        match t {
+            ScriptHashType::Data => "data".into(),
+            ScriptHashType::Type => "type".into(),
+            ScriptHashType::Data1 => "data1".into(),
+        }
+    };
+    json["args"] = fmt_vec(sc.args().raw_data().to_vec().as_slice()).into();
+
+    json
+}
+
+pub(crate) fn gen_json_outpoint(sc: &OutPoint) -> JsonValue {
+    let mut js = JsonValue::new_object();
+    js["index"] = fmt_u32(sc.index().as_slice()).into();
+    js["tx_hash"] = fmt_vec(sc.tx_hash().as_slice()).into();
+    js
+}
+
+fn gen_json_output(d: &CellOutput) -> JsonValue {
+    let mut js = JsonValue::new_object();
+    js["capacity"] = fmt_u64(d.capacity().as_slice()).into();
+    js["lock"] = gen_json_script(&d.lock());
+    if let Some(t) = d.type_().to_opt() {
+        js["type"] = gen_json_script(&t)
+    }
+    js
+}
+
+fn gen_json_input(d: &CellInput) -> JsonValue {
+    let mut js = JsonValue::new_object();
+    js["since"] = fmt_u64(d.since().as_slice()).into();
+    js["previous_output"] = gen_json_outpoint(&d.previous_output());
+    js
+}
+
+fn gen_json_cell_dep(d: &CellDep) -> JsonValue {
+    let mut js_cell = JsonValue::new_object();
+
+    js_cell["out_point"] = gen_json_outpoint(&d.out_point());
+    js_cell["dep_type"] = if d.dep_type() == DepType::DepGroup.into() {
+        "dep_group".into()
+    } else {
+        "code".into()
+    };
+    js_cell
+}
+
+fn gen_json_header(header_data: &HeaderView) -> JsonValue {
+    let mut js = JsonValue::new_object();
+    js["hash"] = fmt_vec(header_data.hash().as_slice()).into();
+    js["version"] = fmt_u32(header_data.version().to_le_bytes().to_vec().as_slice()).into();
+    js["compact_target"] = fmt_u32(
+        header_data
+            .compact_target()
+            .to_le_bytes()
+            .to_vec()
+            .as_slice(),
+    )
+    .into();
+    js["timestamp"] = format!("0x{:x}", header_data.timestamp()).into();
+    js["number"] = fmt_u64(header_data.number().to_le_bytes().to_vec().as_slice()).into();
+    js["epoch"] = format!("0x{:x}", header_data.epoch().full_value()).into();
+    js["parent_hash"] = fmt_vec(header_data.parent_hash().as_slice()).into();
+    js["transactions_root"] = fmt_vec(header_data.transactions_root().as_slice()).into();
+    js["proposals_hash"] = fmt_vec(header_data.proposals_hash().as_slice()).into();
+    js["extra_hash"] = fmt_vec(header_data.extra_hash().as_slice()).into();
+    js["dao"] = fmt_vec(header_data.dao().as_slice()).into();
+    js["nonce"] = format!("0x{:x}", header_data.nonce()).into();
+
+    js
+}
+
+pub(crate) fn invalid(path: &str, msg: &str) -> Error {
+    Error::InvalidJson(format!("{}: {}", path, msg))
+}
+
+pub(crate) fn parse_str<'j>(js: &'j JsonValue, path: &str) -> Result<&'j str, Error> {
+    js.as_str()
+        .ok_or_else(|| invalid(path, "expected a string"))
+}
+
+fn parse_bytes(js: &JsonValue, path: &str) -> Result<Bytes, Error> {
+    let s = parse_str(js, path)?;
+    let hex = s
+        .strip_prefix("0x")
+        .ok_or_else(|| invalid(path, "expected a 0x prefixed hex string"))?;
+    if hex.len() % 2 != 0 {
+        return Err(invalid(path, "odd number of hex digits"));
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16))
+        .collect::<Result<Vec<u8>, _>>()
+        .map(Bytes::from)
+        .map_err(|_| invalid(path, "invalid hex string"))
+}
+
+/// Parses a `0x` prefixed 32 byte hash, naming `path` in the error.
+pub fn parse_byte32(js: &JsonValue, path: &str) -> Result<Byte32, Error> {
+    let bytes = parse_bytes(js, path)?;
+    Byte32::from_slice(&bytes).map_err(|_| invalid(path, "expected 32 bytes"))
+}
+
+fn parse_u128(js: &JsonValue, path: &str) -> Result<u128, Error> {
+    let s = parse_str(js, path)?;
+    let hex = s
+        .strip_prefix("0x")
+        .ok_or_else(|| invalid(path, "expected a 0x prefixed hex number"))?;
+    u128::from_str_radix(hex, 16).map_err(|_| invalid(path, "invalid hex number"))
+}
+
+pub(crate) fn parse_u64(js: &JsonValue, path: &str) -> Result<u64, Error> {
+    u64::try_from(parse_u128(js, path)?).map_err(|_| invalid(path, "number too large"))
+}
+
+fn parse_u32(js: &JsonValue, path: &str) -> Result<u32, Error> {
+    u32::try_from(parse_u128(js, path)?).map_err(|_| invalid(path, "number too large"))
+}
+
+pub(crate) fn parse_array<'j>(js: &'j JsonValue, path: &str) -> Result<&'j [JsonValue], Error> {
+    match js {
+        JsonValue::Array(items) => Ok(items),
+        _ => Err(invalid(path, "expected an array")),
+    }
+}
+
+fn parse_json_script(js: &JsonValue, path: &str) -> Result<Script, Error> {
+    let hash_type = match parse_str(&js["hash_type"], &format!("{}.hash_type", path))? {
+        "data" => ScriptHashType::Data,
+        "type" => ScriptHashType::Type,
+        "data1" => ScriptHashType::Data1,
+        _ => return Err(invalid(path, "unknown hash_type")),
+    };
+    Ok(Script::new_builder()
+        .code_hash(parse_byte32(
+            &js["code_hash"],
+            &format!("{}.code_hash", path),
+        )?)
+        .hash_type(hash_type.into())
+        .args(parse_bytes(&js["args"], &format!("{}.args", path))?.pack())
+        .build())
+}
+
+pub(crate) fn parse_json_outpoint(js: &JsonValue, path: &str) -> Result<OutPoint, Error> {
+    Ok(OutPoint::new(
+        parse_byte32(&js["tx_hash"], &format!("{}.tx_hash", path))?,
+        parse_u32(&js["index"], &format!("{}.index", path))?,
+    ))
+}
+
+fn parse_json_output(js: &JsonValue, path: &str) -> Result<CellOutput, Error> {
+    let type_ = match &js["type"] {
+        JsonValue::Null => None,
+        t => Some(parse_json_script(t, &format!("{}.type", path))?),
+    };
+    Ok(CellOutput::new_builder()
+        .capacity(parse_u64(&js["capacity"], &format!("{}.capacity", path))?.pack())
+        .lock(parse_json_script(&js["lock"], &format!("{}.lock", path))?)
+        .type_(type_.pack())
+        .build())
+}
+
+fn parse_json_input(js: &JsonValue, path: &str) -> Result<CellInput, Error> {
+    Ok(CellInput::new(
+        parse_json_outpoint(&js["previous_output"], &format!("{}.previous_output", path))?,
+        parse_u64(&js["since"], &format!("{}.since", path))?,
+    ))
+}
+
+fn parse_json_cell_dep(js: &JsonValue, path: &str) -> Result<CellDep, Error> {
+    let dep_type = match parse_str(&js["dep_type"], &format!("{}.dep_type", path))? {
+        "code" => DepType::Code,
+        "dep_group" => DepType::DepGroup,
+        _ => return Err(invalid(path, "unknown dep_type")),
+    };
+    Ok(CellDep::new_builder()
+        .out_point(parse_json_outpoint(
+            &js["out_point"],
+            &format!("{}.out_point", path),
+        )?)
+        .dep_type(dep_type.into())
+        .build())
+}
+
+fn parse_json_header(js: &JsonValue, path: &str) -> Result<HeaderView, Error> {
+    let field = |name: &str| (&js[name], format!("{}.{}", path, name));
+    let raw = RawHeader::new_builder()
+        .version({
+            let (js, path) = field("version");
+            parse_u32(js, &path)?.pack()
+        })
+        .compact_target({
+            let (js, path) = field("compact_target");
+            parse_u32(js, &path)?.pack()
+        })
+        .timestamp({
+            let (js, path) = field("timestamp");
+            parse_u64(js, &path)?.pack()
+        })
+        .number({
+            let (js, path) = field("number");
+            parse_u64(js, &path)?.pack()
+        })
+        .epoch({
+            let (js, path) = field("epoch");
+            parse_u64(js, &path)?.pack()
+        })
+        .parent_hash({
+            let (js, path) = field("parent_hash");
+            parse_byte32(js, &path)?
+        })
+        .transactions_root({
+            let (js, path) = field("transactions_root");
+            parse_byte32(js, &path)?
+        })
+        .proposals_hash({
+            let (js, path) = field("proposals_hash");
+            parse_byte32(js, &path)?
+        })
+        .extra_hash({
+            let (js, path) = field("extra_hash");
+            parse_byte32(js, &path)?
+        })
+        .dao({
+            let (js, path) = field("dao");
+            parse_byte32(js, &path)?
+        })
+        .build();
+    let (nonce, nonce_path) = field("nonce");
+    let header = Header::new_builder()
+        .raw(raw)
+        .nonce(parse_u128(nonce, &nonce_path)?.pack())
+        .build()
+        .into_view();
+
+    if !js["hash"].is_null()
+        && parse_byte32(&js["hash"], &format!("{}.hash", path))? != header.hash()
+    {
+        return Err(invalid(path, "hash does not match the header fields"));
+    }
+    Ok(header)
+}
+
+fn mock_cell_dep(cell: &CellMeta, dep_type: DepType, data: Bytes) -> MockCellDep {
+    MockCellDep {
+        cell_dep: CellDep::new_builder()
+            .out_point(cell.out_point.clone())
+            .dep_type(dep_type.into())
+            .build(),
+        output: cell.cell_output.clone(),
+        data,
+    }
+}
+
+impl MockTransaction {
+    pub(crate) fn build<DL: CellDataProvider + HeaderProvider>(
+        resolved_tx: &ResolvedTransaction,
+        header_deps: &Option<HashMap<Byte32, HeaderView>>,
+        data_loader: Option<&DL>,
+    ) -> Self {
+        let transaction = &resolved_tx.transaction;
+        let inputs = resolved_tx
+            .resolved_inputs
+            .iter()
+            .zip(transaction.inputs())
+            .map(|(cell, input)| MockInput {
+                input,
+                output: cell.cell_output.clone(),
+                data: load_cell_data(cell, data_loader),
+            })
+            .collect();
+        let cell_deps: Vec<MockCellDep> = resolved_tx
+            .resolved_cell_deps
+            .iter()
+            .map(|cell| mock_cell_dep(cell, DepType::Code, load_cell_data(cell, data_loader)))
+            .chain(resolved_tx.resolved_dep_groups.iter().map(|cell| {
+                mock_cell_dep(cell, DepType::DepGroup, load_cell_data(cell, data_loader))
+            }))
+            .collect();
+        let header_deps = transaction
+            .header_deps_iter()
+            .map(|hash| load_header(&hash, header_deps, data_loader))
+            .collect();
+
+        let tx = transaction
+            .as_advanced_builder()
+            .set_cell_deps(cell_deps.iter().map(|dep| dep.cell_dep.clone()).collect())
+            .build();
+
+        MockTransaction {
+            mock_info: MockInfo {
+                inputs,
+                cell_deps,
+                header_deps,
+            },
+            tx,
+            environment: None,
+        }
+    }
+
+    /// Builds the mock transaction from the cell data and headers carried by `resolved_tx`
+    /// and `header_deps`, like [`gen_json`](crate::gen_json) does.
+    pub fn from_resolved_tx(
+        resolved_tx: &ResolvedTransaction,
+        header_deps: Option<HashMap<Byte32, HeaderView>>,
+    ) -> Self {
+        Self::build(resolved_tx, &header_deps, None::<&NoDataLoader>)
+    }
+
+    /// Builds the mock transaction, loading the cell data and header deps that `resolved_tx`
+    /// does not carry from `data_loader`.
+    pub fn from_resolved_tx_with_data_loader<DL: CellDataProvider + HeaderProvider>(
+        data_loader: &DL,
+        resolved_tx: &ResolvedTransaction,
+    ) -> Self {
+        Self::build(resolved_tx, &None, Some(data_loader))
+    }
+
+    pub(crate) fn blank_bin_hash(&mut self, bin_hash: &Byte32) {
+        for dep in self.mock_info.cell_deps.iter_mut() {
+            if CellOutput::calc_data_hash(&dep.data) == *bin_hash {
+                dep.data = Bytes::new();
+            }
+        }
+    }
+
+    /// Replaces the data of the cell deps holding the binary at `bin_path` with `0x`, so that
+    /// ckb-debugger loads it from `--bin` instead.
+    ///
+    /// Panics if `bin_path` cannot be read or is empty.
+    pub fn blank_bin(&mut self, bin_path: &str) {
+        self.blank_bin_hash(&get_bin_hash(bin_path).expect("read bin file failed"));
+    }
+
+    pub fn to_json(&self) -> JsonValue {
+        let mut js_root: JsonValue = JsonValue::new_object();
+        js_root["mock_info"] = {
+            let mut js = JsonValue::new_object();
+            js["inputs"] = JsonValue::Array(
+                self.mock_info
+                    .inputs
+                    .iter()
+                    .map(|input| {
+                        let mut js_cell = JsonValue::new_object();
+                        js_cell["input"] = gen_json_input(&input.input);
+                        js_cell["output"] = gen_json_output(&input.output);
+                        js_cell["data"] = fmt_vec(&input.data).into();
+                        js_cell
+                    })
+                    .collect(),
+            );
+            js["cell_deps"] = JsonValue::Array(
+                self.mock_info
+                    .cell_deps
+                    .iter()
+                    .map(|dep| {
+                        let mut js_cell_dep = JsonValue::new_object();
+                        js_cell_dep["cell_dep"] = gen_json_cell_dep(&dep.cell_dep);
+                        js_cell_dep["output"] = gen_json_output(&dep.output);
+                        js_cell_dep["data"] = fmt_vec(&dep.data).into();
+                        js_cell_dep
+                    })
+                    .collect(),
+            );
+            js["header_deps"] = JsonValue::Array(
+                self.mock_info
+                    .header_deps
+                    .iter()
+                    .map(gen_json_header)
+                    .collect(),
+            );
+            js
+        };
+        js_root["tx"] = {
+            let mut js_tx = JsonValue::new_object();
+            js_tx["version"] = fmt_u32(&self.tx.version().to_le_bytes()).into();
+            js_tx["cell_deps"] = JsonValue::Array(
+                self.tx
+                    .cell_deps_iter()
+                    .map(|dep| gen_json_cell_dep(&dep))
+                    .collect(),
+            );
+            js_tx["header_deps"] = JsonValue::Array(
+                self.tx
+                    .header_deps_iter()
+                    .map(|hash| fmt_vec(hash.as_slice()).into())
+                    .collect(),
+            );
+            js_tx["inputs"] = JsonValue::Array(
+                self.tx
+                    .inputs()
+                    .into_iter()
+                    .map(|input| gen_json_input(&input))
+                    .collect(),
+            );
+            js_tx["outputs"] = JsonValue::Array(
+                self.tx
+                    .outputs()
+                    .into_iter()
+                    .map(|output| gen_json_output(&output))
+                    .collect(),
+            );
+            js_tx["outputs_data"] = JsonValue::Array(
+                self.tx
+                    .outputs_data()
+                    .into_iter()
+                    .map(|data| fmt_vec(&data.raw_data()).into())
+                    .collect(),
+            );
+            js_tx["witnesses"] = JsonValue::Array(
+                self.tx
+                    .witnesses()
+                    .into_iter()
+                    .map(|data| fmt_vec(&data.raw_data()).into())
+                    .collect(),
+            );
+            js_tx
+        };
+        if let Some(environment) = &self.environment {
+            js_root["extension"]["environment"] = environment.to_json();
+        }
+
+        js_root
+    }
+
+    /// Parses a transaction file in the format written by [`Self::to_json`], which is also the
+    /// format of ckb-debugger's `--tx-file`.
+    pub fn from_json(js: &JsonValue) -> Result<Self, Error> {
+        let mock_info = &js["mock_info"];
+        let inputs = parse_array(&mock_info["inputs"], "mock_info.inputs")?
+            .iter()
+            .enumerate()
+            .map(|(i, js)| {
+                let path = format!("mock_info.inputs[{}]", i);
+                Ok(MockInput {
+                    input: parse_json_input(&js["input"], &format!("{}.input", path))?,
+                    output: parse_json_output(&js["output"], &format!("{}.output", path))?,
+                    data: parse_bytes(&js["data"], &format!("{}.data", path))?,
+                })
+            })
+            .collect::<Result<_, Error>>()?;
+        let cell_deps = parse_array(&mock_info["cell_deps"], "mock_info.cell_deps")?
+            .iter()
+            .enumerate()
+            .map(|(i, js)| {
+                let path = format!("mock_info.cell_deps[{}]", i);
+                Ok(MockCellDep {
+                    cell_dep: parse_json_cell_dep(&js["cell_dep"], &format!("{}.cell_dep", path))?,
+                    output: parse_json_output(&js["output"], &format!("{}.output", path))?,
+                    data: parse_bytes(&js["data"], &format!("{}.data", path))?,
+                })
+            })
+            .collect::<Result<_, Error>>()?;
+        let header_deps = match &mock_info["header_deps"] {
+            JsonValue::Null => Vec::new(),
+            headers => parse_array(headers, "mock_info.header_deps")?
+                .iter()
+                .enumerate()
+                .map(|(i, js)| parse_json_header(js, &format!("mock_info.header_deps[{}]", i)))
+                .collect::<Result<_, Error>>()?,
+        };
+
+        let js_tx = &js["tx"];
+        let list = |name: &str| -> Result<&[JsonValue], Error> {
+            match &js_tx[name] {
+                JsonValue::Null => Ok(&[]),
+                items => parse_array(items, &format!("tx.{}", name)),
+            }
+        };
+        let raw = RawTransaction::new_builder()
+            .version(parse_u32(&js_tx["version"], "tx.version")?.pack())
+            .cell_deps(
+                list("cell_deps")?
+                    .iter()
+                    .enumerate()
+                    .map(|(i, js)| parse_json_cell_dep(js, &format!("tx.cell_deps[{}]", i)))
+                    .collect::<Result<Vec<_>, Error>>()?
+                    .pack(),
+            )
+            .header_deps(
+                list("header_deps")?
+                    .iter()
+                    .enumerate()
+                    .map(|(i, js)| parse_byte32(js, &format!("tx.header_deps[{}]", i)))
+                    .collect::<Result<Vec<_>, Error>>()?
+                    .pack(),
+            )
+            .inputs(
+                list("inputs")?
+                    .iter()
+                    .enumerate()
+                    .map(|(i, js)| parse_json_input(js, &format!("tx.inputs[{}]", i)))
+                    .collect::<Result<Vec<_>, Error>>()?
+                    .pack(),
+            )
+            .outputs(
+                list("outputs")?
+                    .iter()
+                    .enumerate()
+                    .map(|(i, js)| parse_json_output(js, &format!("tx.outputs[{}]", i)))
+                    .collect::<Result<Vec<_>, Error>>()?
+                    .pack(),
+            )
+            .outputs_data(
+                list("outputs_data")?
+                    .iter()
+                    .enumerate()
+                    .map(|(i, js)| {
+                        parse_bytes(js, &format!("tx.outputs_data[{}]", i)).map(|d| d.pack())
+                    })
+                    .collect::<Result<Vec<_>, Error>>()?
+                    .pack(),
+            )
+            .build();
+        let witnesses: Vec<_> = list("witnesses")?
+            .iter()
+            .enumerate()
+            .map(|(i, js)| parse_bytes(js, &format!("tx.witnesses[{}]", i)).map(|d| d.pack()))
+            .collect::<Result<_, Error>>()?;
+        let tx = Transaction::new_builder()
+            .raw(raw)
+            .witnesses(witnesses.pack())
+            .build()
+            .into_view();
+        let environment = match &js["extension"]["environment"] {
+            JsonValue::Null => None,
+            js => Some(VerifyEnvironment::from_json(js, "extension.environment")?),
+        };
+
+        Ok(MockTransaction {
+            mock_info: MockInfo {
+                inputs,
+                cell_deps,
+                header_deps,
+            },
+            tx,
+            environment,
+        })
+    }
+
+    pub fn from_json_str(s: &str) -> Result<Self, Error> {
+        let js = json::parse(s).map_err(|e| Error::InvalidJson(e.to_string()))?;
+        Self::from_json(&js)
+    }
+
+    /// Resolves `tx` against the cells and headers of `mock_info`, which also serves as the
+    /// data loader of the returned transaction.
+    pub fn resolve(&self) -> Result<ResolvedTransaction, Error> {
+        let header_deps = self
+            .mock_info
+            .header_deps
+            .iter()
+            .map(|header| (header.hash(), header.clone()))
+            .collect();
+        resolve_transaction(&self.tx, &self.mock_info, Some(header_deps))
+    }
+
+    /// Reads a transaction file, see [`Self::from_json`].
+    pub fn read_from_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Self::from_json_str(&fs::read_to_string(path)?)
+    }
+
+    pub fn to_json_string(&self, format: JsonFormat) -> String {
+        match format {
+            JsonFormat::Compact => self.to_json().dump(),
+            JsonFormat::Pretty => self.to_json().pretty(2),
+        }
+    }
+
+    pub fn write_to<W: io::Write>(&self, writer: &mut W, format: JsonFormat) -> io::Result<()> {
+        match format {
+            JsonFormat::Compact => self.to_json().write(writer),
+            JsonFormat::Pretty => self.to_json().write_pretty(writer, 2),
+        }
+    }
+}
+
+impl MockInfo {
+    fn find_cell(&self, out_point: &OutPoint) -> Option<(&CellOutput, &Bytes)> {
+        self.inputs
+            .iter()
+            .find(|input| input.input.previous_output() == *out_point)
+            .map(|input| (&input.output, &input.data))
+            .or_else(|| {
+                self.cell_deps
+                    .iter()
+                    .find(|dep| dep.cell_dep.out_point() == *out_point)
+                    .map(|dep| (&dep.output, &dep.data))
+            })
+    }
+}
+
+impl CellLookup for MockInfo {
+    fn get_cell(&self, out_point: &OutPoint) -> Option<(CellOutput, Bytes)> {
+        self.find_cell(out_point)
+            .map(|(output, data)| (output.clone(), data.clone()))
+    }
+
+    fn get_header(&self, hash: &Byte32) -> Option<HeaderView> {
+        self.header_deps
+            .iter()
+            .find(|header| header.hash() == *hash)
+            .cloned()
+    }
+}
+
+impl CellDataProvider for MockInfo {
+    fn get_cell_data(&self, out_point: &OutPoint) -> Option<Bytes> {
+        self.find_cell(out_point).map(|(_, data)| data.clone())
+    }
+
+    fn get_cell_data_hash(&self, out_point: &OutPoint) -> Option<Byte32> {
+        self.find_cell(out_point)
+            .map(|(_, data)| CellOutput::calc_data_hash(data))
+    }
+}
+
+impl HeaderProvider for MockInfo {
+    fn get_header(&self, hash: &Byte32) -> Option<HeaderView> {
+        CellLookup::get_header(self, hash)
+    }
+}
+
+/// Stands in for the data loader type when everything is read from the resolved transaction.
+struct NoDataLoader;
+
+impl CellDataProvider for NoDataLoader {
+    fn get_cell_data(&self, _out_point: &OutPoint) -> Option<Bytes> {
+        None
+    }
+
+    fn get_cell_data_hash(&self, _out_point: &OutPoint) -> Option<Byte32> {
+        None
+    }
+}
+
+impl HeaderProvider for NoDataLoader {
+    fn get_header(&self, _hash: &Byte32) -> Option<HeaderView> {
+        None
+    }
+}
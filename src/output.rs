@@ -0,0 +1,196 @@
+use ckb_types::{core::Cycle, packed::Byte32, prelude::Entity};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{fmt_vec, VerifyEnvironment};
+
+static DUMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// A dumped transaction file and the ckb-debugger command line that runs it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Dump {
+    pub tx_file: PathBuf,
+    /// Ends with the [`Labels`](crate::Labels) entry of the group as a shell comment, if any.
+    pub command: String,
+}
+
+/// How the ckb-debugger command of a dump runs its group, for
+/// [`gen_json_with_options`](crate::gen_json_with_options).
+///
+/// ```ignore
+/// let options = DumpOptions::new().max_cycles(max_cycles).environment(environment);
+/// let command = gen_json_with_options(&verifier, &resolved_tx, None, 0, bin_path, "tx.json", &options);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct DumpOptions {
+    pub(crate) dbg_addr: Option<String>,
+    pub(crate) environment: Option<VerifyEnvironment>,
+    pub(crate) max_cycles: Option<Cycle>,
+}
+
+impl DumpOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Runs ckb-debugger as a gdb server listening on `addr`.
+    pub fn dbg_addr(mut self, addr: &str) -> Self {
+        self.dbg_addr = Some(String::from(addr));
+        self
+    }
+
+    /// Records the environment in the dump and runs the group on the VM version it selects,
+    /// see [`VerifyEnvironment`].
+    pub fn environment(mut self, environment: VerifyEnvironment) -> Self {
+        self.environment = Some(environment);
+        self
+    }
+
+    /// The cycle limit passed to ckb-debugger as `--max-cycles`.
+    pub fn max_cycles(mut self, max_cycles: Cycle) -> Self {
+        self.max_cycles = Some(max_cycles);
+        self
+    }
+}
+
+/// A directory that dumps are written to under generated, collision-free names, so tests
+/// running in parallel never clobber each other's files.
+///
+/// Names look like `<name>-<script hash>-<timestamp>-<pid>-<counter>.json`. `name` defaults to
+/// the current thread name, which is the test name under `cargo test`.
+#[derive(Clone, Debug)]
+pub struct DumpDir {
+    dir: PathBuf,
+    name: Option<String>,
+}
+
+fn sanitize(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+impl DumpDir {
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        DumpDir {
+            dir: dir.into(),
+            name: None,
+        }
+    }
+
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = Some(String::from(name));
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The name a dump written under [`Self::unique_path`] was generated from, without the
+    /// script hash, timestamp, pid, counter and extension. It is the same every time a test
+    /// dumps, so dumps of different runs can be matched by it. Other files give their stem.
+    pub fn dump_name(tx_file: &Path) -> String {
+        let stem = tx_file
+            .file_stem()
+            .map(|stem| stem.to_string_lossy())
+            .unwrap_or_default();
+        String::from(split_unique_name(&stem).0)
+    }
+
+    /// Generates a file name that no other dump of this process (or of any other process
+    /// writing to the same directory) uses. `script_hash` is the dumped group, if any.
+    pub fn unique_path(&self, script_hash: Option<&Byte32>, extension: &str) -> PathBuf {
+        let name = match &self.name {
+            Some(name) => name.clone(),
+            None => match std::thread::current().name() {
+                Some(name) if name != "main" => String::from(name),
+                _ => String::from("dump"),
+            },
+        };
+        let hash = match script_hash {
+            Some(hash) => format!("-{}", &fmt_vec(hash.as_slice())[2..18]),
+            None => String::new(),
+        };
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or_default();
+        let counter = DUMP_COUNTER.fetch_add(1, Ordering::SeqCst);
+
+        self.dir.join(format!(
+            "{}{}-{}-{}-{}.{}",
+            sanitize(&name),
+            hash,
+            timestamp,
+            std::process::id(),
+            counter,
+            extension
+        ))
+    }
+}
+
+/// Splits a file stem generated by [`DumpDir::unique_path`] into the name it was generated
+/// from and its timestamp, pid and counter, dropping the script hash. Other stems are returned
+/// whole.
+pub(crate) fn split_unique_name(stem: &str) -> (&str, Option<(u128, u128, u128)>) {
+    let number = |part: &str| -> Option<u128> {
+        if part.bytes().all(|b| b.is_ascii_digit()) {
+            part.parse().ok()
+        } else {
+            None
+        }
+    };
+    let parts: Vec<&str> = stem.rsplitn(4, '-').collect();
+    let (rest, suffix) = match parts.as_slice() {
+        [counter, pid, timestamp, rest] => {
+            match (number(timestamp), number(pid), number(counter)) {
+                (Some(timestamp), Some(pid), Some(counter)) => (*rest, (timestamp, pid, counter)),
+                _ => return (stem, None),
+            }
+        }
+        _ => return (stem, None),
+    };
+    let name = match rest.rsplit_once('-') {
+        Some((name, hash)) if hash.len() == 16 && hash.bytes().all(|b| b.is_ascii_hexdigit()) => {
+            name
+        }
+        _ => rest,
+    };
+    (name, Some(suffix))
+}
+
+/// Writes `data` to a temporary file next to `path` and renames it over `path`, so readers
+/// never observe a partially written file. Missing parent directories are created.
+pub fn write_atomic(path: &Path, data: &[u8]) -> io::Result<()> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    fs::create_dir_all(dir)?;
+
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "no file name"))?;
+    let tmp_path = dir.join(format!(
+        ".{}.{}-{}.tmp",
+        file_name.to_string_lossy(),
+        std::process::id(),
+        DUMP_COUNTER.fetch_add(1, Ordering::SeqCst)
+    ));
+    if let Err(e) = fs::write(&tmp_path, data).and_then(|_| fs::rename(&tmp_path, path)) {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(e);
+    }
+    Ok(())
+}
@@ -0,0 +1,181 @@
+use ckb_script::ScriptGroup;
+use ckb_types::{core::Cycle, packed::Byte32};
+use std::{cell::RefCell, env, path::PathBuf, thread};
+
+use crate::{
+    gen_cmd_line, write_atomic, DumpDir, Error, Expected, JsonFormat, Labels, Manifest,
+    ManifestGroup, MockTransaction, Report, WitnessAnnotations, DUMP_DIR_VAR,
+};
+
+/// A transaction verified by a [`crate::DumpingVerifier`], ready to be dumped.
+pub(crate) struct Recorded {
+    pub mock_tx: MockTransaction,
+    pub groups: Vec<(Byte32, ScriptGroup)>,
+    pub bin_path: Option<PathBuf>,
+    /// The failing group index and its error.
+    pub failed: Option<(usize, String)>,
+    pub expected: Option<Expected>,
+    pub report: Option<Report>,
+    pub labels: Labels,
+    /// The cycle limit of the whole transaction.
+    pub max_cycles: Option<Cycle>,
+}
+
+impl Recorded {
+    /// Writes the transaction into `dump_dir`, with a manifest listing the command line of
+    /// every group, the witness annotations, the [`Expected`] sidecar if the transaction was
+    /// verified, the [`Report`] if enabled, and the [`Labels`] if any.
+    pub fn write(&self, dump_dir: &DumpDir) -> Result<Manifest, Error> {
+        let script_hash = self
+            .failed
+            .as_ref()
+            .and_then(|(i, _)| self.groups.get(*i).map(|(hash, _)| hash));
+        let tx_file = dump_dir.unique_path(script_hash, "json");
+        write_atomic(
+            &tx_file,
+            self.mock_tx.to_json_string(JsonFormat::Pretty).as_bytes(),
+        )?;
+        let tx_file = std::fs::canonicalize(tx_file)?;
+
+        // Groups are verified one after another against the limit of the transaction, so each
+        // runs with what the groups before it left.
+        let mut cycles_left = self.max_cycles;
+        let manifest = Manifest {
+            groups: self
+                .groups
+                .iter()
+                .enumerate()
+                .map(|(i, (script_hash, group))| {
+                    let cycles = self
+                        .expected
+                        .as_ref()
+                        .and_then(|expected| expected.groups.get(i))
+                        .and_then(|output| output.cycles);
+                    let max_cycles = cycles_left;
+                    cycles_left = cycles_left.map(|left| left.saturating_sub(cycles.unwrap_or(0)));
+                    Ok(ManifestGroup {
+                        group_type: group.group_type,
+                        script_hash: script_hash.clone(),
+                        command: gen_cmd_line(
+                            group,
+                            self.bin_path.as_deref(),
+                            &tx_file,
+                            None,
+                            self.mock_tx.environment.as_ref(),
+                            max_cycles,
+                        )?,
+                        error: self
+                            .failed
+                            .as_ref()
+                            .filter(|(failed_index, _)| *failed_index == i)
+                            .map(|(_, error)| error.clone()),
+                        label: self.labels.get_script(script_hash).map(String::from),
+                        cycles,
+                        max_cycles,
+                    })
+                })
+                .collect::<Result<_, Error>>()?,
+            tx_file,
+        };
+        manifest.write()?;
+        WitnessAnnotations::from_groups(&self.mock_tx.tx, &self.groups).write(&manifest.tx_file)?;
+        if let Some(expected) = &self.expected {
+            expected.write(&manifest.tx_file)?;
+        }
+        if let Some(report) = &self.report {
+            report.write(&manifest.tx_file)?;
+        }
+        if !self.labels.is_empty() {
+            self.labels.write(&manifest.tx_file)?;
+        }
+        Ok(manifest)
+    }
+}
+
+thread_local! {
+    static RECORDED: RefCell<Option<Vec<Result<Recorded, Error>>>> = const { RefCell::new(None) };
+}
+
+pub(crate) fn is_recording() -> bool {
+    RECORDED.with(|recorded| recorded.borrow().is_some())
+}
+
+/// Records a verified transaction, or the error preparing its dump, for the guard to report.
+pub(crate) fn record(tx: Result<Recorded, Error>) {
+    RECORDED.with(|recorded| {
+        if let Some(recorded) = recorded.borrow_mut().as_mut() {
+            recorded.push(tx);
+        }
+    })
+}
+
+/// Records the transactions verified by a [`crate::DumpingVerifier`] on the current thread,
+/// and dumps them if the thread panics before the guard is dropped.
+///
+/// This is what `#[dump_on_failure]` expands to. Dumps go to [`DUMP_DIR_VAR`] if set, or to
+/// `ckb-debugger-dumper` in the system temp directory, and are named after the test.
+pub struct Recording {
+    dump_dir: DumpDir,
+    name: String,
+    previous: Option<Vec<Result<Recorded, Error>>>,
+}
+
+impl Recording {
+    /// Starts recording until the returned guard is dropped. `name` is usually
+    /// `module_path::test_name`.
+    pub fn start(name: &str) -> Self {
+        let dir = match env::var_os(DUMP_DIR_VAR) {
+            Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => env::temp_dir().join("ckb-debugger-dumper"),
+        };
+        let name = name.replace("::", "-");
+        Recording {
+            dump_dir: DumpDir::new(dir).name(&name),
+            previous: RECORDED.with(|recorded| recorded.replace(Some(Vec::new()))),
+            name,
+        }
+    }
+
+    pub fn dir<P: Into<PathBuf>>(mut self, dir: P) -> Self {
+        self.dump_dir = DumpDir::new(dir).name(&self.name);
+        self
+    }
+}
+
+impl Drop for Recording {
+    fn drop(&mut self) {
+        let recorded = RECORDED
+            .with(|recorded| recorded.replace(self.previous.take()))
+            .unwrap_or_default();
+        if !thread::panicking() || recorded.is_empty() {
+            return;
+        }
+
+        eprintln!(
+            "{} failed, dumping {} verified transaction(s)",
+            self.name,
+            recorded.len()
+        );
+        for tx in &recorded {
+            let tx = match tx {
+                Ok(tx) => tx,
+                Err(e) => {
+                    eprintln!("record transaction failed: {}", e);
+                    continue;
+                }
+            };
+            match tx.write(&self.dump_dir) {
+                Ok(manifest) => {
+                    eprintln!(
+                        "transaction dumped to {}",
+                        manifest.tx_file.to_string_lossy()
+                    );
+                    for group in &manifest.groups {
+                        eprintln!("  {}", group.commented_command());
+                    }
+                }
+                Err(e) => eprintln!("dump transaction failed: {}", e),
+            }
+        }
+    }
+}
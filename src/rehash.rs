@@ -0,0 +1,196 @@
+use ckb_types::{
+    bytes::Bytes,
+    core::ScriptHashType,
+    packed::{Byte32, CellOutput, Script},
+    prelude::*,
+};
+
+use crate::{Error, MockTransaction};
+
+/// Swaps a rebuilt binary into a mock transaction and rewrites the `data` and `data1` scripts
+/// that ran the old one, so the dump verifies with the new build.
+///
+/// Blanking a dep to `0x` and running the new build with `--bin` only works while the code
+/// hash is unchanged. A rebuilt binary has a new data hash, so every script whose `code_hash`
+/// is the old one is rewritten to the new one. Their script hashes change as well, and with
+/// [`Self::rewrite_args`] the args embedding them, such as the owner lock hash of a UDT, are
+/// updated to match.
+///
+/// ```ignore
+/// let rehashed = Rehash::new(old_code_hash, new_binary).apply(&mut mock_tx)?;
+/// ```
+#[derive(Clone, Debug)]
+pub struct Rehash {
+    old_code_hash: Byte32,
+    new_code: Bytes,
+    deps: Vec<usize>,
+    rewrite_args: bool,
+}
+
+/// What [`Rehash::apply`] changed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Rehashed {
+    pub new_code_hash: Byte32,
+    /// Indices into `mock_info.cell_deps` of the deps now holding the new binary.
+    pub deps: Vec<usize>,
+    /// The old and new hashes of the rewritten scripts.
+    pub script_hashes: Vec<(Byte32, Byte32)>,
+}
+
+fn is_data_hash_type(script: &Script) -> bool {
+    script.hash_type() != ScriptHashType::Type.into()
+}
+
+/// `args` with every occurrence of an old hash replaced by its new hash.
+fn replace_hashes(args: &[u8], script_hashes: &[(Byte32, Byte32)]) -> Option<Bytes> {
+    let mut args = args.to_vec();
+    let mut changed = false;
+    for (old, new) in script_hashes {
+        let mut i = 0;
+        while i + 32 <= args.len() {
+            if &args[i..i + 32] == old.as_slice() {
+                args[i..i + 32].copy_from_slice(new.as_slice());
+                changed = true;
+                i += 32;
+            } else {
+                i += 1;
+            }
+        }
+    }
+    if changed {
+        Some(Bytes::from(args))
+    } else {
+        None
+    }
+}
+
+/// Applies `f` to the lock and type script of `output`, recording the hashes it changes.
+fn map_cell<F: Fn(&Script) -> Option<Script>>(
+    output: &CellOutput,
+    f: &F,
+    script_hashes: &mut Vec<(Byte32, Byte32)>,
+) -> CellOutput {
+    let mut map = |script: Script| match f(&script) {
+        Some(new) => {
+            let pair = (script.calc_script_hash(), new.calc_script_hash());
+            if !script_hashes.contains(&pair) {
+                script_hashes.push(pair);
+            }
+            new
+        }
+        None => script,
+    };
+    let lock = map(output.lock());
+    let type_ = output.type_().to_opt().map(&mut map);
+    output
+        .clone()
+        .as_builder()
+        .lock(lock)
+        .type_(type_.pack())
+        .build()
+}
+
+/// Applies `f` to every script of the transaction and its cells, returning the hashes it
+/// changed.
+fn map_scripts<F: Fn(&Script) -> Option<Script>>(
+    mock_tx: &mut MockTransaction,
+    f: F,
+) -> Vec<(Byte32, Byte32)> {
+    let mut script_hashes = Vec::new();
+    for input in mock_tx.mock_info.inputs.iter_mut() {
+        input.output = map_cell(&input.output, &f, &mut script_hashes);
+    }
+    for dep in mock_tx.mock_info.cell_deps.iter_mut() {
+        dep.output = map_cell(&dep.output, &f, &mut script_hashes);
+    }
+    let outputs: Vec<CellOutput> = mock_tx
+        .tx
+        .outputs()
+        .into_iter()
+        .map(|output| map_cell(&output, &f, &mut script_hashes))
+        .collect();
+    mock_tx.tx = mock_tx
+        .tx
+        .as_advanced_builder()
+        .set_outputs(outputs)
+        .build();
+    script_hashes
+}
+
+impl Rehash {
+    /// Replaces the binary whose data hash is `old_code_hash` with `new_code`.
+    pub fn new(old_code_hash: Byte32, new_code: Bytes) -> Self {
+        Rehash {
+            old_code_hash,
+            new_code,
+            deps: Vec::new(),
+            rewrite_args: false,
+        }
+    }
+
+    /// Also puts the new binary in cell dep `index`, for a dep whose data was blanked to `0x`
+    /// and no longer hashes to the old code hash.
+    pub fn dep(mut self, index: usize) -> Self {
+        self.deps.push(index);
+        self
+    }
+
+    /// Also rewrites the script hashes changed by the rehash wherever they appear in script
+    /// args, until no args change.
+    pub fn rewrite_args(mut self, rewrite_args: bool) -> Self {
+        self.rewrite_args = rewrite_args;
+        self
+    }
+
+    pub fn apply(&self, mock_tx: &mut MockTransaction) -> Result<Rehashed, Error> {
+        let cell_deps = &mut mock_tx.mock_info.cell_deps;
+        if let Some(index) = self.deps.iter().find(|index| **index >= cell_deps.len()) {
+            return Err(Error::InvalidEdit(format!(
+                "cell_deps[{}] out of range, the transaction has {}",
+                index,
+                cell_deps.len()
+            )));
+        }
+        let mut deps = Vec::new();
+        for (index, dep) in cell_deps.iter_mut().enumerate() {
+            let holds_old =
+                !dep.data.is_empty() && CellOutput::calc_data_hash(&dep.data) == self.old_code_hash;
+            if holds_old || self.deps.contains(&index) {
+                dep.data = self.new_code.clone();
+                deps.push(index);
+            }
+        }
+
+        let new_code_hash = CellOutput::calc_data_hash(&self.new_code);
+        let mut script_hashes = map_scripts(mock_tx, |script| {
+            if is_data_hash_type(script) && script.code_hash() == self.old_code_hash {
+                Some(
+                    script
+                        .clone()
+                        .as_builder()
+                        .code_hash(new_code_hash.clone())
+                        .build(),
+                )
+            } else {
+                None
+            }
+        });
+
+        // Each round may change the hash of scripts whose args the next round rewrites.
+        let mut rewritten = script_hashes.clone();
+        while self.rewrite_args && !rewritten.is_empty() {
+            rewritten = map_scripts(mock_tx, |script| {
+                replace_hashes(&script.args().raw_data(), &script_hashes)
+                    .map(|args| script.clone().as_builder().args(args.pack()).build())
+            });
+            rewritten.retain(|pair| !script_hashes.contains(pair));
+            script_hashes.extend(rewritten.iter().cloned());
+        }
+
+        Ok(Rehashed {
+            new_code_hash,
+            deps,
+            script_hashes,
+        })
+    }
+}
@@ -0,0 +1,326 @@
+use ckb_script::ScriptGroupType;
+use ckb_traits::{CellDataProvider, HeaderProvider};
+use ckb_types::{
+    bytes::Bytes,
+    core::{cell::CellMeta, cell::ResolvedTransaction, ScriptHashType},
+    packed::{CellOutput, Script},
+    prelude::*,
+};
+use std::{
+    convert::TryFrom,
+    fmt,
+    fmt::Write,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    error::fmt_out_point, fmt_vec, groups::find_code_dep, list_script_groups, write_atomic,
+    DecoderRegistry, Error, Labels, ScriptGroupInfo, Since,
+};
+
+const SHANNONS_PER_CKB: u64 = 100_000_000;
+
+/// A Markdown description of a transaction for review: capacities in CKB, script hashes and the
+/// deps they resolve to, decoded `since` values, data hashes and the script groups of every
+/// cell, with their [`Labels`] if given and their data rendered by a [`DecoderRegistry`].
+/// Written next to a dump as `<name>.report.md` when enabled.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Report {
+    pub markdown: String,
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.markdown)
+    }
+}
+
+fn fmt_capacity(shannons: u64) -> String {
+    let fraction = format!("{:08}", shannons % SHANNONS_PER_CKB);
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        format!("{} CKB", shannons / SHANNONS_PER_CKB)
+    } else {
+        format!("{}.{} CKB", shannons / SHANNONS_PER_CKB, fraction)
+    }
+}
+
+fn fmt_group_type(group: &ScriptGroupInfo) -> &'static str {
+    match group.group_type {
+        ScriptGroupType::Lock => "lock",
+        ScriptGroupType::Type => "type",
+    }
+}
+
+fn fmt_hash_type(script: &Script) -> String {
+    match ScriptHashType::try_from(u8::from(script.hash_type())) {
+        Ok(ScriptHashType::Data) => String::from("data"),
+        Ok(ScriptHashType::Type) => String::from("type"),
+        Ok(ScriptHashType::Data1) => String::from("data1"),
+        Err(_) => format!("unknown ({})", u8::from(script.hash_type())),
+    }
+}
+
+fn fmt_data(data: &[u8]) -> String {
+    if data.is_empty() {
+        String::from("empty")
+    } else {
+        format!(
+            "{} bytes, blake2b `{}`",
+            data.len(),
+            fmt_vec(CellOutput::calc_data_hash(data).as_slice())
+        )
+    }
+}
+
+/// `: label` if there is one, to append to a heading or list item.
+fn fmt_label(label: Option<&str>) -> String {
+    label
+        .map(|label| format!(": {}", label))
+        .unwrap_or_default()
+}
+
+fn cell_data<DL: CellDataProvider>(data_loader: &DL, cell: &CellMeta) -> Bytes {
+    cell.mem_cell_data
+        .clone()
+        .or_else(|| data_loader.load_cell_data(cell))
+        .unwrap_or_default()
+}
+
+impl Report {
+    pub fn new<DL: CellDataProvider + HeaderProvider>(
+        data_loader: &DL,
+        resolved_tx: &ResolvedTransaction,
+    ) -> Self {
+        Self::with_labels(data_loader, resolved_tx, &Labels::new())
+    }
+
+    /// Names cells and scripts after `labels`.
+    pub fn with_labels<DL: CellDataProvider + HeaderProvider>(
+        data_loader: &DL,
+        resolved_tx: &ResolvedTransaction,
+        labels: &Labels,
+    ) -> Self {
+        Self::annotated(data_loader, resolved_tx, labels, &DecoderRegistry::new())
+    }
+
+    /// Names cells and scripts after `labels` and renders cell data with `decoders`.
+    pub fn annotated<DL: CellDataProvider + HeaderProvider>(
+        data_loader: &DL,
+        resolved_tx: &ResolvedTransaction,
+        labels: &Labels,
+        decoders: &DecoderRegistry,
+    ) -> Self {
+        let groups = list_script_groups(data_loader, resolved_tx, None);
+        let tx = &resolved_tx.transaction;
+        let mut md = String::new();
+
+        // Writing to a String cannot fail.
+        let _ = writeln!(md, "# Transaction `{}`\n", fmt_vec(tx.hash().as_slice()));
+
+        let _ = writeln!(md, "## Script groups\n");
+        let _ = writeln!(
+            md,
+            "| # | type | label | script hash | code | inputs | outputs |\n|---|---|---|---|---|---|---|"
+        );
+        for (i, group) in groups.iter().enumerate() {
+            let _ = writeln!(
+                md,
+                "| {} | {} | {} | `{}` | {} | {:?} | {:?} |",
+                i,
+                fmt_group_type(group),
+                labels.get_script(&group.script_hash).unwrap_or(""),
+                fmt_vec(group.script_hash.as_slice()),
+                group
+                    .code_dep
+                    .as_ref()
+                    .map(|dep| format!("cell dep {}", dep.index))
+                    .unwrap_or_else(|| String::from("not found")),
+                group.input_indices,
+                group.output_indices
+            );
+        }
+
+        let script = |md: &mut String, name: &str, script: &Script| {
+            let _ = writeln!(
+                md,
+                "- {}: `{}`{}\n  - code_hash `{}`, hash_type {}, args `{}`\n  - code: {}",
+                name,
+                fmt_vec(script.calc_script_hash().as_slice()),
+                fmt_label(labels.get_script(&script.calc_script_hash())),
+                fmt_vec(script.code_hash().as_slice()),
+                fmt_hash_type(script),
+                fmt_vec(&script.args().raw_data()),
+                find_code_dep(data_loader, resolved_tx, script, &None)
+                    .map(|dep| format!("cell dep {}", dep.index))
+                    .unwrap_or_else(|| String::from("not found")),
+            );
+        };
+        let cell = |md: &mut String, output: &CellOutput, data: &[u8], member: Vec<String>| {
+            let _ = writeln!(
+                md,
+                "- capacity: {} ({} shannons)",
+                fmt_capacity(output.capacity().unpack()),
+                Unpack::<u64>::unpack(&output.capacity())
+            );
+            script(md, "lock", &output.lock());
+            if let Some(type_) = output.type_().to_opt() {
+                script(md, "type", &type_);
+            }
+            let _ = writeln!(md, "- data: {}", fmt_data(data));
+            if let Some((name, decoded)) = decoders.decode(output, data) {
+                let _ = writeln!(md, "  - {}: {}", name, decoded);
+            }
+            if !member.is_empty() {
+                let _ = writeln!(md, "- groups: {}", member.join(", "));
+            }
+        };
+        let member = |index: usize, of: fn(&ScriptGroupInfo) -> &Vec<usize>| {
+            groups
+                .iter()
+                .enumerate()
+                .filter(|(_, group)| of(group).contains(&index))
+                .map(|(i, group)| format!("{} #{}", fmt_group_type(group), i))
+                .collect::<Vec<_>>()
+        };
+
+        let _ = writeln!(md, "\n## Inputs");
+        for (i, (input, meta)) in tx
+            .inputs()
+            .into_iter()
+            .zip(resolved_tx.resolved_inputs.iter())
+            .enumerate()
+        {
+            let since = Since(input.since().unpack());
+            let _ = writeln!(
+                md,
+                "\n### Input {}{}\n\n- previous output: `{}`\n- since: `{:#x}` ({})",
+                i,
+                fmt_label(labels.get_out_point(&input.previous_output())),
+                fmt_out_point(&input.previous_output()),
+                since.0,
+                since
+            );
+            for warning in since.warnings() {
+                let _ = writeln!(md, "  - warning: {}", warning);
+            }
+            cell(
+                &mut md,
+                &meta.cell_output,
+                &cell_data(data_loader, meta),
+                member(i, |group| &group.input_indices),
+            );
+        }
+
+        let _ = writeln!(md, "\n## Outputs");
+        for (i, (output, data)) in tx.outputs_with_data_iter().enumerate() {
+            let _ = writeln!(md, "\n### Output {}\n", i);
+            cell(
+                &mut md,
+                &output,
+                &data,
+                member(i, |group| &group.output_indices),
+            );
+        }
+
+        let _ = writeln!(md, "\n## Cell deps");
+        let deps = resolved_tx
+            .resolved_cell_deps
+            .iter()
+            .map(|meta| ("code", meta))
+            .chain(
+                resolved_tx
+                    .resolved_dep_groups
+                    .iter()
+                    .map(|meta| ("dep_group", meta)),
+            );
+        for (i, (dep_type, meta)) in deps.enumerate() {
+            let _ = writeln!(
+                md,
+                "\n### Cell dep {} ({}, `{}`){}\n",
+                i,
+                dep_type,
+                fmt_out_point(&meta.out_point),
+                fmt_label(labels.get_out_point(&meta.out_point))
+            );
+            let provides: Vec<String> = groups
+                .iter()
+                .enumerate()
+                .filter(|(_, group)| {
+                    group
+                        .code_dep
+                        .as_ref()
+                        .map(|dep| dep.index == i && dep_type == "code")
+                        .unwrap_or(false)
+                })
+                .map(|(index, group)| format!("{} #{}", fmt_group_type(group), index))
+                .collect();
+            if let Some(type_) = meta.cell_output.type_().to_opt() {
+                let _ = writeln!(
+                    md,
+                    "- type hash: `{}`",
+                    fmt_vec(type_.calc_script_hash().as_slice())
+                );
+            }
+            cell(
+                &mut md,
+                &meta.cell_output,
+                &cell_data(data_loader, meta),
+                Vec::new(),
+            );
+            if !provides.is_empty() {
+                let _ = writeln!(md, "- code of groups: {}", provides.join(", "));
+            }
+        }
+
+        let _ = writeln!(md, "\n## Header deps\n");
+        for (i, hash) in tx.header_deps_iter().enumerate() {
+            let _ = write!(md, "- {}: `{}`", i, fmt_vec(hash.as_slice()));
+            match data_loader.get_header(&hash) {
+                Some(header) => {
+                    let _ = writeln!(
+                        md,
+                        ", number {}, epoch {} + {}/{}, timestamp {} ms",
+                        header.number(),
+                        header.epoch().number(),
+                        header.epoch().index(),
+                        header.epoch().length(),
+                        header.timestamp()
+                    );
+                }
+                None => {
+                    let _ = writeln!(md, ", not found");
+                }
+            }
+        }
+
+        let _ = writeln!(md, "\n## Witnesses\n");
+        for (i, witness) in tx.witnesses().into_iter().enumerate() {
+            let readers: Vec<String> = groups
+                .iter()
+                .enumerate()
+                .filter(|(_, group)| group.witness_indices.contains(&i))
+                .map(|(index, group)| format!("{} #{}", fmt_group_type(group), index))
+                .collect();
+            let _ = write!(md, "- {}: {}", i, fmt_data(&witness.raw_data()));
+            if !readers.is_empty() {
+                let _ = write!(md, ", read by {}", readers.join(", "));
+            }
+            let _ = writeln!(md);
+        }
+
+        Report { markdown: md }
+    }
+
+    /// The report path for `tx_file`: `a/b.json` becomes `a/b.report.md`.
+    pub fn path_for(tx_file: &Path) -> PathBuf {
+        tx_file.with_extension("report.md")
+    }
+
+    /// Writes the report next to `tx_file` and returns its path.
+    pub fn write(&self, tx_file: &Path) -> Result<PathBuf, Error> {
+        let path = Self::path_for(tx_file);
+        write_atomic(&path, self.markdown.as_bytes())?;
+        Ok(path)
+    }
+}
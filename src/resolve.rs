@@ -0,0 +1,217 @@
+use ckb_traits::{CellDataProvider, HeaderProvider};
+use ckb_types::{
+    bytes::Bytes,
+    core::{
+        cell::{
+            resolve_transaction as ckb_resolve_transaction, CellMeta, CellMetaBuilder,
+            CellProvider, CellStatus, HeaderChecker, ResolvedTransaction,
+        },
+        error::OutPointError,
+        DepType, HeaderView, TransactionView,
+    },
+    packed::{Byte32, CellOutput, OutPoint, OutPointVec},
+    prelude::Entity,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+};
+
+use crate::{dump_to_path, script_groups, DumpOptions, Error};
+
+/// Looks up the cells and headers a transaction refers to.
+pub trait CellLookup {
+    fn get_cell(&self, out_point: &OutPoint) -> Option<(CellOutput, Bytes)>;
+
+    fn get_header(&self, _hash: &Byte32) -> Option<HeaderView> {
+        None
+    }
+}
+
+impl CellLookup for HashMap<OutPoint, (CellOutput, Bytes)> {
+    fn get_cell(&self, out_point: &OutPoint) -> Option<(CellOutput, Bytes)> {
+        self.get(out_point).cloned()
+    }
+}
+
+/// Adapts a `CellProvider` that can also load cell data and headers, e.g. a chain store, to
+/// [`CellLookup`].
+pub struct ProviderLookup<'a, P>(pub &'a P);
+
+impl<'a, P: CellProvider + CellDataProvider + HeaderProvider> CellLookup for ProviderLookup<'a, P> {
+    fn get_cell(&self, out_point: &OutPoint) -> Option<(CellOutput, Bytes)> {
+        match self.0.cell(out_point, true) {
+            CellStatus::Live(cell) => {
+                let data = self.0.load_cell_data(&cell)?;
+                Some((cell.cell_output, data))
+            }
+            _ => None,
+        }
+    }
+
+    fn get_header(&self, hash: &Byte32) -> Option<HeaderView> {
+        self.0.get_header(hash)
+    }
+}
+
+/// Serves a [`CellLookup`] and the given header deps to the resolver and the dumper.
+struct LookupDataLoader<'a, L> {
+    lookup: &'a L,
+    header_deps: Option<HashMap<Byte32, HeaderView>>,
+}
+
+impl<'a, L: CellLookup> CellProvider for LookupDataLoader<'a, L> {
+    fn cell(&self, out_point: &OutPoint, _eager_load: bool) -> CellStatus {
+        match self.lookup.get_cell(out_point) {
+            Some((output, data)) => CellStatus::live_cell(
+                CellMetaBuilder::from_cell_output(output, data)
+                    .out_point(out_point.clone())
+                    .build(),
+            ),
+            None => CellStatus::Unknown,
+        }
+    }
+}
+
+impl<'a, L: CellLookup> HeaderChecker for LookupDataLoader<'a, L> {
+    fn check_valid(&self, block_hash: &Byte32) -> Result<(), OutPointError> {
+        match self.get_header(block_hash) {
+            Some(_) => Ok(()),
+            None => Err(OutPointError::InvalidHeader(block_hash.clone())),
+        }
+    }
+}
+
+impl<'a, L: CellLookup> CellDataProvider for LookupDataLoader<'a, L> {
+    fn load_cell_data(&self, cell: &CellMeta) -> Option<Bytes> {
+        cell.mem_cell_data
+            .clone()
+            .or_else(|| self.get_cell_data(&cell.out_point))
+    }
+
+    fn load_cell_data_hash(&self, cell: &CellMeta) -> Option<Byte32> {
+        self.load_cell_data(cell)
+            .map(|data| CellOutput::calc_data_hash(&data))
+    }
+
+    fn get_cell_data(&self, out_point: &OutPoint) -> Option<Bytes> {
+        self.lookup.get_cell(out_point).map(|(_, data)| data)
+    }
+
+    fn get_cell_data_hash(&self, out_point: &OutPoint) -> Option<Byte32> {
+        self.get_cell_data(out_point)
+            .map(|data| CellOutput::calc_data_hash(&data))
+    }
+}
+
+impl<'a, L: CellLookup> HeaderProvider for LookupDataLoader<'a, L> {
+    fn get_header(&self, hash: &Byte32) -> Option<HeaderView> {
+        self.header_deps
+            .as_ref()
+            .and_then(|headers| headers.get(hash).cloned())
+            .or_else(|| self.lookup.get_header(hash))
+    }
+}
+
+/// Collects every input, cell dep and dep group member that `lookup` does not know about, so
+/// they can be reported all at once.
+fn find_unresolved<L: CellLookup>(tx: &TransactionView, lookup: &L) -> Vec<OutPoint> {
+    let mut unresolved: Vec<OutPoint> = Vec::new();
+    let mut check = |out_point: OutPoint| {
+        if lookup.get_cell(&out_point).is_none() && !unresolved.contains(&out_point) {
+            unresolved.push(out_point);
+        }
+    };
+
+    for out_point in tx.input_pts_iter() {
+        check(out_point);
+    }
+    for cell_dep in tx.cell_deps_iter() {
+        let out_point = cell_dep.out_point();
+        if cell_dep.dep_type() == DepType::DepGroup.into() {
+            if let Some((_, data)) = lookup.get_cell(&out_point) {
+                if let Ok(sub_out_points) = OutPointVec::from_slice(&data) {
+                    sub_out_points.into_iter().for_each(&mut check);
+                }
+            }
+        }
+        check(out_point);
+    }
+    unresolved
+}
+
+fn resolve<L: CellLookup>(
+    tx: &TransactionView,
+    data_loader: &LookupDataLoader<'_, L>,
+) -> Result<ResolvedTransaction, Error> {
+    let unresolved = find_unresolved(tx, data_loader.lookup);
+    if !unresolved.is_empty() {
+        return Err(Error::UnresolvedOutPoints(unresolved));
+    }
+
+    ckb_resolve_transaction(tx.clone(), &mut HashSet::new(), data_loader, data_loader).map_err(
+        |e| match e {
+            OutPointError::Unknown(out_point) => Error::UnresolvedOutPoints(vec![out_point]),
+            OutPointError::Dead(out_point) => {
+                if tx.input_pts_iter().filter(|p| *p == out_point).count() > 1 {
+                    Error::DuplicatedInput(out_point)
+                } else {
+                    Error::DeadCell(out_point)
+                }
+            }
+            OutPointError::InvalidDepGroup(out_point) => Error::InvalidDepGroup(out_point),
+            OutPointError::InvalidHeader(hash) => Error::UnknownHeader(hash),
+            e => Error::Resolve(e.to_string()),
+        },
+    )
+}
+
+/// Resolves the inputs, cell deps (expanding dep groups) and header deps of `tx` from `lookup`.
+///
+/// Header deps are searched in `header_deps` first, then in `lookup`. Every out point that cannot
+/// be resolved is reported in [`Error::UnresolvedOutPoints`].
+pub fn resolve_transaction<L: CellLookup>(
+    tx: &TransactionView,
+    lookup: &L,
+    header_deps: Option<HashMap<Byte32, HeaderView>>,
+) -> Result<ResolvedTransaction, Error> {
+    let data_loader = LookupDataLoader {
+        lookup,
+        header_deps,
+    };
+    resolve(tx, &data_loader)
+}
+
+/// Same as [`gen_json`](crate::gen_json), but starting from a `TransactionView` whose cells are
+/// resolved by [`resolve_transaction`].
+pub fn gen_json_from_tx<L: CellLookup>(
+    tx: &TransactionView,
+    lookup: &L,
+    header_deps: Option<HashMap<Byte32, HeaderView>>,
+    group_index: usize,
+    bin_path: &str,
+    json_file_name: &str,
+    dbg_addr: Option<&str>,
+) -> Result<String, Error> {
+    let data_loader = LookupDataLoader {
+        lookup,
+        header_deps,
+    };
+    let resolved_tx = resolve(tx, &data_loader)?;
+    let groups = script_groups(&resolved_tx);
+    let (_, script_group) = groups
+        .get(group_index)
+        .ok_or(Error::UnknownScriptGroup(group_index))?;
+    dump_to_path(
+        Some(&data_loader),
+        &resolved_tx,
+        None,
+        script_group,
+        Some(bin_path),
+        Path::new(json_file_name),
+        &DumpOptions {
+            dbg_addr: dbg_addr.map(String::from),
+            ..Default::default()
+        },
+    )
+}
@@ -0,0 +1,140 @@
+use ckb_types::core::EpochNumberWithFraction;
+use std::fmt;
+
+const RELATIVE_FLAG: u64 = 1 << 63;
+const METRIC_MASK: u64 = 0b11 << 61;
+const RESERVED_MASK: u64 = 0b1_1111 << 56;
+const VALUE_MASK: u64 = 0x00ff_ffff_ffff_ffff;
+
+/// What the lock condition of a [`Since`] is measured in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SinceMetric {
+    BlockNumber(u64),
+    Epoch(EpochNumberWithFraction),
+    /// Seconds. Absolute values are compared to the median time of the previous 37 blocks.
+    Timestamp(u64),
+}
+
+/// A misuse of the `since` encoding that makes the input fail verification.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SinceWarning {
+    /// Bits 56 to 60 must be zero.
+    ReservedBits(u64),
+    /// The metric flag `0b11` is not defined.
+    InvalidMetric,
+    /// The epoch fraction has an index past its length, or a zero length outside of a relative
+    /// `0/0`.
+    MalformedEpoch,
+}
+
+impl fmt::Display for SinceWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SinceWarning::ReservedBits(bits) => {
+                write!(f, "reserved bits {:#x} are set", bits)
+            }
+            SinceWarning::InvalidMetric => write!(f, "the metric flag 0b11 is invalid"),
+            SinceWarning::MalformedEpoch => {
+                write!(f, "the epoch fraction is not index < length")
+            }
+        }
+    }
+}
+
+/// The `since` field of an input, decoded following RFC 0017.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Since(pub u64);
+
+impl Since {
+    /// Whether the input has no lock condition.
+    pub fn is_none(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Whether the condition is relative to the block the input cell was committed in.
+    pub fn is_relative(&self) -> bool {
+        self.0 & RELATIVE_FLAG != 0
+    }
+
+    /// `None` if there is no lock condition or the metric flag is invalid.
+    pub fn metric(&self) -> Option<SinceMetric> {
+        if self.is_none() {
+            return None;
+        }
+        let value = self.0 & VALUE_MASK;
+        match (self.0 & METRIC_MASK) >> 61 {
+            0b00 => Some(SinceMetric::BlockNumber(value)),
+            // Unchecked, a zero length is kept as is rather than normalized to 0/1.
+            0b01 => Some(SinceMetric::Epoch(
+                EpochNumberWithFraction::from_full_value_unchecked(value),
+            )),
+            0b10 => Some(SinceMetric::Timestamp(value)),
+            _ => None,
+        }
+    }
+
+    /// The ways the encoding is misused, empty if the value is well formed.
+    pub fn warnings(&self) -> Vec<SinceWarning> {
+        let mut warnings = Vec::new();
+        if self.is_none() {
+            return warnings;
+        }
+        if self.0 & RESERVED_MASK != 0 {
+            warnings.push(SinceWarning::ReservedBits(self.0 & RESERVED_MASK));
+        }
+        match self.metric() {
+            None => warnings.push(SinceWarning::InvalidMetric),
+            Some(SinceMetric::Epoch(epoch)) => {
+                // Relative epochs are increments, for which 0/0 is allowed.
+                let well_formed = if self.is_relative() {
+                    epoch.is_well_formed_increment()
+                } else {
+                    epoch.is_well_formed()
+                };
+                if !well_formed {
+                    warnings.push(SinceWarning::MalformedEpoch);
+                }
+            }
+            Some(_) => {}
+        }
+        warnings
+    }
+}
+
+impl From<u64> for Since {
+    fn from(since: u64) -> Self {
+        Since(since)
+    }
+}
+
+/// E.g. `relative epoch 1 + 2/10` or `absolute block 1000`.
+impl fmt::Display for Since {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_none() {
+            return write!(f, "none");
+        }
+        let kind = if self.is_relative() {
+            "relative"
+        } else {
+            "absolute"
+        };
+        match self.metric() {
+            Some(SinceMetric::BlockNumber(number)) => write!(f, "{} block {}", kind, number),
+            Some(SinceMetric::Epoch(epoch)) => write!(
+                f,
+                "{} epoch {} + {}/{}",
+                kind,
+                epoch.number(),
+                epoch.index(),
+                epoch.length()
+            ),
+            Some(SinceMetric::Timestamp(seconds)) if self.is_relative() => {
+                write!(f, "{} {} seconds", kind, seconds)
+            }
+            Some(SinceMetric::Timestamp(seconds)) => {
+                write!(f, "{} median time {} (unix seconds)", kind, seconds)
+            }
+            None => write!(f, "{} invalid metric flag", kind),
+        }
+    }
+}
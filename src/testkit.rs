@@ -0,0 +1,554 @@
+//! Helpers to build resolved transactions in tests, enabled by the `testkit` feature.
+
+use ckb_chain_spec::consensus::{Consensus, ConsensusBuilder};
+use ckb_script::TxVerifyEnv;
+use ckb_traits::{CellDataProvider, HeaderProvider};
+use ckb_types::{
+    bytes::{BufMut, Bytes, BytesMut},
+    core::{
+        cell::{CellMeta, CellMetaBuilder, ResolvedTransaction},
+        hardfork::HardForkSwitch,
+        Capacity, DepType, EpochNumberWithFraction, HeaderView, ScriptHashType, TransactionBuilder,
+        TransactionView,
+    },
+    packed::{
+        Byte32, CellDep, CellInput, CellOutput, OutPoint, OutPointVec, Script, Uint128, Uint32,
+        Uint64, WitnessArgsBuilder,
+    },
+    prelude::*,
+};
+use rand::{thread_rng, Rng};
+use std::{collections::HashMap, io::Read};
+
+use crate::{resolve_transaction, CellLookup, VerifyEnvironment};
+
+/// An in-memory chain state holding the cells and headers a test transaction refers to.
+#[derive(Clone, Default)]
+pub struct DummyDataLoader {
+    pub cells: HashMap<OutPoint, (CellOutput, Bytes)>,
+    pub headers: HashMap<Byte32, HeaderView>,
+}
+
+impl DummyDataLoader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl CellDataProvider for DummyDataLoader {
+    // load Cell Data
+    fn load_cell_data(&self, cell: &CellMeta) -> Option<Bytes> {
+        cell.mem_cell_data
+            .clone()
+            .or_else(|| self.get_cell_data(&cell.out_point))
+    }
+
+    fn load_cell_data_hash(&self, cell: &CellMeta) -> Option<Byte32> {
+        self.load_cell_data(cell)
+            .map(|e| CellOutput::calc_data_hash(&e))
+    }
+
+    fn get_cell_data(&self, out_point: &OutPoint) -> Option<Bytes> {
+        self.cells.get(out_point).map(|(_, data)| data.clone())
+    }
+
+    fn get_cell_data_hash(&self, out_point: &OutPoint) -> Option<Byte32> {
+        self.get_cell_data(out_point)
+            .map(|data| CellOutput::calc_data_hash(&data))
+    }
+}
+
+impl HeaderProvider for DummyDataLoader {
+    fn get_header(&self, hash: &Byte32) -> Option<HeaderView> {
+        self.headers.get(hash).cloned()
+    }
+}
+
+impl CellLookup for DummyDataLoader {
+    fn get_cell(&self, out_point: &OutPoint) -> Option<(CellOutput, Bytes)> {
+        self.cells.get(out_point).cloned()
+    }
+
+    fn get_header(&self, hash: &Byte32) -> Option<HeaderView> {
+        self.headers.get(hash).cloned()
+    }
+}
+
+/// A code cell deployed by [`TxBuilder::deploy`] or [`TxBuilder::deploy_code`].
+#[derive(Clone, Debug)]
+pub struct DeployedCode {
+    pub out_point: OutPoint,
+    pub hash_type: ScriptHashType,
+    /// The data hash of the code, or the hash of the cell's type script for
+    /// `ScriptHashType::Type`.
+    pub code_hash: Byte32,
+}
+
+impl DeployedCode {
+    /// A script running this code with `args`.
+    pub fn script(&self, args: Bytes) -> Script {
+        Script::new_builder()
+            .code_hash(self.code_hash.clone())
+            .hash_type(self.hash_type.into())
+            .args(args.pack())
+            .build()
+    }
+}
+
+/// Builds a transaction and the [`DummyDataLoader`] holding every cell it spends or depends on.
+///
+/// ```ignore
+/// let mut builder = TxBuilder::new();
+/// let lock = builder.deploy(code, ScriptHashType::Data1).script(Bytes::new());
+/// let (resolved_tx, data_loader) = builder
+///     .input(lock.clone(), None, Bytes::new())
+///     .output(lock, None, Bytes::new())
+///     .witness_args(Some(signature), None, None)
+///     .build();
+/// ```
+#[derive(Default)]
+pub struct TxBuilder {
+    data_loader: DummyDataLoader,
+    cell_deps: Vec<CellDep>,
+    header_deps: Vec<Byte32>,
+    inputs: Vec<CellInput>,
+    outputs: Vec<CellOutput>,
+    outputs_data: Vec<Bytes>,
+    witnesses: Vec<Bytes>,
+    cell_count: u64,
+}
+
+impl TxBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a live cell to the data loader, without referring to it from the transaction.
+    pub fn live_cell(&mut self, output: CellOutput, data: Bytes) -> OutPoint {
+        self.cell_count += 1;
+        let mut tx_hash = [0xCEu8; 32];
+        tx_hash[..8].copy_from_slice(&self.cell_count.to_le_bytes());
+        let out_point = OutPoint::new(Byte32::new(tx_hash), 0);
+        self.data_loader
+            .cells
+            .insert(out_point.clone(), (output, data));
+        out_point
+    }
+
+    /// Deploys `code` in a live cell, without adding it to the cell deps. With
+    /// `ScriptHashType::Type`, the cell gets a unique type script.
+    pub fn deploy_code(&mut self, code: Bytes, hash_type: ScriptHashType) -> DeployedCode {
+        let lock = Script::default();
+        let (output, code_hash) = match hash_type {
+            ScriptHashType::Type => {
+                let type_script = Script::new_builder()
+                    .code_hash(Byte32::new([0xFE; 32]))
+                    .hash_type(ScriptHashType::Data1.into())
+                    .args(Bytes::from((self.cell_count + 1).to_le_bytes().to_vec()).pack())
+                    .build();
+                let code_hash = type_script.calc_script_hash();
+                (
+                    CellOutput::new_builder()
+                        .lock(lock)
+                        .type_(Some(type_script).pack()),
+                    code_hash,
+                )
+            }
+            ScriptHashType::Data | ScriptHashType::Data1 => (
+                CellOutput::new_builder().lock(lock),
+                CellOutput::calc_data_hash(&code),
+            ),
+        };
+        let output = output
+            .build_exact_capacity(Capacity::bytes(code.len()).unwrap())
+            .unwrap();
+        DeployedCode {
+            out_point: self.live_cell(output, code),
+            hash_type,
+            code_hash,
+        }
+    }
+
+    /// Deploys `code` and adds it to the cell deps.
+    pub fn deploy(&mut self, code: Bytes, hash_type: ScriptHashType) -> DeployedCode {
+        let deployed = self.deploy_code(code, hash_type);
+        self.cell_dep(deployed.out_point.clone(), DepType::Code);
+        deployed
+    }
+
+    pub fn cell_dep(&mut self, out_point: OutPoint, dep_type: DepType) -> &mut Self {
+        self.cell_deps.push(
+            CellDep::new_builder()
+                .out_point(out_point)
+                .dep_type(dep_type.into())
+                .build(),
+        );
+        self
+    }
+
+    /// Creates a dep group cell listing `out_points` and adds it to the cell deps.
+    pub fn dep_group(&mut self, out_points: &[OutPoint]) -> OutPoint {
+        let data = OutPointVec::new_builder()
+            .set(out_points.to_vec())
+            .build()
+            .as_bytes();
+        let output = CellOutput::new_builder()
+            .build_exact_capacity(Capacity::bytes(data.len()).unwrap())
+            .unwrap();
+        let out_point = self.live_cell(output, data);
+        self.cell_dep(out_point.clone(), DepType::DepGroup);
+        out_point
+    }
+
+    pub fn input(&mut self, lock: Script, type_: Option<Script>, data: Bytes) -> &mut Self {
+        self.input_with_since(lock, type_, data, 0)
+    }
+
+    /// Spends a new live cell with the given `since` value.
+    pub fn input_with_since(
+        &mut self,
+        lock: Script,
+        type_: Option<Script>,
+        data: Bytes,
+        since: u64,
+    ) -> &mut Self {
+        let output = gen_output(lock, type_, &data);
+        let out_point = self.live_cell(output, data);
+        self.inputs.push(CellInput::new(out_point, since));
+        self
+    }
+
+    pub fn output(&mut self, lock: Script, type_: Option<Script>, data: Bytes) -> &mut Self {
+        self.outputs.push(gen_output(lock, type_, &data));
+        self.outputs_data.push(data);
+        self
+    }
+
+    pub fn witness(&mut self, witness: Bytes) -> &mut Self {
+        self.witnesses.push(witness);
+        self
+    }
+
+    /// Adds a `WitnessArgs` witness.
+    pub fn witness_args(
+        &mut self,
+        lock: Option<Bytes>,
+        input_type: Option<Bytes>,
+        output_type: Option<Bytes>,
+    ) -> &mut Self {
+        let witness = WitnessArgsBuilder::default()
+            .lock(lock.pack())
+            .input_type(input_type.pack())
+            .output_type(output_type.pack())
+            .build();
+        self.witness(witness.as_bytes())
+    }
+
+    pub fn header_dep(&mut self, header: HeaderView) -> &mut Self {
+        self.header_deps.push(header.hash());
+        self.data_loader.headers.insert(header.hash(), header);
+        self
+    }
+
+    pub fn build_transaction(&self) -> TransactionView {
+        TransactionBuilder::default()
+            .cell_deps(self.cell_deps.clone())
+            .header_deps(self.header_deps.clone())
+            .inputs(self.inputs.clone())
+            .outputs(self.outputs.clone())
+            .outputs_data(self.outputs_data.iter().map(|data| data.pack()))
+            .witnesses(self.witnesses.iter().map(|witness| witness.pack()))
+            .build()
+    }
+
+    /// Builds and resolves the transaction.
+    pub fn build(&self) -> (ResolvedTransaction, DummyDataLoader) {
+        let tx = self.build_transaction();
+        let resolved_tx =
+            resolve_transaction(&tx, &self.data_loader, None).expect("resolve transaction failed");
+        (resolved_tx, self.data_loader.clone())
+    }
+}
+
+fn gen_output(lock: Script, type_: Option<Script>, data: &Bytes) -> CellOutput {
+    CellOutput::new_builder()
+        .lock(lock)
+        .type_(type_.pack())
+        .build_exact_capacity(Capacity::bytes(data.len()).unwrap())
+        .unwrap()
+}
+
+pub fn gen_rand_array32() -> [u8; 32] {
+    let mut buf = [0u8; 32];
+    let mut rng = thread_rng();
+    rng.fill(&mut buf);
+    buf
+}
+
+pub fn gen_rand_byte32() -> Byte32 {
+    Byte32::new(gen_rand_array32())
+}
+
+pub fn gen_rand_bytes(capacity: usize) -> Bytes {
+    let mut ret = BytesMut::with_capacity(capacity);
+
+    let mut rnd = thread_rng();
+    for _i in 0..capacity {
+        ret.put_u8(rnd.gen_range(0, 255));
+    }
+
+    ret.freeze()
+}
+
+pub struct CkbDepsData {
+    pub data: Bytes,
+    pub data_type: ScriptHashType,
+    pub tx_hash: Byte32,
+    pub tx_index: u32,
+
+    pub out_point: Option<OutPoint>,
+    pub type_hash: Option<Byte32>,
+}
+
+#[derive(Clone)]
+pub struct CkbScriptData {
+    /// Key of the code in the deps passed to [`gen_ckb_tx`].
+    pub script_id: u32,
+    pub args: Bytes,
+    pub witness: Bytes,
+}
+
+#[derive(Clone)]
+pub struct CkbCellScript {
+    pub lock: CkbScriptData,
+    pub type_: Option<CkbScriptData>,
+}
+
+#[derive(Clone)]
+pub struct CkbCellData {
+    pub input_tx_hash: Byte32,
+
+    pub input_data: Bytes,
+    pub output_data: Bytes,
+
+    pub input_script: CkbCellScript,
+    pub output_script: CkbCellScript,
+}
+
+/// A consensus with every hardfork disabled, except rfc 0032 from epoch 200.
+pub fn gen_consensus() -> Consensus {
+    let hardfork_switch = HardForkSwitch::new_builder()
+        .disable_rfc_0028()
+        .disable_rfc_0029()
+        .disable_rfc_0030()
+        .disable_rfc_0031()
+        .rfc_0032(200)
+        .disable_rfc_0036()
+        .disable_rfc_0038()
+        .build()
+        .unwrap();
+    ConsensusBuilder::default()
+        .hardfork_switch(hardfork_switch)
+        .build()
+}
+
+/// A commit environment at epoch 300, under the hardfork switches of [`gen_consensus`].
+pub fn gen_environment() -> VerifyEnvironment {
+    let epoch = EpochNumberWithFraction::new(300, 0, 1);
+    let header = HeaderView::new_advanced_builder()
+        .epoch(epoch.pack())
+        .build();
+    VerifyEnvironment::commit(&header).hardfork_switch(gen_consensus().hardfork_switch().clone())
+}
+
+/// A commit environment at epoch 300, see [`gen_environment`].
+pub fn gen_tx_env() -> TxVerifyEnv {
+    gen_environment().tx_verify_env()
+}
+
+pub fn load_bin(path: &str) -> Bytes {
+    let mut f =
+        std::fs::File::open(path).unwrap_or_else(|_| panic!("open bin file failed: {}", path));
+
+    let mut buf: Vec<u8> = Vec::new();
+    f.read_to_end(&mut buf)
+        .unwrap_or_else(|_| panic!("read bin file failed: {}", path));
+    Bytes::from(buf)
+}
+
+pub fn gen_cell_script(script: CkbScriptData, deps: &HashMap<u32, CkbDepsData>) -> Script {
+    let code = deps.get(&script.script_id).unwrap();
+    let code_hash = {
+        if code.data_type == ScriptHashType::Type {
+            code.type_hash.clone().unwrap()
+        } else {
+            CellOutput::calc_data_hash(&code.data)
+        }
+    };
+
+    Script::new_builder()
+        .args(script.args.pack())
+        .code_hash(code_hash)
+        .hash_type(code.data_type.into())
+        .build()
+}
+
+pub fn gen_cell_output(
+    cell_script: CkbCellScript,
+    cell_data: Bytes,
+    deps: &HashMap<u32, CkbDepsData>,
+) -> CellOutput {
+    let cell_capacity = Capacity::bytes(cell_data.len()).unwrap();
+    let mut input_cell = CellOutput::new_builder()
+        .capacity(cell_capacity.pack())
+        .lock(gen_cell_script(cell_script.lock.clone(), deps));
+    if cell_script.type_.is_some() {
+        input_cell = input_cell
+            .type_(Some(gen_cell_script(cell_script.type_.clone().unwrap(), deps)).pack());
+    }
+    input_cell.build()
+}
+
+/// Builds a transaction spending `cells`, with every entry of `deps` as a code cell dep.
+///
+/// Type deps get a type script whose hash is stored in [`CkbDepsData::type_hash`]. Each cell gets
+/// a `WitnessArgs` witness built from the `witness` of its scripts.
+pub fn gen_ckb_tx(
+    cells: Vec<CkbCellData>,
+    deps: HashMap<u32, CkbDepsData>,
+    header_dep: Vec<HeaderView>,
+) -> (ResolvedTransaction, DummyDataLoader) {
+    let mut tx_builder = TransactionBuilder::default();
+    let mut dummy = DummyDataLoader::new();
+
+    let mut deps_max_count: u32 = 0;
+    for id in deps.keys() {
+        if *id > deps_max_count {
+            deps_max_count = *id;
+        }
+    }
+    let mut deps = deps;
+    for i in 0..=deps_max_count {
+        let dep = deps.get_mut(&i);
+        if dep.is_none() {
+            continue;
+        }
+
+        let dep = dep.unwrap();
+
+        let out_point = OutPoint::new(dep.tx_hash.clone(), dep.tx_index);
+
+        let mut output_builder =
+            CellOutput::new_builder().capacity(Capacity::bytes(dep.data.len()).unwrap().pack());
+
+        if dep.data_type == ScriptHashType::Type {
+            let type_sc: Script = Script::new_builder()
+                .args(Bytes::from([0xFF; 32].to_vec()).pack())
+                .code_hash(Byte32::new([0xFE; 32]))
+                .hash_type(ScriptHashType::Data1.into())
+                .build();
+            dep.type_hash = Some(type_sc.calc_script_hash());
+            output_builder = output_builder.type_(Some(type_sc).pack());
+        }
+
+        // dep contract code
+        let sighash_all_cell = output_builder.build();
+        dummy
+            .cells
+            .insert(out_point.clone(), (sighash_all_cell, dep.data.clone()));
+        dep.out_point = Option::Some(out_point.clone());
+
+        tx_builder = tx_builder.cell_dep(
+            CellDep::new_builder()
+                .out_point(out_point)
+                .dep_type(DepType::Code.into())
+                .build(),
+        );
+    }
+
+    for cell in cells {
+        let input_cell = gen_cell_output(cell.input_script.clone(), cell.input_data.clone(), &deps);
+        let input_out_point = OutPoint::new(cell.input_tx_hash.clone(), 0);
+
+        dummy
+            .cells
+            .insert(input_out_point.clone(), (input_cell, cell.input_data));
+
+        tx_builder = tx_builder.input(CellInput::new(input_out_point, 0));
+        tx_builder = tx_builder
+            .output(gen_cell_output(
+                cell.output_script.clone(),
+                cell.output_data.clone(),
+                &deps,
+            ))
+            .output_data(cell.output_data.pack());
+
+        let mut witness = WitnessArgsBuilder::default();
+        if !cell.input_script.lock.witness.is_empty() {
+            witness = witness.lock(Some(cell.input_script.lock.witness).pack());
+        }
+
+        if let Some(t) = cell.input_script.type_ {
+            if !t.witness.is_empty() {
+                witness = witness.input_type(Some(t.witness).pack());
+            }
+        }
+
+        if let Some(t) = cell.output_script.type_ {
+            if !t.witness.is_empty() {
+                witness = witness.output_type(Some(t.witness).pack());
+            }
+        }
+
+        tx_builder = tx_builder.witness(witness.build().as_bytes().pack());
+    }
+
+    for header in header_dep {
+        tx_builder = tx_builder.header_dep(header.hash());
+        dummy.headers.insert(header.hash(), header);
+    }
+
+    let tx_builder = tx_builder.build();
+
+    let resolved_cell_deps = tx_builder
+        .cell_deps()
+        .into_iter()
+        .map(|deps_out_point| {
+            let (dep_output, dep_data) = dummy.cells.get(&deps_out_point.out_point()).unwrap();
+            CellMetaBuilder::from_cell_output(dep_output.to_owned(), dep_data.to_owned())
+                .out_point(deps_out_point.out_point())
+                .build()
+        })
+        .collect();
+
+    let mut resolved_inputs = Vec::new();
+    for i in 0..tx_builder.inputs().len() {
+        let previous_out_point = tx_builder.inputs().get(i).unwrap().previous_output();
+        let (input_output, input_data) = dummy.cells.get(&previous_out_point).unwrap();
+        resolved_inputs.push(
+            CellMetaBuilder::from_cell_output(input_output.to_owned(), input_data.to_owned())
+                .out_point(previous_out_point)
+                .build(),
+        );
+    }
+
+    let tx = ResolvedTransaction {
+        transaction: tx_builder.clone(),
+        resolved_cell_deps,
+        resolved_inputs,
+        resolved_dep_groups: vec![],
+    };
+    (tx, dummy)
+}
+
+pub fn u32_to_uint32(d: u32) -> Uint32 {
+    d.pack()
+}
+
+pub fn u64_to_uint64(d: u64) -> Uint64 {
+    d.pack()
+}
+
+pub fn u128_to_uint128(d: u128) -> Uint128 {
+    d.pack()
+}
@@ -0,0 +1,404 @@
+use ckb_types::{
+    bytes::Bytes,
+    core::{DepType, HeaderView, ScriptHashType},
+    packed::{Byte, BytesOpt, CellDep, CellOutput, OutPoint, Script, ScriptOpt, WitnessArgs},
+    prelude::*,
+};
+use std::{convert::TryFrom, fmt};
+
+use crate::{error::fmt_out_point, fmt_vec, MockTransaction};
+
+/// Data up to this length is shown in full, longer data by its length and hash.
+const SHORT_DATA: usize = 32;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ChangeKind {
+    Added(String),
+    Removed(String),
+    Changed { before: String, after: String },
+}
+
+/// A difference between two transactions, e.g. `outputs[1].lock.args` changed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Change {
+    pub path: String,
+    pub kind: ChangeKind,
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.kind {
+            ChangeKind::Added(value) => write!(f, "+ {}: {}", self.path, value),
+            ChangeKind::Removed(value) => write!(f, "- {}: {}", self.path, value),
+            ChangeKind::Changed { before, after } => {
+                write!(f, "~ {}: {} -> {}", self.path, before, after)
+            }
+        }
+    }
+}
+
+/// The differences between two mock transactions, see [`diff_mock_tx`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TxDiff {
+    pub changes: Vec<Change>,
+}
+
+impl TxDiff {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    fn added(&mut self, path: String, value: String) {
+        self.changes.push(Change {
+            path,
+            kind: ChangeKind::Added(value),
+        });
+    }
+
+    fn removed(&mut self, path: String, value: String) {
+        self.changes.push(Change {
+            path,
+            kind: ChangeKind::Removed(value),
+        });
+    }
+
+    fn compare(&mut self, path: String, before: String, after: String) {
+        if before != after {
+            self.changes.push(Change {
+                path,
+                kind: ChangeKind::Changed { before, after },
+            });
+        }
+    }
+
+    fn compare_script(&mut self, path: &str, before: &Script, after: &Script) {
+        if before.as_slice() == after.as_slice() {
+            return;
+        }
+        self.compare(
+            format!("{}.code_hash", path),
+            fmt_vec(before.code_hash().as_slice()),
+            fmt_vec(after.code_hash().as_slice()),
+        );
+        self.compare(
+            format!("{}.hash_type", path),
+            fmt_hash_type(before),
+            fmt_hash_type(after),
+        );
+        self.compare(
+            format!("{}.args", path),
+            fmt_data(&before.args().raw_data()),
+            fmt_data(&after.args().raw_data()),
+        );
+    }
+
+    fn compare_type(&mut self, path: &str, before: &ScriptOpt, after: &ScriptOpt) {
+        match (before.to_opt(), after.to_opt()) {
+            (Some(before), Some(after)) => self.compare_script(path, &before, &after),
+            (None, Some(after)) => self.added(String::from(path), fmt_script(&after)),
+            (Some(before), None) => self.removed(String::from(path), fmt_script(&before)),
+            (None, None) => {}
+        }
+    }
+
+    fn compare_cell(
+        &mut self,
+        path: &str,
+        (before, before_data): (&CellOutput, &[u8]),
+        (after, after_data): (&CellOutput, &[u8]),
+    ) {
+        self.compare(
+            format!("{}.capacity", path),
+            Unpack::<u64>::unpack(&before.capacity()).to_string(),
+            Unpack::<u64>::unpack(&after.capacity()).to_string(),
+        );
+        self.compare_script(&format!("{}.lock", path), &before.lock(), &after.lock());
+        self.compare_type(&format!("{}.type", path), &before.type_(), &after.type_());
+        self.compare(
+            format!("{}.data", path),
+            fmt_data(before_data),
+            fmt_data(after_data),
+        );
+    }
+
+    fn compare_header(&mut self, path: &str, before: &HeaderView, after: &HeaderView) {
+        self.compare(
+            format!("{}.hash", path),
+            fmt_vec(before.hash().as_slice()),
+            fmt_vec(after.hash().as_slice()),
+        );
+        self.compare(
+            format!("{}.number", path),
+            before.number().to_string(),
+            after.number().to_string(),
+        );
+        self.compare(
+            format!("{}.epoch", path),
+            before.epoch().to_string(),
+            after.epoch().to_string(),
+        );
+        self.compare(
+            format!("{}.timestamp", path),
+            before.timestamp().to_string(),
+            after.timestamp().to_string(),
+        );
+    }
+
+    fn compare_witness(&mut self, path: &str, before: &[u8], after: &[u8]) {
+        let args = |data: &[u8]| WitnessArgs::from_slice(data).ok();
+        match (args(before), args(after)) {
+            (Some(before), Some(after)) => {
+                let fields = [
+                    ("lock", before.lock(), after.lock()),
+                    ("input_type", before.input_type(), after.input_type()),
+                    ("output_type", before.output_type(), after.output_type()),
+                ];
+                for (name, before, after) in fields.iter() {
+                    self.compare(
+                        format!("{}.{}", path, name),
+                        fmt_bytes_opt(before),
+                        fmt_bytes_opt(after),
+                    );
+                }
+            }
+            _ => self.compare(String::from(path), fmt_data(before), fmt_data(after)),
+        }
+    }
+}
+
+impl fmt::Display for TxDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "no differences");
+        }
+        for (i, change) in self.changes.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", change)?;
+        }
+        Ok(())
+    }
+}
+
+fn fmt_hash_type(script: &Script) -> String {
+    match ScriptHashType::try_from(u8::from(script.hash_type())) {
+        Ok(ScriptHashType::Data) => String::from("data"),
+        Ok(ScriptHashType::Type) => String::from("type"),
+        Ok(ScriptHashType::Data1) => String::from("data1"),
+        Err(_) => format!("{}", u8::from(script.hash_type())),
+    }
+}
+
+fn fmt_script(script: &Script) -> String {
+    format!(
+        "{} {} {}",
+        fmt_vec(script.code_hash().as_slice()),
+        fmt_hash_type(script),
+        fmt_data(&script.args().raw_data())
+    )
+}
+
+/// Short data in full, longer data as its length and blake2b hash.
+fn fmt_data(data: &[u8]) -> String {
+    if data.len() <= SHORT_DATA {
+        fmt_vec(data)
+    } else {
+        format!(
+            "{} bytes {}",
+            data.len(),
+            fmt_vec(CellOutput::calc_data_hash(data).as_slice())
+        )
+    }
+}
+
+fn fmt_bytes_opt(bytes: &BytesOpt) -> String {
+    match bytes.to_opt() {
+        Some(bytes) => fmt_data(&bytes.raw_data()),
+        None => String::from("none"),
+    }
+}
+
+fn fmt_script_opt(script: &ScriptOpt) -> String {
+    match script.to_opt() {
+        Some(script) => fmt_script(&script),
+        None => String::from("none"),
+    }
+}
+
+/// The dep type as written in the JSON dump.
+fn fmt_dep_type(dep_type: Byte) -> String {
+    match DepType::try_from(dep_type) {
+        Ok(DepType::Code) => String::from("code"),
+        Ok(DepType::DepGroup) => String::from("dep_group"),
+        Err(_) => format!("{}", u8::from(dep_type)),
+    }
+}
+
+fn fmt_cell(output: &CellOutput, data: &[u8]) -> String {
+    format!(
+        "capacity {}, lock {}, type {}, data {}",
+        Unpack::<u64>::unpack(&output.capacity()),
+        fmt_script(&output.lock()),
+        fmt_script_opt(&output.type_()),
+        fmt_data(data)
+    )
+}
+
+fn fmt_cell_dep(dep: &CellDep, output: &CellOutput, data: &[u8]) -> String {
+    format!(
+        "dep_type {}, {}",
+        fmt_dep_type(dep.dep_type()),
+        fmt_cell(output, data)
+    )
+}
+
+/// Pairs the items of `before` and `after` with the same out point, then the remaining ones in
+/// order. Unpaired items are returned on their own side.
+fn align<'a, T>(
+    before: &'a [T],
+    after: &'a [T],
+    out_point: impl Fn(&T) -> OutPoint,
+) -> Vec<(Option<&'a T>, Option<&'a T>)> {
+    let mut after_left: Vec<Option<&T>> = after.iter().map(Some).collect();
+    let mut before_left = Vec::new();
+    let mut pairs = Vec::new();
+    for item in before {
+        let found = after_left
+            .iter_mut()
+            .find(|other| other.map(|other| out_point(other) == out_point(item)) == Some(true));
+        match found {
+            Some(other) => pairs.push((Some(item), other.take())),
+            None => before_left.push(item),
+        }
+    }
+    let mut after_left = after_left.into_iter().flatten();
+    for item in before_left {
+        pairs.push((Some(item), after_left.next()));
+    }
+    pairs.extend(after_left.map(|item| (None, Some(item))));
+    pairs
+}
+
+/// Compares two mock transactions field by field.
+///
+/// Inputs and cell deps are aligned by out point, outputs, header deps and witnesses by index.
+/// Witnesses that parse as `WitnessArgs` are compared by field. Data longer than 32 bytes is
+/// reported by length and hash.
+pub fn diff_mock_tx(before: &MockTransaction, after: &MockTransaction) -> TxDiff {
+    let mut diff = TxDiff::default();
+
+    let inputs = align(&before.mock_info.inputs, &after.mock_info.inputs, |input| {
+        input.input.previous_output()
+    });
+    for (before, after) in inputs {
+        match (before, after) {
+            (Some(before), Some(after)) => {
+                let path = format!("inputs[{}]", fmt_out_point(&before.input.previous_output()));
+                diff.compare(
+                    format!("{}.previous_output", path),
+                    fmt_out_point(&before.input.previous_output()),
+                    fmt_out_point(&after.input.previous_output()),
+                );
+                diff.compare(
+                    format!("{}.since", path),
+                    format!("{:#x}", Unpack::<u64>::unpack(&before.input.since())),
+                    format!("{:#x}", Unpack::<u64>::unpack(&after.input.since())),
+                );
+                diff.compare_cell(
+                    &path,
+                    (&before.output, &before.data),
+                    (&after.output, &after.data),
+                );
+            }
+            (None, Some(after)) => diff.added(
+                format!("inputs[{}]", fmt_out_point(&after.input.previous_output())),
+                fmt_cell(&after.output, &after.data),
+            ),
+            (Some(before), None) => diff.removed(
+                format!("inputs[{}]", fmt_out_point(&before.input.previous_output())),
+                fmt_cell(&before.output, &before.data),
+            ),
+            (None, None) => {}
+        }
+    }
+
+    let before_outputs: Vec<(CellOutput, Bytes)> = before.tx.outputs_with_data_iter().collect();
+    let after_outputs: Vec<(CellOutput, Bytes)> = after.tx.outputs_with_data_iter().collect();
+    for i in 0..before_outputs.len().max(after_outputs.len()) {
+        let path = format!("outputs[{}]", i);
+        match (before_outputs.get(i), after_outputs.get(i)) {
+            (Some(before), Some(after)) => {
+                diff.compare_cell(&path, (&before.0, &before.1), (&after.0, &after.1))
+            }
+            (None, Some(after)) => diff.added(path, fmt_cell(&after.0, &after.1)),
+            (Some(before), None) => diff.removed(path, fmt_cell(&before.0, &before.1)),
+            (None, None) => {}
+        }
+    }
+
+    let cell_deps = align(
+        &before.mock_info.cell_deps,
+        &after.mock_info.cell_deps,
+        |dep| dep.cell_dep.out_point(),
+    );
+    for (before, after) in cell_deps {
+        match (before, after) {
+            (Some(before), Some(after)) => {
+                let path = format!("cell_deps[{}]", fmt_out_point(&before.cell_dep.out_point()));
+                diff.compare(
+                    format!("{}.out_point", path),
+                    fmt_out_point(&before.cell_dep.out_point()),
+                    fmt_out_point(&after.cell_dep.out_point()),
+                );
+                diff.compare(
+                    format!("{}.dep_type", path),
+                    fmt_dep_type(before.cell_dep.dep_type()),
+                    fmt_dep_type(after.cell_dep.dep_type()),
+                );
+                diff.compare_cell(
+                    &path,
+                    (&before.output, &before.data),
+                    (&after.output, &after.data),
+                );
+            }
+            (None, Some(after)) => diff.added(
+                format!("cell_deps[{}]", fmt_out_point(&after.cell_dep.out_point())),
+                fmt_cell_dep(&after.cell_dep, &after.output, &after.data),
+            ),
+            (Some(before), None) => diff.removed(
+                format!("cell_deps[{}]", fmt_out_point(&before.cell_dep.out_point())),
+                fmt_cell_dep(&before.cell_dep, &before.output, &before.data),
+            ),
+            (None, None) => {}
+        }
+    }
+
+    let before_headers = &before.mock_info.header_deps;
+    let after_headers = &after.mock_info.header_deps;
+    for i in 0..before_headers.len().max(after_headers.len()) {
+        let path = format!("header_deps[{}]", i);
+        match (before_headers.get(i), after_headers.get(i)) {
+            (Some(before), Some(after)) => diff.compare_header(&path, before, after),
+            (None, Some(after)) => diff.added(path, fmt_vec(after.hash().as_slice())),
+            (Some(before), None) => diff.removed(path, fmt_vec(before.hash().as_slice())),
+            (None, None) => {}
+        }
+    }
+
+    let before_witnesses = before.tx.witnesses();
+    let after_witnesses = after.tx.witnesses();
+    for i in 0..before_witnesses.len().max(after_witnesses.len()) {
+        let path = format!("witnesses[{}]", i);
+        match (before_witnesses.get(i), after_witnesses.get(i)) {
+            (Some(before), Some(after)) => {
+                diff.compare_witness(&path, &before.raw_data(), &after.raw_data())
+            }
+            (None, Some(after)) => diff.added(path, fmt_data(&after.raw_data())),
+            (Some(before), None) => diff.removed(path, fmt_data(&before.raw_data())),
+            (None, None) => {}
+        }
+    }
+
+    diff
+}
@@ -0,0 +1,362 @@
+use ckb_script::{ScriptError, TransactionScriptError, TransactionScriptsVerifier};
+use ckb_traits::{CellDataProvider, HeaderProvider};
+use ckb_types::{
+    core::{cell::ResolvedTransaction, Cycle},
+    packed::Byte32,
+};
+use std::{cell::RefCell, env, fmt, path::PathBuf, str::FromStr};
+
+use crate::{
+    get_bin_hash,
+    recorder::{self, Recorded},
+    script_groups, DebugCapture, DecoderRegistry, Dump, DumpDir, Error, Expected, GroupOutput,
+    Labels, Manifest, MockTransaction, Report, VerifyEnvironment,
+};
+
+/// Wraps a `TransactionScriptsVerifier` and dumps the transaction when verification fails.
+///
+/// The failing group's command line is returned in [`VerifyError::dump`], and every group's
+/// command line is recorded in the [`Manifest`] written next to the transaction file, with the
+/// cycles the group consumed. Commands pass ckb-debugger what the limit given to
+/// [`Self::verify`] left the group as `--max-cycles`. The cycles and debug output of each group
+/// are written next to it as well, see [`Expected`].
+pub struct DumpingVerifier<'a, DL> {
+    verifier: TransactionScriptsVerifier<'a, DL>,
+    data_loader: &'a DL,
+    resolved_tx: &'a ResolvedTransaction,
+    dump_dir: DumpDir,
+    mode: DumpMode,
+    bin_path: Option<String>,
+    capture: DebugCapture,
+    expected: RefCell<Option<Expected>>,
+    dumped: RefCell<Option<Result<Manifest, Error>>>,
+    report: bool,
+    labels: Labels,
+    decoders: DecoderRegistry,
+    environment: Option<VerifyEnvironment>,
+    max_cycles: Option<Cycle>,
+}
+
+/// The directory [`DumpingVerifier::from_env`] dumps into. Dumping is disabled when unset.
+pub const DUMP_DIR_VAR: &str = "CKB_DUMPER_DIR";
+/// The [`DumpMode`] of [`DumpingVerifier::from_env`]: `on-failure` (the default), `always` or
+/// `never`.
+pub const DUMP_MODE_VAR: &str = "CKB_DUMPER_MODE";
+/// Set to `1` to have [`DumpingVerifier::from_env`] write a [`Report`] with every dump.
+pub const DUMP_REPORT_VAR: &str = "CKB_DUMPER_REPORT";
+
+/// When [`DumpingVerifier::verify`] dumps the transaction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DumpMode {
+    Never,
+    OnFailure,
+    Always,
+}
+
+impl FromStr for DumpMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "never" => Ok(DumpMode::Never),
+            "on-failure" => Ok(DumpMode::OnFailure),
+            "always" => Ok(DumpMode::Always),
+            _ => Err(format!(
+                "invalid dump mode {:?}, expected never, on-failure or always",
+                s
+            )),
+        }
+    }
+}
+
+/// A verification failure, together with where the transaction was dumped.
+#[derive(Debug)]
+pub struct VerifyError {
+    pub error: TransactionScriptError,
+    /// Index of the failing group in [`script_groups`] order.
+    pub group_index: usize,
+    /// `None` if dumping is disabled by [`DumpMode::Never`].
+    pub dump: Option<Result<Dump, Error>>,
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.error)?;
+        match &self.dump {
+            None => Ok(()),
+            Some(Ok(dump)) => write!(
+                f,
+                "\ntransaction dumped to {}, debug with:\n{}",
+                dump.tx_file.to_string_lossy(),
+                dump.command
+            ),
+            Some(Err(e)) => write!(f, "\ndump transaction failed: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for VerifyError {}
+
+impl<'a, DL: CellDataProvider + HeaderProvider> DumpingVerifier<'a, DL> {
+    pub fn new(
+        resolved_tx: &'a ResolvedTransaction,
+        data_loader: &'a DL,
+        dump_dir: DumpDir,
+    ) -> Self {
+        let capture = DebugCapture::new();
+        let mut verifier = TransactionScriptsVerifier::new(resolved_tx, data_loader);
+        verifier.set_debug_printer(capture.printer());
+        DumpingVerifier {
+            verifier,
+            data_loader,
+            resolved_tx,
+            dump_dir,
+            mode: DumpMode::OnFailure,
+            bin_path: None,
+            capture,
+            expected: RefCell::new(None),
+            dumped: RefCell::new(None),
+            report: false,
+            labels: Labels::new(),
+            decoders: DecoderRegistry::new(),
+            environment: None,
+            max_cycles: None,
+        }
+    }
+
+    /// Configures dumping from the environment, so a test suite can be rerun with dumps
+    /// without code changes:
+    ///
+    /// ```text
+    /// CKB_DUMPER_DIR=target/ckb-dumps CKB_DUMPER_MODE=always CKB_DUMPER_REPORT=1 cargo test
+    /// ```
+    ///
+    /// Without [`DUMP_DIR_VAR`] nothing is dumped by `verify`, and an explicit [`Self::dump`]
+    /// writes into the system temp directory.
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`DUMP_MODE_VAR`] is not a valid [`DumpMode`].
+    pub fn from_env(resolved_tx: &'a ResolvedTransaction, data_loader: &'a DL) -> Self {
+        let mode = match env::var(DUMP_MODE_VAR) {
+            Ok(mode) if !mode.is_empty() => mode
+                .parse()
+                .unwrap_or_else(|e| panic!("{}: {}", DUMP_MODE_VAR, e)),
+            _ => DumpMode::OnFailure,
+        };
+        let report = matches!(env::var(DUMP_REPORT_VAR), Ok(report) if report == "1");
+        match env::var_os(DUMP_DIR_VAR) {
+            Some(dir) if !dir.is_empty() => {
+                Self::new(resolved_tx, data_loader, DumpDir::new(PathBuf::from(dir))).mode(mode)
+            }
+            _ => Self::new(resolved_tx, data_loader, DumpDir::new(env::temp_dir()))
+                .mode(DumpMode::Never),
+        }
+        .report(report)
+    }
+
+    pub fn mode(mut self, mode: DumpMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Also writes a [`Report`] next to every dump.
+    pub fn report(mut self, report: bool) -> Self {
+        self.report = report;
+        self
+    }
+
+    /// Names cells and scripts in the manifest, report and command lines of dumps.
+    pub fn labels(mut self, labels: Labels) -> Self {
+        self.labels = labels;
+        self
+    }
+
+    /// The decoders the [`Report`] renders cell data with, the built-in ones by default.
+    pub fn decoders(mut self, decoders: DecoderRegistry) -> Self {
+        self.decoders = decoders;
+        self
+    }
+
+    /// Records the environment of the test in dumps and runs their groups on the VM version it
+    /// selects in ckb-debugger. It does not change in-process verification, see
+    /// [`VerifyEnvironment`].
+    pub fn environment(mut self, environment: VerifyEnvironment) -> Self {
+        self.environment = Some(environment);
+        self
+    }
+
+    /// The cycle limit passed to ckb-debugger by dumps written without [`Self::verify`], which
+    /// uses its own.
+    pub fn max_cycles(mut self, max_cycles: Cycle) -> Self {
+        self.max_cycles = Some(max_cycles);
+        self
+    }
+
+    /// Replaces the data of the deps holding this binary with `0x` and passes it to
+    /// ckb-debugger through `--bin`. Without it, the debugger runs the code from the dump.
+    pub fn bin_path(mut self, bin_path: &str) -> Self {
+        self.bin_path = Some(String::from(bin_path));
+        self
+    }
+
+    /// Installs a printer for the debug output of scripts. The output is captured for the
+    /// [`Expected`] sidecar either way.
+    pub fn set_debug_printer<F: Fn(&Byte32, &str) + 'static>(&mut self, func: F) {
+        let capture = self.capture.printer();
+        self.verifier
+            .set_debug_printer(move |script_hash: &Byte32, msg: &str| {
+                capture(script_hash, msg);
+                func(script_hash, msg);
+            });
+    }
+
+    /// The per-group cycles and debug output of the last [`Self::verify`].
+    pub fn expected(&self) -> Option<Expected> {
+        self.expected.borrow().clone()
+    }
+
+    /// Takes the dump written after the last passing [`Self::verify`] in [`DumpMode::Always`],
+    /// or the error writing it. A failing verify returns its dump in [`VerifyError::dump`].
+    pub fn take_dump(&self) -> Option<Result<Manifest, Error>> {
+        self.dumped.borrow_mut().take()
+    }
+
+    pub fn verifier(&self) -> &TransactionScriptsVerifier<'a, DL> {
+        &self.verifier
+    }
+
+    /// Dumps the transaction, with every group's command line in the manifest.
+    pub fn dump(&self) -> Result<Manifest, Error> {
+        self.write_dump(None, self.max_cycles)
+    }
+
+    fn prepare_dump(
+        &self,
+        failed: Option<(usize, &TransactionScriptError)>,
+        max_cycles: Option<Cycle>,
+    ) -> Result<Recorded, Error> {
+        let bin_path = self
+            .bin_path
+            .as_ref()
+            .map(std::fs::canonicalize)
+            .transpose()?;
+
+        let mut mock_tx = MockTransaction::build(self.resolved_tx, &None, Some(self.data_loader));
+        if let Some(bin_path) = &bin_path {
+            mock_tx.blank_bin_hash(&get_bin_hash(bin_path.to_str().unwrap())?);
+        }
+        mock_tx.environment = self.environment.clone();
+        Ok(Recorded {
+            mock_tx,
+            groups: script_groups(self.resolved_tx),
+            bin_path,
+            failed: failed.map(|(i, e)| (i, e.to_string())),
+            expected: self.expected(),
+            report: if self.report {
+                Some(Report::annotated(
+                    self.data_loader,
+                    self.resolved_tx,
+                    &self.labels,
+                    &self.decoders,
+                ))
+            } else {
+                None
+            },
+            labels: self.labels.clone(),
+            max_cycles,
+        })
+    }
+
+    fn write_dump(
+        &self,
+        failed: Option<(usize, &TransactionScriptError)>,
+        max_cycles: Option<Cycle>,
+    ) -> Result<Manifest, Error> {
+        self.prepare_dump(failed, max_cycles)?.write(&self.dump_dir)
+    }
+
+    /// Hands the verified transaction to the recording of [`crate::Recording`], if any. An error
+    /// preparing the dump is reported by the recording.
+    fn record(&self, failed: Option<(usize, &TransactionScriptError)>, max_cycles: Cycle) {
+        if recorder::is_recording() {
+            recorder::record(self.prepare_dump(failed, Some(max_cycles)));
+        }
+    }
+
+    /// Verifies the script groups one by one, in the same order and with the same cycle
+    /// accounting as `TransactionScriptsVerifier::verify`. Depending on the [`DumpMode`], the
+    /// transaction is dumped when a group fails or after every verification.
+    pub fn verify(&self, max_cycles: Cycle) -> Result<Cycle, VerifyError> {
+        let groups = script_groups(self.resolved_tx);
+        let mut outputs: Vec<GroupOutput> = groups
+            .iter()
+            .map(|(script_hash, group)| GroupOutput {
+                group_type: group.group_type,
+                script_hash: script_hash.clone(),
+                cycles: None,
+                debug_output: String::new(),
+                error: None,
+            })
+            .collect();
+        self.capture.clear();
+        *self.dumped.borrow_mut() = None;
+
+        let mut cycles: Cycle = 0;
+        for (group_index, (script_hash, group)) in groups.iter().enumerate() {
+            self.capture.set_group_type(group.group_type);
+            let result = self
+                .verifier
+                .verify_single(group.group_type, script_hash, max_cycles - cycles)
+                .and_then(|used| {
+                    cycles
+                        .checked_add(used)
+                        .ok_or(ScriptError::CyclesOverflow(cycles, used))
+                });
+            outputs[group_index].debug_output = self.capture.output(group.group_type, script_hash);
+            match result {
+                Ok(total) => {
+                    outputs[group_index].cycles = Some(total - cycles);
+                    cycles = total;
+                }
+                Err(e) => {
+                    let error = e.source(group);
+                    outputs[group_index].error = Some(error.to_string());
+                    *self.expected.borrow_mut() = Some(Expected { groups: outputs });
+                    self.record(Some((group_index, &error)), max_cycles);
+                    let dump = match self.mode {
+                        DumpMode::Never => None,
+                        DumpMode::OnFailure | DumpMode::Always => Some(
+                            self.write_dump(Some((group_index, &error)), Some(max_cycles))
+                                .map(|manifest| Dump {
+                                    command: manifest.groups[group_index].commented_command(),
+                                    tx_file: manifest.tx_file,
+                                }),
+                        ),
+                    };
+                    return Err(VerifyError {
+                        error,
+                        group_index,
+                        dump,
+                    });
+                }
+            }
+        }
+        *self.expected.borrow_mut() = Some(Expected { groups: outputs });
+        self.record(None, max_cycles);
+        if self.mode == DumpMode::Always {
+            *self.dumped.borrow_mut() = Some(self.write_dump(None, Some(max_cycles)));
+        }
+        Ok(cycles)
+    }
+}
+
+/// Verifies `resolved_tx` with a [`DumpingVerifier::from_env`], meant to be the one verify
+/// call every test of a suite goes through.
+pub fn verify_and_dump<DL: CellDataProvider + HeaderProvider>(
+    resolved_tx: &ResolvedTransaction,
+    data_loader: &DL,
+    max_cycles: Cycle,
+) -> Result<Cycle, VerifyError> {
+    DumpingVerifier::from_env(resolved_tx, data_loader).verify(max_cycles)
+}
@@ -0,0 +1,240 @@
+use ckb_script::{ScriptGroup, ScriptGroupType};
+use ckb_types::{
+    core::{cell::ResolvedTransaction, TransactionView},
+    packed::{Byte32, BytesOpt, WitnessArgs},
+    prelude::*,
+};
+use json::JsonValue;
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use crate::{fmt_vec, script_groups, write_atomic, Error};
+
+/// The `WitnessArgs` field a script conventionally reads from a witness.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WitnessField {
+    /// Read by the lock of the input at the same index.
+    Lock,
+    /// Read by the type script of the input at the same index.
+    InputType,
+    /// Read by the type script of the output at the same index.
+    OutputType,
+}
+
+impl fmt::Display for WitnessField {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WitnessField::Lock => write!(f, "lock"),
+            WitnessField::InputType => write!(f, "input_type"),
+            WitnessField::OutputType => write!(f, "output_type"),
+        }
+    }
+}
+
+/// A script group that reads a witness through `Source::GroupInput` or `Source::GroupOutput`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WitnessConsumer {
+    pub group_index: usize,
+    pub group_type: ScriptGroupType,
+    pub script_hash: Byte32,
+    pub field: WitnessField,
+    /// Whether this is the first witness of the group, the one scripts usually decode.
+    pub first: bool,
+}
+
+/// A witness of the transaction, decoded as `WitnessArgs` where possible.
+#[derive(Clone, Debug)]
+pub struct WitnessAnnotation {
+    pub index: usize,
+    pub length: usize,
+    /// `None` if the witness is not a `WitnessArgs`.
+    pub witness_args: Option<WitnessArgs>,
+    pub consumers: Vec<WitnessConsumer>,
+}
+
+/// A witness a script group most likely fails on.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WitnessProblem {
+    /// The first input of a lock group has no witness.
+    Missing {
+        group_index: usize,
+        witness_index: usize,
+    },
+    /// The first witness of a group is not a `WitnessArgs`. Empty witnesses only count for lock
+    /// groups, type scripts commonly leave theirs empty.
+    Malformed {
+        group_index: usize,
+        witness_index: usize,
+    },
+}
+
+impl fmt::Display for WitnessProblem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WitnessProblem::Missing {
+                group_index,
+                witness_index,
+            } => write!(
+                f,
+                "group {} has no witness at index {}",
+                group_index, witness_index
+            ),
+            WitnessProblem::Malformed {
+                group_index,
+                witness_index,
+            } => write!(
+                f,
+                "witness {} of group {} is not a WitnessArgs",
+                witness_index, group_index
+            ),
+        }
+    }
+}
+
+/// The witnesses of a transaction, annotated with the groups that read them. Written next to a
+/// dump as `<name>.witnesses.json`.
+#[derive(Clone, Debug, Default)]
+pub struct WitnessAnnotations {
+    /// In witness order.
+    pub witnesses: Vec<WitnessAnnotation>,
+    pub problems: Vec<WitnessProblem>,
+}
+
+/// Annotates the witnesses of `resolved_tx`, see [`WitnessAnnotations`].
+pub fn annotate_witnesses(resolved_tx: &ResolvedTransaction) -> WitnessAnnotations {
+    WitnessAnnotations::from_groups(&resolved_tx.transaction, &script_groups(resolved_tx))
+}
+
+impl WitnessAnnotations {
+    pub(crate) fn from_groups(tx: &TransactionView, groups: &[(Byte32, ScriptGroup)]) -> Self {
+        let mut witnesses: Vec<WitnessAnnotation> = tx
+            .witnesses()
+            .into_iter()
+            .enumerate()
+            .map(|(index, witness)| {
+                let data = witness.raw_data();
+                WitnessAnnotation {
+                    index,
+                    length: data.len(),
+                    witness_args: WitnessArgs::from_slice(&data).ok(),
+                    consumers: Vec::new(),
+                }
+            })
+            .collect();
+        let mut problems = Vec::new();
+
+        for (group_index, (script_hash, group)) in groups.iter().enumerate() {
+            let (input_field, lock) = match group.group_type {
+                ScriptGroupType::Lock => (WitnessField::Lock, true),
+                ScriptGroupType::Type => (WitnessField::InputType, false),
+            };
+            let indices = group.input_indices.iter().map(|i| (*i, input_field)).chain(
+                group
+                    .output_indices
+                    .iter()
+                    .map(|i| (*i, WitnessField::OutputType)),
+            );
+            for (n, (witness_index, field)) in indices.enumerate() {
+                let first = n == 0;
+                match witnesses.get_mut(witness_index) {
+                    Some(witness) => {
+                        let malformed =
+                            witness.witness_args.is_none() && (lock || witness.length > 0);
+                        if first && malformed {
+                            problems.push(WitnessProblem::Malformed {
+                                group_index,
+                                witness_index,
+                            });
+                        }
+                        witness.consumers.push(WitnessConsumer {
+                            group_index,
+                            group_type: group.group_type,
+                            script_hash: script_hash.clone(),
+                            field,
+                            first,
+                        });
+                    }
+                    None if first && lock => problems.push(WitnessProblem::Missing {
+                        group_index,
+                        witness_index,
+                    }),
+                    None => {}
+                }
+            }
+        }
+
+        WitnessAnnotations {
+            witnesses,
+            problems,
+        }
+    }
+
+    /// The sidecar path for `tx_file`: `a/b.json` becomes `a/b.witnesses.json`.
+    pub fn path_for(tx_file: &Path) -> PathBuf {
+        tx_file.with_extension("witnesses.json")
+    }
+
+    pub fn to_json(&self) -> JsonValue {
+        let bytes_opt = |bytes: BytesOpt| match bytes.to_opt() {
+            Some(bytes) => fmt_vec(&bytes.raw_data()).into(),
+            None => JsonValue::Null,
+        };
+        let mut js = JsonValue::new_object();
+        js["witnesses"] = JsonValue::Array(
+            self.witnesses
+                .iter()
+                .map(|witness| {
+                    let mut js_witness = JsonValue::new_object();
+                    js_witness["index"] = witness.index.into();
+                    js_witness["length"] = witness.length.into();
+                    js_witness["witness_args"] = match &witness.witness_args {
+                        Some(args) => {
+                            let mut js_args = JsonValue::new_object();
+                            js_args["lock"] = bytes_opt(args.lock());
+                            js_args["input_type"] = bytes_opt(args.input_type());
+                            js_args["output_type"] = bytes_opt(args.output_type());
+                            js_args
+                        }
+                        None => JsonValue::Null,
+                    };
+                    js_witness["consumers"] = JsonValue::Array(
+                        witness
+                            .consumers
+                            .iter()
+                            .map(|consumer| {
+                                let mut js_consumer = JsonValue::new_object();
+                                js_consumer["group_index"] = consumer.group_index.into();
+                                js_consumer["group_type"] = match consumer.group_type {
+                                    ScriptGroupType::Lock => "lock".into(),
+                                    ScriptGroupType::Type => "type".into(),
+                                };
+                                js_consumer["script_hash"] =
+                                    fmt_vec(consumer.script_hash.as_slice()).into();
+                                js_consumer["field"] = consumer.field.to_string().into();
+                                js_consumer["first"] = consumer.first.into();
+                                js_consumer
+                            })
+                            .collect(),
+                    );
+                    js_witness
+                })
+                .collect(),
+        );
+        js["problems"] = JsonValue::Array(
+            self.problems
+                .iter()
+                .map(|problem| problem.to_string().into())
+                .collect(),
+        );
+        js
+    }
+
+    /// Writes the sidecar next to `tx_file` and returns its path.
+    pub fn write(&self, tx_file: &Path) -> Result<PathBuf, Error> {
+        let path = Self::path_for(tx_file);
+        write_atomic(&path, self.to_json().pretty(2).as_bytes())?;
+        Ok(path)
+    }
+}
@@ -0,0 +1,211 @@
+use ckb_debugger_dumper::{
+    Baseline, BaselineRunner, DumpDir, DumpingVerifier, JsonFormat, Labels, Manifest,
+    MockTransaction,
+};
+use ckb_script::TransactionScriptsVerifier;
+use ckb_types::{bytes::Bytes, core::ScriptHashType};
+
+mod misc;
+use misc::*;
+
+fn gen_dump_dir(name: &str) -> std::path::PathBuf {
+    let (tx, dummy) = gen_ckb_tx(
+        gen_always_success_cells(3),
+        gen_always_success_deps(),
+        Vec::new(),
+    );
+    let dir = temp_dir(name);
+    std::fs::create_dir_all(&dir).unwrap();
+    let mock_tx = MockTransaction::from_resolved_tx_with_data_loader(&dummy, &tx);
+    let tx_file = dir.join("transfer.json");
+    std::fs::write(&tx_file, mock_tx.to_json_string(JsonFormat::Pretty)).unwrap();
+    Labels::new()
+        .out_point(tx.resolved_inputs[0].out_point.clone(), "first input")
+        .write(&tx_file)
+        .unwrap();
+
+    let mut blanked = mock_tx;
+    for dep in blanked.mock_info.cell_deps.iter_mut() {
+        dep.data = Default::default();
+    }
+    std::fs::write(
+        dir.join("transfer_blanked.json"),
+        blanked.to_json_string(JsonFormat::Pretty),
+    )
+    .unwrap();
+    dir
+}
+
+#[test]
+fn test_baseline() {
+    let dir = gen_dump_dir("baseline");
+    let bin_path = write_temp_file(
+        "baseline_always_success",
+        ckb_always_success_script::ALWAYS_SUCCESS,
+    );
+    let baseline = BaselineRunner::new(&dir).bin_path(&bin_path).run().unwrap();
+
+    let mock_tx = MockTransaction::read_from_file(dir.join("transfer.json")).unwrap();
+    let resolved_tx = mock_tx.resolve().unwrap();
+    let groups = ckb_debugger_dumper::script_groups(&resolved_tx);
+    assert_eq!(baseline.groups.len(), groups.len() * 2);
+    let verifier = TransactionScriptsVerifier::new(&resolved_tx, &mock_tx.mock_info);
+    for (i, (script_hash, group)) in groups.iter().enumerate() {
+        let cycles = verifier
+            .verify_single(group.group_type, script_hash, u64::MAX)
+            .unwrap();
+        let measured = &baseline.groups[i];
+        assert_eq!(measured.name, "transfer");
+        assert_eq!(measured.tx_file, "transfer.json");
+        assert_eq!(measured.group_index, i);
+        assert_eq!(measured.script_hash, *script_hash);
+        assert_eq!(measured.cycles, Ok(cycles));
+        // The blanked dump runs the same code from --bin.
+        let blanked = &baseline.groups[groups.len() + i];
+        assert_eq!(blanked.tx_file, "transfer_blanked.json");
+        assert_eq!(blanked.cycles, Ok(cycles));
+    }
+
+    let baseline_file = dir.join("cycles.baseline.json");
+    baseline.write(&baseline_file).unwrap();
+    assert_eq!(Baseline::read(&baseline_file).unwrap(), baseline);
+    // The baseline itself is not a dump.
+    assert_eq!(
+        BaselineRunner::new(&dir).bin_path(&bin_path).run().unwrap(),
+        baseline
+    );
+    assert!(baseline.compare(&baseline, 0.0).is_empty());
+
+    let without_bin = BaselineRunner::new(&dir).run().unwrap();
+    let changes = baseline.compare(&without_bin, 0.0);
+    assert_eq!(changes.len(), groups.len());
+    for change in &changes {
+        assert!(change.after.as_ref().unwrap().cycles.is_err());
+        assert!(
+            change.to_string().contains(" cycles -> failed ("),
+            "{}",
+            change
+        );
+    }
+}
+
+#[test]
+fn test_baseline_threshold() {
+    let dir = gen_dump_dir("baseline_threshold");
+    let baseline = BaselineRunner::new(&dir).run().unwrap();
+    let cycles = *baseline.groups[0].cycles.as_ref().unwrap();
+
+    let mut current = baseline.clone();
+    current.groups[0].cycles = Ok(cycles + cycles / 10);
+    assert!(baseline.compare(&current, 20.0).is_empty());
+    let changes = baseline.compare(&current, 5.0);
+    assert_eq!(changes.len(), 1);
+    let percent = changes[0].percent().unwrap();
+    assert!((percent - 10.0).abs() < 0.1, "{}", percent);
+    assert!(changes[0].to_string().starts_with(&format!(
+        "transfer.json group #0 {} 0x",
+        baseline.groups[0].group_type
+    )));
+    assert!(changes[0].to_string().ends_with(&format!(
+        ": {} cycles -> {} cycles (+{:.2}%)",
+        cycles,
+        cycles + cycles / 10,
+        percent
+    )));
+
+    let removed = current.groups.remove(1);
+    let changes = baseline.compare(&current, 20.0);
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].before, Some(removed.clone()));
+    assert!(changes[0].after.is_none());
+    assert!(changes[0].to_string().ends_with(" cycles -> none"));
+    let changes = current.compare(&baseline, 20.0);
+    assert_eq!(changes[0].after, Some(removed));
+    assert!(changes[0].to_string().contains(": none -> "));
+}
+
+/// Verifies and dumps a transaction spending `inputs` cells of the same lock, as a test does
+/// every run.
+fn dump_run(dir: &std::path::Path, inputs: usize) -> Manifest {
+    let code = Bytes::from(ckb_always_success_script::ALWAYS_SUCCESS.to_vec());
+    let mut builder = TxBuilder::new();
+    let lock = builder
+        .deploy(code.clone(), ScriptHashType::Data1)
+        .script(Bytes::from(vec![1; 20]));
+    let type_ = builder
+        .deploy(code, ScriptHashType::Type)
+        .script(Bytes::new());
+    builder.input(lock.clone(), Some(type_), Bytes::new());
+    for _ in 1..inputs {
+        builder.input(lock.clone(), None, Bytes::new());
+    }
+    let (tx, dummy) = builder.build();
+    let verifier = DumpingVerifier::new(&tx, &dummy, DumpDir::new(dir)).max_cycles(0xFFFFFFFF);
+    verifier.verify(0xFFFFFFFF).unwrap();
+    verifier.dump().unwrap()
+}
+
+#[test]
+fn test_baseline_across_runs() {
+    let first = temp_dir("baseline_first_run");
+    let second = temp_dir("baseline_second_run");
+    for dir in &[&first, &second] {
+        dump_run(dir, 1);
+        dump_run(dir, 3);
+    }
+
+    // The cycles come from the manifests, and match running the dumps again.
+    let baseline = BaselineRunner::new(&first).run().unwrap();
+    assert_eq!(
+        BaselineRunner::new(&first).rerun(true).run().unwrap(),
+        baseline
+    );
+    for group in &baseline.groups {
+        assert_eq!(group.name, "test_baseline_across_runs");
+        assert_ne!(group.tx_file, "test_baseline_across_runs.json");
+    }
+    let current = BaselineRunner::new(&second).run().unwrap();
+    assert_eq!(current.groups.len(), baseline.groups.len());
+    assert!(baseline.compare(&current, 0.0).is_empty());
+
+    // Both dumps of the test have the same lock group, matched in the order of the dumps.
+    let lock_hash = &baseline.groups[0].script_hash;
+    let last = baseline
+        .groups
+        .iter()
+        .rposition(|group| group.script_hash == *lock_hash)
+        .unwrap();
+    assert_ne!(last, 0);
+    let mut changed = current.clone();
+    let cycles = changed.groups[last].cycles.clone().unwrap();
+    changed.groups[last].cycles = Ok(cycles * 2);
+    let changes = baseline.compare(&changed, 0.0);
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].before.as_ref(), Some(&baseline.groups[last]));
+    assert_eq!(changes[0].percent(), Some(100.0));
+}
+
+#[test]
+fn test_baseline_reads_manifest() {
+    let dir = temp_dir("baseline_manifest");
+    let manifest = dump_run(&dir, 2);
+    let baseline = BaselineRunner::new(&dir).run().unwrap();
+    assert_eq!(baseline.groups.len(), manifest.groups.len());
+    for (group, recorded) in baseline.groups.iter().zip(&manifest.groups) {
+        assert_eq!(group.cycles, Ok(recorded.cycles.unwrap()));
+    }
+
+    let path = Manifest::path_for(&manifest.tx_file);
+    let mut js = json::parse(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    js["groups"][0]["cycles"] = 1.into();
+    js["groups"][1].remove("cycles");
+    std::fs::write(&path, js.pretty(2)).unwrap();
+    let edited = BaselineRunner::new(&dir).run().unwrap();
+    assert_eq!(edited.groups[0].cycles, Ok(1));
+    // A group without recorded cycles is run in-process.
+    assert_eq!(edited.groups[1].cycles, baseline.groups[1].cycles);
+    assert_eq!(
+        BaselineRunner::new(&dir).rerun(true).run().unwrap(),
+        baseline
+    );
+}
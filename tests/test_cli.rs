@@ -0,0 +1,308 @@
+use ckb_debugger_dumper::{fmt_out_point, JsonFormat, Labels, MockTransaction};
+use ckb_types::core::EpochNumberWithFraction;
+use std::process::{Command, Output};
+
+mod misc;
+use misc::*;
+
+fn dumper(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_ckb-debugger-dumper"))
+        .args(args)
+        .output()
+        .expect("run ckb-debugger-dumper")
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+#[test]
+fn test_cli() {
+    let (tx, dummy) = gen_ckb_tx(
+        gen_always_success_cells(2),
+        gen_always_success_deps(),
+        Vec::new(),
+    );
+    let mock_tx = MockTransaction::from_resolved_tx_with_data_loader(&dummy, &tx);
+    let tx_file = write_temp_file(
+        "cli_tx.json",
+        mock_tx.to_json_string(JsonFormat::Pretty).as_bytes(),
+    );
+    let bin_path = write_temp_file(
+        "cli_always_success",
+        ckb_always_success_script::ALWAYS_SUCCESS,
+    );
+
+    let output = dumper(&["show", &tx_file]);
+    assert!(output.status.success());
+    let show = stdout(&output);
+    for section in &[
+        "inputs:",
+        "cell_deps:",
+        "header_deps:",
+        "outputs:",
+        "witnesses:",
+    ] {
+        assert!(show.contains(section), "{}", show);
+    }
+    assert!(show.contains(" since 0x0 (none)"), "{}", show);
+    let out_point = fmt_out_point(&tx.resolved_cell_deps[0].out_point);
+    assert!(show.contains(&format!(" code {}\n", out_point)), "{}", show);
+
+    let output = dumper(&["groups", &tx_file]);
+    assert!(output.status.success());
+    let groups = stdout(&output);
+    assert_eq!(
+        groups.matches("ckb-debugger --tx-file").count(),
+        groups.lines().count() / 2
+    );
+
+    let lock = &tx.resolved_inputs[0].cell_output.lock();
+    Labels::new()
+        .out_point(tx.resolved_inputs[0].out_point.clone(), "first input")
+        .script(lock, "always success lock")
+        .write(std::path::Path::new(&tx_file))
+        .unwrap();
+    let groups = stdout(&dumper(&["groups", &tx_file]));
+    assert!(groups.contains(" # always success lock\n"), "{}", groups);
+    let show = stdout(&dumper(&["show", &tx_file]));
+    assert!(show.contains(" \"first input\" since "), "{}", show);
+    assert!(
+        show.contains(" \"always success lock\" (code_hash "),
+        "{}",
+        show
+    );
+    std::fs::remove_file(Labels::path_for(std::path::Path::new(&tx_file))).unwrap();
+
+    let extracted = write_temp_file("cli_extracted", b"");
+    assert!(dumper(&["extract-bin", &tx_file, "0", &extracted])
+        .status
+        .success());
+    assert_eq!(
+        std::fs::read(&extracted).unwrap(),
+        ckb_always_success_script::ALWAYS_SUCCESS
+    );
+    assert!(!dumper(&["extract-bin", &tx_file, "9", &extracted])
+        .status
+        .success());
+
+    let output = dumper(&["validate", &tx_file]);
+    assert!(output.status.success(), "{}", stdout(&output));
+    assert!(stdout(&output).starts_with("ok:"));
+
+    // Blank both deps: the groups lose their code until --bin provides it.
+    let blank_file = write_temp_file("cli_blank.json", b"");
+    assert!(
+        dumper(&["replace-bin", &tx_file, "0", "--output", &blank_file])
+            .status
+            .success()
+    );
+    assert!(dumper(&["replace-bin", &blank_file, "1"]).status.success());
+    let blank = MockTransaction::read_from_file(&blank_file).unwrap();
+    assert!(blank
+        .mock_info
+        .cell_deps
+        .iter()
+        .all(|dep| dep.data.is_empty()));
+    assert_eq!(
+        MockTransaction::read_from_file(&tx_file).unwrap().to_json(),
+        mock_tx.to_json()
+    );
+
+    let output = dumper(&["validate", &blank_file]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stdout(&output).contains("no cell dep provides the code"));
+    assert!(dumper(&["validate", &blank_file, "--bin", &bin_path])
+        .status
+        .success());
+
+    assert!(dumper(&["replace-bin", &blank_file, "0", &bin_path])
+        .status
+        .success());
+    let replaced = MockTransaction::read_from_file(&blank_file).unwrap();
+    assert_eq!(
+        replaced.mock_info.cell_deps[0].data,
+        mock_tx.mock_info.cell_deps[0].data
+    );
+
+    assert!(!dumper(&["show", "/nonexistent.json"]).status.success());
+    assert!(!dumper(&["frobnicate"]).status.success());
+}
+
+#[test]
+fn test_cli_minimize() {
+    let (tx, dummy) = gen_ckb_tx(
+        gen_always_success_cells(3),
+        gen_always_success_deps(),
+        Vec::new(),
+    );
+    let mock_tx = MockTransaction::from_resolved_tx_with_data_loader(&dummy, &tx);
+    let tx_file = write_temp_file(
+        "cli_minimize.json",
+        mock_tx.to_json_string(JsonFormat::Pretty).as_bytes(),
+    );
+    let min_file = tx_file.replace(".json", ".min.json");
+
+    let output = dumper(&["minimize", &tx_file, "0"]);
+    assert!(output.status.success(), "{}", stdout(&output));
+    assert!(stdout(&output).contains("passes"));
+    assert!(stdout(&output).contains(&format!("--tx-file {}", min_file)));
+    let minimized = MockTransaction::read_from_file(&min_file).unwrap();
+    assert!(minimized.tx.inputs().len() < mock_tx.tx.inputs().len());
+
+    assert!(!dumper(&["minimize", &tx_file, "99"]).status.success());
+}
+
+#[test]
+fn test_cli_diff() {
+    let (tx, dummy) = gen_ckb_tx(
+        gen_always_success_cells(2),
+        gen_always_success_deps(),
+        Vec::new(),
+    );
+    let mut mock_tx = MockTransaction::from_resolved_tx_with_data_loader(&dummy, &tx);
+    let before = write_temp_file(
+        "cli_diff_before.json",
+        mock_tx.to_json_string(JsonFormat::Pretty).as_bytes(),
+    );
+    let output = dumper(&["diff", &before, &before]);
+    assert!(output.status.success());
+    assert_eq!(stdout(&output).trim(), "no differences");
+
+    mock_tx.mock_info.inputs.pop();
+    let after = write_temp_file(
+        "cli_diff_after.json",
+        mock_tx.to_json_string(JsonFormat::Pretty).as_bytes(),
+    );
+    let output = dumper(&["diff", &before, &after]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stdout(&output).starts_with("- inputs["));
+}
+
+#[test]
+fn test_cli_rehash() {
+    let (tx, dummy) = gen_ckb_tx(
+        gen_always_success_cells(2),
+        gen_always_success_deps(),
+        Vec::new(),
+    );
+    let mock_tx = MockTransaction::from_resolved_tx_with_data_loader(&dummy, &tx);
+    let tx_file = write_temp_file(
+        "cli_rehash.json",
+        mock_tx.to_json_string(JsonFormat::Pretty).as_bytes(),
+    );
+    let mut rebuilt = ckb_always_success_script::ALWAYS_SUCCESS.to_vec();
+    rebuilt.push(0);
+    let bin_path = write_temp_file("cli_rehash_bin", &rebuilt);
+
+    let output = dumper(&["rehash", &tx_file, "0", &bin_path, "--rewrite-args"]);
+    assert!(output.status.success(), "{}", stdout(&output));
+    assert!(stdout(&output).contains("cell deps [0, 1]"));
+    let rehashed = MockTransaction::read_from_file(&tx_file).unwrap();
+    for dep in &rehashed.mock_info.cell_deps {
+        assert_eq!(dep.data.to_vec(), rebuilt);
+    }
+    assert!(dumper(&["validate", &tx_file]).status.success());
+
+    assert!(dumper(&["replace-bin", &tx_file, "0"]).status.success());
+    let output = dumper(&["rehash", &tx_file, "0", &bin_path]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--old-hash"));
+}
+
+#[test]
+fn test_cli_environment() {
+    let (tx, dummy) = gen_ckb_tx(
+        gen_always_success_cells(2),
+        gen_always_success_deps(),
+        Vec::new(),
+    );
+    let mut mock_tx = MockTransaction::from_resolved_tx_with_data_loader(&dummy, &tx);
+    mock_tx.environment = Some(gen_environment());
+    let tx_file = write_temp_file(
+        "cli_environment.json",
+        mock_tx.to_json_string(JsonFormat::Pretty).as_bytes(),
+    );
+
+    let show = stdout(&dumper(&["show", &tx_file]));
+    assert!(
+        show.contains("environment: committed, tip block 0, epoch 300 + 0/1\n"),
+        "{}",
+        show
+    );
+    assert!(show.contains("  rfc_0028 disabled\n"), "{}", show);
+    assert!(show.contains("  rfc_0032 from epoch 200\n"), "{}", show);
+    let groups = stdout(&dumper(&["groups", &tx_file]));
+    assert_eq!(
+        groups.matches(" --script-version 1\n").count(),
+        groups.lines().count() / 2
+    );
+    let output = dumper(&["validate", &tx_file]);
+    assert!(output.status.success());
+    assert!(!stdout(&output).contains("warning"), "{}", stdout(&output));
+
+    // Before rfc 0032, type scripts run on VM version 0.
+    let mut environment = gen_environment();
+    environment.epoch = EpochNumberWithFraction::new(100, 0, 1);
+    mock_tx.environment = Some(environment);
+    std::fs::write(&tx_file, mock_tx.to_json_string(JsonFormat::Pretty)).unwrap();
+    let validate = stdout(&dumper(&["validate", &tx_file]));
+    assert!(
+        validate.contains("in the recorded environment, but version 1 in-process"),
+        "{}",
+        validate
+    );
+    assert!(validate.contains("uses hash type data1"), "{}", validate);
+}
+
+#[test]
+fn test_cli_baseline() {
+    let (tx, dummy) = gen_ckb_tx(
+        gen_always_success_cells(2),
+        gen_always_success_deps(),
+        Vec::new(),
+    );
+    let mock_tx = MockTransaction::from_resolved_tx_with_data_loader(&dummy, &tx);
+    let dir = temp_dir("cli_baseline");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        dir.join("transfer.json"),
+        mock_tx.to_json_string(JsonFormat::Pretty),
+    )
+    .unwrap();
+    let dir = dir.to_str().unwrap();
+    let baseline_file = write_temp_file("cli_baseline.json", b"");
+
+    let output = dumper(&["baseline", dir, "--output", &baseline_file]);
+    assert!(output.status.success(), "{:?}", output);
+    let mut js = json::parse(&std::fs::read_to_string(&baseline_file).unwrap()).unwrap();
+    assert_eq!(js["groups"].len(), mock_tx.groups(None).unwrap().len());
+    let printed = json::parse(&stdout(&dumper(&["baseline", dir]))).unwrap();
+    assert_eq!(printed, js);
+
+    let output = dumper(&["compare-cycles", dir, &baseline_file]);
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "no changes\n");
+
+    let cycles = js["groups"][0]["cycles"].as_u64().unwrap();
+    js["groups"][0]["cycles"] = (cycles - cycles / 10).into();
+    std::fs::write(&baseline_file, js.pretty(2)).unwrap();
+    let output = dumper(&["compare-cycles", dir, &baseline_file]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(
+        stdout(&output).starts_with("transfer.json group #0 "),
+        "{}",
+        stdout(&output)
+    );
+    let output = dumper(&["compare-cycles", dir, &baseline_file, "--threshold", "20"]);
+    assert!(output.status.success());
+    let output = dumper(&[
+        "compare-cycles",
+        dir,
+        &baseline_file,
+        "--rerun",
+        "--threshold",
+        "20",
+    ]);
+    assert!(output.status.success());
+}
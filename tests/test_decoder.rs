@@ -0,0 +1,133 @@
+use ckb_debugger_dumper::{
+    CellDataDecoder, DaoDecoder, DecoderRegistry, DepGroupDecoder, Report, TypeIdDecoder,
+    UdtDecoder, DAO_CODE_HASH, TYPE_ID_CODE_HASH,
+};
+use ckb_types::{
+    bytes::Bytes,
+    core::ScriptHashType,
+    packed::{Byte32, CellOutput, OutPoint, Script},
+    prelude::*,
+};
+
+mod misc;
+use misc::*;
+
+fn cell(type_: Option<Script>) -> CellOutput {
+    CellOutput::new_builder().type_(type_.pack()).build()
+}
+
+fn type_script(code_hash: &[u8], args: Bytes) -> Script {
+    Script::new_builder()
+        .code_hash(Byte32::from_slice(code_hash).unwrap())
+        .hash_type(ScriptHashType::Type.into())
+        .args(args.pack())
+        .build()
+}
+
+#[test]
+fn test_builtin_decoders() {
+    let udt = type_script(&[1; 32], Bytes::from(vec![2; 32]));
+    let amount = 1_000_000u128.to_le_bytes();
+    assert_eq!(
+        UdtDecoder::new().decode(&cell(Some(udt.clone())), &amount),
+        Some(format!(
+            "amount 1000000, owner lock hash 0x{}",
+            "02".repeat(32)
+        ))
+    );
+    assert_eq!(
+        UdtDecoder::new().decode(&cell(Some(udt.clone())), &[0; 8]),
+        None
+    );
+    assert_eq!(UdtDecoder::new().decode(&cell(None), &amount), None);
+    let other_udt = UdtDecoder::new().code_hash([3; 32].pack());
+    assert_eq!(other_udt.decode(&cell(Some(udt)), &amount), None);
+
+    let dao = cell(Some(type_script(DAO_CODE_HASH.as_bytes(), Bytes::new())));
+    assert_eq!(
+        DaoDecoder.decode(&dao, &[0; 8]),
+        Some(String::from("deposit"))
+    );
+    assert_eq!(
+        DaoDecoder.decode(&dao, &1234u64.to_le_bytes()),
+        Some(String::from("withdrawing, deposited in block 1234"))
+    );
+    assert_eq!(DaoDecoder.decode(&dao, &[0; 16]), None);
+
+    let out_points = vec![OutPoint::new([4; 32].pack(), 1)].pack();
+    assert_eq!(
+        DepGroupDecoder.decode(&cell(None), out_points.as_slice()),
+        Some(format!("0x{}:0x1", "04".repeat(32)))
+    );
+    assert_eq!(DepGroupDecoder.decode(&cell(None), &[1, 2, 3]), None);
+
+    let type_id = cell(Some(type_script(
+        TYPE_ID_CODE_HASH.as_bytes(),
+        Bytes::from(vec![5; 32]),
+    )));
+    assert_eq!(
+        TypeIdDecoder.decode(&type_id, &[]),
+        Some(format!("id 0x{}", "05".repeat(32)))
+    );
+}
+
+struct OrderDecoder;
+
+impl CellDataDecoder for OrderDecoder {
+    fn name(&self) -> &str {
+        "order"
+    }
+
+    fn decode(&self, _output: &CellOutput, data: &[u8]) -> Option<String> {
+        if data.len() == 16 {
+            Some(format!("price {}", data[0]))
+        } else {
+            None
+        }
+    }
+}
+
+#[test]
+fn test_decoder_registry() {
+    let udt = cell(Some(type_script(&[1; 32], Bytes::from(vec![2; 32]))));
+    let data = [7; 16];
+    assert_eq!(DecoderRegistry::new().decode(&udt, &data).unwrap().0, "udt");
+    let registry = DecoderRegistry::new().register(OrderDecoder);
+    assert_eq!(
+        registry.decode(&udt, &data),
+        Some(("order", String::from("price 7")))
+    );
+    assert_eq!(DecoderRegistry::empty().decode(&udt, &data), None);
+}
+
+#[test]
+fn test_report_decodes_cells() {
+    let mut builder = TxBuilder::new();
+    let code = builder.deploy(
+        Bytes::from(ckb_always_success_script::ALWAYS_SUCCESS.to_vec()),
+        ScriptHashType::Data1,
+    );
+    let lock = code.script(Bytes::new());
+    let udt = code.script(Bytes::from(vec![2; 32]));
+    builder.dep_group(std::slice::from_ref(&code.out_point));
+    let (tx, data_loader) = builder
+        .input(
+            lock.clone(),
+            Some(udt.clone()),
+            Bytes::from(100u128.to_le_bytes().to_vec()),
+        )
+        .output(lock, Some(udt), Bytes::from(vec![3; 16]))
+        .build();
+
+    let report = Report::new(&data_loader, &tx).markdown;
+    assert!(report.contains("  - udt: amount 100, owner lock hash 0x"));
+    assert!(report.contains("  - dep_group: 0x"));
+    let report = Report::annotated(
+        &data_loader,
+        &tx,
+        &Default::default(),
+        &DecoderRegistry::new().register(OrderDecoder),
+    )
+    .markdown;
+    assert!(report.contains("  - order: price 3"));
+}
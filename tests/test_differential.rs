@@ -0,0 +1,98 @@
+#![cfg(unix)]
+
+use ckb_debugger_dumper::{DifferentialRunner, DumpDir, Normalizer};
+use std::os::unix::fs::PermissionsExt;
+
+mod misc;
+use misc::*;
+
+fn fake_debugger(name: &str, output: &str) -> String {
+    fake_debugger_script(name, &format!("printf '{}'", output))
+}
+
+fn fake_debugger_script(name: &str, script: &str) -> String {
+    let path = write_temp_file(name, format!("#!/bin/sh\n{}\n", script).as_bytes());
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    path
+}
+
+#[test]
+fn test_normalizer() {
+    let normalizer = Normalizer::until_last_marker("----");
+    assert_eq!(
+        normalizer.normalize("a\n----\nb\n----\nc\n"),
+        "a\n----\nb\n----"
+    );
+    assert_eq!(normalizer.normalize("no marker"), "no marker");
+
+    let normalizer = Normalizer::filter_lines(|line| !line.starts_with("cycles"));
+    assert_eq!(normalizer.normalize("a\ncycles: 10\nb"), "a\nb\n");
+}
+
+#[test]
+fn test_differential_runner() {
+    let (tx, dummy) = gen_ckb_tx(
+        gen_always_success_cells(3),
+        gen_always_success_deps(),
+        Vec::new(),
+    );
+    let dir = temp_dir("differential");
+
+    let report = DifferentialRunner::new(&tx, &dummy, DumpDir::new(&dir))
+        .debugger(&fake_debugger("debugger_silent", ""))
+        .run()
+        .unwrap();
+    assert_eq!(
+        report.groups.len(),
+        ckb_debugger_dumper::script_groups(&tx).len()
+    );
+    assert!(report.tx_file.exists());
+    assert!(report.groups[0].args.contains(&String::from("--tx-file")));
+    assert!(report.groups.iter().all(|group| group.result.is_ok()));
+    report.assert_match();
+
+    let failing = fake_debugger_script("debugger_failing", "echo 'script error' >&2\nexit 1");
+    let report = DifferentialRunner::new(&tx, &dummy, DumpDir::new(&dir))
+        .debugger(&failing)
+        .run()
+        .unwrap();
+    assert!(!report.is_match());
+    assert_eq!(report.groups[0].exit_code, Some(1));
+    assert_eq!(report.groups[0].stderr, "script error\n");
+    let message = report.to_string();
+    assert!(message.contains("ckb-debugger failed with exit code 1"));
+    assert!(message.contains("script error"));
+
+    let report = DifferentialRunner::new(&tx, &dummy, DumpDir::new(&dir))
+        .debugger(&fake_debugger("debugger_silent", ""))
+        .environment(gen_environment())
+        .max_cycles(1_000_000)
+        .run()
+        .unwrap();
+    report.assert_match();
+    let args = &report.groups[0].args;
+    assert!(args.contains(&String::from("--script-version")));
+    let max_cycles = args.iter().position(|arg| arg == "--max-cycles").unwrap();
+    assert_eq!(args[max_cycles + 1], "1000000");
+
+    let noisy = fake_debugger("debugger_noisy", "Run result: 0\\nAll cycles: 539\\n");
+    let report = DifferentialRunner::new(&tx, &dummy, DumpDir::new(&dir))
+        .debugger(&noisy)
+        .run()
+        .unwrap();
+    assert!(!report.is_match());
+    assert_eq!(
+        report.groups[0].diff(),
+        "+Run result: 0\n+All cycles: 539\n"
+    );
+    assert!(report.to_string().contains("differ from ckb-debugger"));
+
+    let report = DifferentialRunner::new(&tx, &dummy, DumpDir::new(&dir))
+        .debugger(&noisy)
+        .normalizer(Normalizer::filter_lines(|line| {
+            !line.starts_with("Run result") && !line.starts_with("All cycles")
+        }))
+        .run()
+        .unwrap();
+    report.assert_match();
+}
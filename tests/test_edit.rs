@@ -0,0 +1,160 @@
+use ckb_debugger_dumper::{Error, JsonFormat, MockTransaction, ScriptTarget};
+use ckb_script::{ScriptGroupType, TransactionScriptsVerifier};
+use ckb_types::{
+    bytes::Bytes,
+    core::{EpochNumberWithFraction, HeaderBuilder, ScriptHashType},
+    packed::CellOutput,
+    prelude::*,
+};
+
+mod misc;
+use misc::*;
+
+fn gen_mock_tx() -> MockTransaction {
+    let mut builder = TxBuilder::new();
+    let code = builder.deploy(
+        Bytes::from(ckb_always_success_script::ALWAYS_SUCCESS.to_vec()),
+        ScriptHashType::Data1,
+    );
+    let lock = code.script(Bytes::from(vec![1]));
+    let type_ = code.script(Bytes::from(vec![2]));
+    let (tx, data_loader) = builder
+        .input(lock.clone(), None, Bytes::new())
+        .input(lock.clone(), Some(type_.clone()), Bytes::from(vec![3; 4]))
+        .output(lock, Some(type_), Bytes::new())
+        .build();
+    MockTransaction::from_resolved_tx_with_data_loader(&data_loader, &tx)
+}
+
+fn assert_consistent(mock_tx: &MockTransaction) {
+    for (input, mock_input) in mock_tx
+        .tx
+        .inputs()
+        .into_iter()
+        .zip(&mock_tx.mock_info.inputs)
+    {
+        assert_eq!(input.as_slice(), mock_input.input.as_slice());
+    }
+    assert_eq!(
+        mock_tx.tx.header_deps_iter().collect::<Vec<_>>(),
+        mock_tx
+            .mock_info
+            .header_deps
+            .iter()
+            .map(|header| header.hash())
+            .collect::<Vec<_>>()
+    );
+    let parsed =
+        MockTransaction::from_json_str(&mock_tx.to_json_string(JsonFormat::Compact)).unwrap();
+    assert_eq!(parsed.tx.hash(), mock_tx.tx.hash());
+    let resolved_tx = mock_tx.resolve().unwrap();
+    TransactionScriptsVerifier::new(&resolved_tx, &mock_tx.mock_info)
+        .verify(u64::MAX)
+        .unwrap();
+}
+
+#[test]
+fn test_edit() {
+    let mut mock_tx = gen_mock_tx();
+    let groups = mock_tx.groups(None).unwrap();
+    assert_eq!(groups.len(), 2);
+
+    mock_tx
+        .set_witness(2, Bytes::from(vec![4; 8]))
+        .set_since(1, 0x8000_0000_0000_0010)
+        .unwrap()
+        .set_input_data(0, Bytes::from(vec![5]))
+        .unwrap();
+    assert_eq!(mock_tx.tx.witnesses().len(), 3);
+    assert!(mock_tx.tx.witnesses().get(0).unwrap().is_empty());
+    assert_eq!(
+        mock_tx.tx.witnesses().get(2).unwrap().raw_data(),
+        Bytes::from(vec![4; 8])
+    );
+    assert_eq!(
+        Unpack::<u64>::unpack(&mock_tx.mock_info.inputs[1].input.since()),
+        0x8000_0000_0000_0010
+    );
+    assert_eq!(mock_tx.mock_info.inputs[0].data, Bytes::from(vec![5]));
+    assert_consistent(&mock_tx);
+
+    // Giving input 1 a lock of its own splits the lock group.
+    mock_tx
+        .set_script_args(ScriptTarget::InputLock(1), Bytes::from(vec![9]))
+        .unwrap();
+    let groups = mock_tx.groups(None).unwrap();
+    assert_eq!(groups.len(), 3);
+    let locks: Vec<_> = groups
+        .iter()
+        .filter(|group| group.group_type == ScriptGroupType::Lock)
+        .map(|group| group.input_indices.clone())
+        .collect();
+    assert!(locks.contains(&vec![0]) && locks.contains(&vec![1]));
+
+    // The output type now differs from the input's, forming a group of its own.
+    mock_tx
+        .set_script_args(ScriptTarget::OutputType(0), Bytes::from(vec![8]))
+        .unwrap();
+    assert_eq!(mock_tx.groups(None).unwrap().len(), 4);
+    let output = mock_tx.tx.outputs().get(0).unwrap();
+    mock_tx
+        .set_output(0, output.clone(), Bytes::from(vec![7; 2]))
+        .unwrap();
+    assert_eq!(
+        mock_tx.tx.outputs_data().get(0).unwrap().raw_data(),
+        Bytes::from(vec![7; 2])
+    );
+    let input = mock_tx.mock_info.inputs[0].output.clone();
+    mock_tx.set_input_cell(0, input, Bytes::new()).unwrap();
+    assert_consistent(&mock_tx);
+
+    let header = HeaderBuilder::default()
+        .compact_target(u32_to_uint32(1))
+        .epoch(EpochNumberWithFraction::new(1, 0, 10).full_value().pack())
+        .build();
+    mock_tx
+        .add_header_dep(header.clone())
+        .add_header_dep(header.clone());
+    assert_eq!(mock_tx.tx.header_deps().len(), 1);
+    assert_consistent(&mock_tx);
+    mock_tx.remove_header_dep(&header.hash()).unwrap();
+    assert!(mock_tx.mock_info.header_deps.is_empty());
+    assert_consistent(&mock_tx);
+
+    // Swapping the code of the scripts leaves no dep to run them.
+    mock_tx.set_cell_dep_data(0, Bytes::from(vec![0])).unwrap();
+    assert!(mock_tx
+        .groups(None)
+        .unwrap()
+        .iter()
+        .all(|group| group.code_dep.is_none()));
+}
+
+#[test]
+fn test_invalid_edit() {
+    let mut mock_tx = gen_mock_tx();
+    let invalid = |e: Error| match e {
+        Error::InvalidEdit(e) => e,
+        e => panic!("unexpected error {}", e),
+    };
+    assert_eq!(
+        invalid(mock_tx.set_since(2, 0).err().unwrap()),
+        "inputs[2] out of range, the transaction has 2"
+    );
+    assert_eq!(
+        invalid(
+            mock_tx
+                .set_script_args(ScriptTarget::InputType(0), Bytes::new())
+                .err()
+                .unwrap()
+        ),
+        "inputs[0] has no type script"
+    );
+    assert!(mock_tx
+        .set_output(1, CellOutput::default(), Bytes::new())
+        .is_err());
+    assert!(mock_tx.set_cell_dep_data(5, Bytes::new()).is_err());
+    assert!(mock_tx
+        .remove_header_dep(&ckb_types::packed::Byte32::zero())
+        .is_err());
+}
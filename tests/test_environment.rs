@@ -0,0 +1,184 @@
+use ckb_debugger_dumper::{
+    DumpDir, DumpingVerifier, EnvironmentWarning, MockTransaction, Since, VerifyEnvironment,
+    VerifyPhase,
+};
+use ckb_script::ScriptVersion;
+use ckb_types::{
+    bytes::Bytes,
+    core::{EpochNumberWithFraction, HeaderView, ScriptHashType},
+    prelude::*,
+};
+
+mod misc;
+use misc::*;
+
+fn gen_environment_at(number: u64, epoch: u64) -> VerifyEnvironment {
+    let tip = HeaderView::new_advanced_builder()
+        .number(number.pack())
+        .epoch(EpochNumberWithFraction::new(epoch, 0, 1).pack())
+        .build();
+    VerifyEnvironment::commit(&tip).hardfork_switch(gen_consensus().hardfork_switch().clone())
+}
+
+#[test]
+fn test_environment_in_dump() {
+    let (tx, dummy) = gen_ckb_tx(
+        gen_always_success_cells(2),
+        gen_always_success_deps(),
+        Vec::new(),
+    );
+    let dir = temp_dir("environment");
+    let manifest = DumpingVerifier::new(&tx, &dummy, DumpDir::new(&dir))
+        .environment(gen_environment())
+        .dump()
+        .unwrap();
+    for group in &manifest.groups {
+        assert!(
+            group.command.ends_with(" --script-version 1"),
+            "{}",
+            group.command
+        );
+    }
+
+    let js = json::parse(&std::fs::read_to_string(&manifest.tx_file).unwrap()).unwrap();
+    let js_environment = &js["extension"]["environment"];
+    assert_eq!(js_environment["phase"].as_str(), Some("committed"));
+    assert_eq!(
+        js_environment["hardfork_switch"]["rfc_0032"].as_str(),
+        Some("0xc8")
+    );
+
+    let environment = MockTransaction::read_from_file(&manifest.tx_file)
+        .unwrap()
+        .environment
+        .unwrap();
+    assert_eq!(environment.to_json(), gen_environment().to_json());
+    assert_eq!(environment.epoch_number(), 300);
+    assert_eq!(
+        environment.to_string(),
+        "committed, tip block 0, epoch 300 + 0/1"
+    );
+    assert!(environment.hardforks().contains(&("rfc_0028", None)));
+    assert!(environment.hardforks().contains(&("rfc_0032", Some(200))));
+    assert_eq!(gen_tx_env().epoch(), environment.epoch);
+}
+
+#[test]
+fn test_environment_without_dump_extension() {
+    let (tx, dummy) = gen_ckb_tx(
+        gen_always_success_cells(1),
+        gen_always_success_deps(),
+        Vec::new(),
+    );
+    let mock_tx = MockTransaction::from_resolved_tx_with_data_loader(&dummy, &tx);
+    assert!(mock_tx.to_json()["extension"].is_null());
+    assert!(MockTransaction::from_json(&mock_tx.to_json())
+        .unwrap()
+        .environment
+        .is_none());
+}
+
+#[test]
+fn test_environment_phases() {
+    let tip = HeaderView::new_advanced_builder()
+        .number(1000u64.pack())
+        .epoch(EpochNumberWithFraction::new(10, 999, 1000).pack())
+        .build();
+    let proposed = VerifyEnvironment::proposed(&tip, 3);
+    assert_eq!(proposed.phase, VerifyPhase::Proposed(3));
+    assert_eq!(
+        proposed.to_string(),
+        "proposed 3 blocks ago, tip block 1000, epoch 10 + 999/1000"
+    );
+    let js = proposed.to_json();
+    assert_eq!(js["proposed_blocks"].as_str(), Some("0x3"));
+    assert!(js["hardfork_switch"].is_null());
+    let parsed = VerifyEnvironment::from_json(&js, "environment").unwrap();
+    assert_eq!(parsed.phase, VerifyPhase::Proposed(3));
+    assert!(parsed.hardfork_switch.is_none());
+
+    // The next block starts epoch 11.
+    assert_eq!(VerifyEnvironment::submit(&tip).epoch_number(), 11);
+    assert_eq!(VerifyEnvironment::commit(&tip).epoch_number(), 10);
+
+    let mut js = gen_environment().to_json();
+    js["phase"] = "pending".into();
+    let err = VerifyEnvironment::from_json(&js, "environment").unwrap_err();
+    assert!(err.to_string().contains("environment.phase"), "{}", err);
+    let mut js = gen_environment().to_json();
+    js["hardfork_switch"].remove("rfc_0036");
+    let err = VerifyEnvironment::from_json(&js, "environment").unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("environment.hardfork_switch.rfc_0036"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn test_environment_before_rfc_0032() {
+    let code = Bytes::from(ckb_always_success_script::ALWAYS_SUCCESS.to_vec());
+    let mut builder = TxBuilder::new();
+    let data_lock = builder
+        .deploy(code.clone(), ScriptHashType::Data)
+        .script(Bytes::new());
+    let data1_lock = builder
+        .deploy(code.clone(), ScriptHashType::Data1)
+        .script(Bytes::new());
+    let type_lock = builder
+        .deploy(code, ScriptHashType::Type)
+        .script(Bytes::new());
+    let (tx, dummy) = builder
+        .input(data_lock.clone(), None, Bytes::new())
+        .input(data1_lock.clone(), None, Bytes::new())
+        .input_with_since(type_lock.clone(), None, Bytes::new(), 500)
+        .build();
+
+    let environment = gen_environment_at(100, 100);
+    assert_eq!(
+        environment.script_version(&data_lock),
+        Some(ScriptVersion::V0)
+    );
+    assert_eq!(environment.script_version(&data1_lock), None);
+    assert_eq!(
+        environment.script_version(&type_lock),
+        Some(ScriptVersion::V0)
+    );
+    assert_eq!(
+        gen_environment().script_version(&type_lock),
+        Some(ScriptVersion::V1)
+    );
+
+    let mut mock_tx = MockTransaction::from_resolved_tx_with_data_loader(&dummy, &tx);
+    mock_tx.environment = Some(environment.clone());
+    let warnings = environment.warnings(&mock_tx);
+    assert_eq!(warnings.len(), 3, "{:?}", warnings);
+    assert!(warnings.contains(&EnvironmentWarning::Data1NotActive {
+        script_hash: data1_lock.calc_script_hash(),
+        activation: 200,
+    }));
+    assert!(warnings.contains(&EnvironmentWarning::ScriptVersion {
+        script_hash: type_lock.calc_script_hash(),
+        version: ScriptVersion::V0,
+        in_process: ScriptVersion::V1,
+    }));
+    assert!(warnings.contains(&EnvironmentWarning::ImmatureSince {
+        input: 2,
+        since: Since(500),
+    }));
+    assert!(gen_environment_at(500, 300).warnings(&mock_tx).is_empty());
+
+    let groups = mock_tx.groups(None).unwrap();
+    let tx_file = write_temp_file("environment_v0.json", mock_tx.to_json().dump().as_bytes());
+    let tx_file = std::path::Path::new(&tx_file);
+    for group in &groups {
+        let command = group.command_with_environment(tx_file, None, Some(&environment));
+        if group.script == data1_lock {
+            assert!(!command.contains("--script-version"), "{}", command);
+        } else {
+            assert!(command.ends_with(" --script-version 0"), "{}", command);
+        }
+        assert!(!group.command(tx_file, None).contains("--script-version"));
+    }
+}
@@ -1,3 +1,4 @@
+use ckb_debugger_dumper::{DifferentialRunner, DumpDir, Normalizer};
 use ckb_script::TransactionScriptsVerifier;
 use ckb_types::{
     bytes::Bytes,
@@ -5,7 +6,7 @@ use ckb_types::{
     packed::Byte32,
 };
 use lazy_static::lazy_static;
-use std::{collections::HashMap, sync::Mutex};
+use std::collections::HashMap;
 
 mod misc;
 use misc::*;
@@ -58,15 +59,15 @@ fn gen_deps() -> HashMap<u32, CkbDepsData> {
 
 fn print_mem(d: &[u8]) {
     let mut c = 0;
-    for i in 0..d.len() {
+    for (i, b) in d.iter().enumerate() {
         c = i;
-        print!("{:#04X}, ", d[i]);
+        print!("{:#04X}, ", b);
         if i % 16 == 15 {
-            print!("\n");
+            println!();
         }
     }
     if c % 16 != 15 {
-        print!("\n");
+        println!();
     }
 }
 
@@ -75,23 +76,6 @@ pub fn dbg_print_mem(d: &[u8], n: &str) {
     print_mem(d);
 }
 
-lazy_static! {
-    pub static ref CKB_VM_OUTPUT_DATA: Mutex<HashMap<Byte32, String>> = Mutex::new(HashMap::new());
-}
-
-pub fn debug_printer(script: &Byte32, msg: &str) {
-    let mut output_data = CKB_VM_OUTPUT_DATA.lock().unwrap();
-    let it = output_data.get_mut(script);
-    if it.is_none() {
-        output_data.insert(script.clone(), String::from(msg));
-    } else {
-        let it = it.unwrap();
-        it.push_str(msg);
-    }
-
-    //print!("{}", msg);
-}
-
 #[test]
 fn test_multiple() {
     let deps = gen_deps();
@@ -124,11 +108,11 @@ fn test_multiple() {
         input_data: gen_rand_bytes(123),
         output_data: gen_rand_bytes(123),
 
-        input_script: CkbCellScritp {
+        input_script: CkbCellScript {
             lock: lock_script1.clone(),
             type_: Option::None,
         },
-        output_script: CkbCellScritp {
+        output_script: CkbCellScript {
             lock: lock_script1.clone(),
             type_: Option::None,
         },
@@ -139,11 +123,11 @@ fn test_multiple() {
         input_data: gen_rand_bytes(456),
         output_data: gen_rand_bytes(456),
 
-        input_script: CkbCellScritp {
+        input_script: CkbCellScript {
             lock: lock_script1.clone(),
             type_: Some(type_script1.clone()),
         },
-        output_script: CkbCellScritp {
+        output_script: CkbCellScript {
             lock: lock_script1.clone(),
             type_: Some(type_script1.clone()),
         },
@@ -154,11 +138,11 @@ fn test_multiple() {
         input_data: gen_rand_bytes(12),
         output_data: gen_rand_bytes(12),
 
-        input_script: CkbCellScritp {
+        input_script: CkbCellScript {
             lock: lock_script2.clone(),
             type_: Some(type_script1.clone()),
         },
-        output_script: CkbCellScritp {
+        output_script: CkbCellScript {
             lock: lock_script2.clone(),
             type_: Some(type_script1.clone()),
         },
@@ -169,11 +153,11 @@ fn test_multiple() {
         input_data: gen_rand_bytes(99),
         output_data: gen_rand_bytes(99),
 
-        input_script: CkbCellScritp {
+        input_script: CkbCellScript {
             lock: lock_script2.clone(),
             type_: Some(type_script2.clone()),
         },
-        output_script: CkbCellScritp {
+        output_script: CkbCellScript {
             lock: lock_script2.clone(),
             type_: Some(type_script2.clone()),
         },
@@ -181,35 +165,17 @@ fn test_multiple() {
 
     let (tx, dummy) = gen_ckb_tx(cells, deps, Vec::new());
 
-    let consensus = gen_consensus();
-    let env = gen_tx_env();
-    let mut verifier = TransactionScriptsVerifier::new(&tx, &consensus, &dummy, &env);
-    verifier.set_debug_printer(debug_printer);
-    verifier.verify(0xFFFFFFFF).expect("run failed");
-
-    for group_index in 0..3 {
-        let cmd_line = ckb_debugger_dumper::gen_json(
-            &verifier,
-            &tx,
-            Option::None,
-            group_index,
-            DUMP_BIN_PATH.as_str(),
-            "test_multi.json",
-            Option::None,
-        );
-        let ckb_dbg_output = run_ckb_debugger(cmd_line.as_str()).unwrap();
-
-        let groups: Vec<Byte32> = verifier.groups().map(|(_f1, f2, _f3)| f2.clone()).collect();
-        let script_id = groups.get(group_index).unwrap();
-        let ckb_output = {
-            let output_data = CKB_VM_OUTPUT_DATA.lock().unwrap();
-            let data = output_data.get(script_id).unwrap().clone();
-
-            let i = data.rfind("----").unwrap();
-            String::from(data.split_at(i + 4).0)
-        };
-        assert_eq!(ckb_dbg_output, ckb_output);
-    }
+    TransactionScriptsVerifier::new(&tx, &dummy)
+        .verify(0xFFFFFFFF)
+        .expect("run failed");
+
+    DifferentialRunner::new(&tx, &dummy, DumpDir::new(temp_dir("test_multi")))
+        .debugger("c/build/ckb-debugger-bins")
+        .bin_path(DUMP_BIN_PATH.as_str())
+        .normalizer(Normalizer::until_last_marker("----"))
+        .run()
+        .expect("run ckb debugger")
+        .assert_match();
 }
 
 #[test]
@@ -234,11 +200,11 @@ fn test_single() {
         input_data: Bytes::from([9; 123].to_vec()),
         output_data: Bytes::from([10; 123].to_vec()),
 
-        input_script: CkbCellScritp {
+        input_script: CkbCellScript {
             lock: lock_script1.clone(),
             type_: Some(type_script1.clone()),
         },
-        output_script: CkbCellScritp {
+        output_script: CkbCellScript {
             lock: lock_script1.clone(),
             type_: Some(type_script1.clone()),
         },
@@ -261,39 +227,17 @@ fn test_single() {
             .build()
     });
 
-    let (tx, dummy) = gen_ckb_tx(cells, deps, header_dep.clone());
-
-    let consensus = gen_consensus();
-    let env = gen_tx_env();
-    let mut verifier = TransactionScriptsVerifier::new(&tx, &consensus, &dummy, &env);
+    let (tx, dummy) = gen_ckb_tx(cells, deps, header_dep);
 
-    verifier.set_debug_printer(debug_printer);
-    verifier.verify(0xFFFFFFFF).expect("run script failed");
-
-    let header_dep: HashMap<Byte32, HeaderView> =
-        header_dep.iter().map(|f| (f.hash(), f.clone())).collect();
-    let group_index = 0;
-    let cmd_line = ckb_debugger_dumper::gen_json(
-        &verifier,
-        &tx,
-        Option::Some(header_dep),
-        group_index,
-        DUMP_BIN_PATH.as_str(),
-        "test.json",
-        Option::None,
-    );
+    TransactionScriptsVerifier::new(&tx, &dummy)
+        .verify(0xFFFFFFFF)
+        .expect("run script failed");
 
-    let ckb_dbg_output = run_ckb_debugger(cmd_line.as_str()).unwrap();
-
-    let groups: Vec<Byte32> = verifier.groups().map(|(_f1, f2, _f3)| f2.clone()).collect();
-    let script_id = groups.get(group_index).unwrap();
-    let ckb_output = {
-        let output_data = CKB_VM_OUTPUT_DATA.lock().unwrap();
-        let data = output_data.get(script_id).unwrap().clone();
-
-        let i = data.rfind("----").unwrap();
-        String::from(data.split_at(i + 4).0)
-    };
-    assert_eq!(ckb_dbg_output, ckb_output);
+    DifferentialRunner::new(&tx, &dummy, DumpDir::new(temp_dir("test_single")))
+        .debugger("c/build/ckb-debugger-bins")
+        .bin_path(DUMP_BIN_PATH.as_str())
+        .normalizer(Normalizer::until_last_marker("----"))
+        .run()
+        .expect("run ckb debugger")
+        .assert_match();
 }
-
@@ -0,0 +1,95 @@
+use ckb_script::TransactionScriptsVerifier;
+
+mod misc;
+use misc::*;
+
+#[test]
+fn test_script_groups_match_verifier() {
+    let (tx, dummy) = gen_ckb_tx(
+        gen_always_success_cells(5),
+        gen_always_success_deps(),
+        Vec::new(),
+    );
+    let verifier = TransactionScriptsVerifier::new(&tx, &dummy);
+    verifier.verify(0xFFFFFFFF).expect("run failed");
+
+    let groups = ckb_debugger_dumper::script_groups(&tx);
+    let expected: Vec<_> = verifier.groups().collect();
+    assert_eq!(groups.len(), expected.len());
+    for ((hash, group), (expected_hash, expected_group)) in groups.iter().zip(expected) {
+        assert_eq!(hash, expected_hash);
+        assert_eq!(group.group_type, expected_group.group_type);
+        assert_eq!(group.script, expected_group.script);
+        assert_eq!(group.input_indices, expected_group.input_indices);
+        assert_eq!(group.output_indices, expected_group.output_indices);
+    }
+}
+
+#[test]
+fn test_gen_json_with_data_loader() {
+    let (mut tx, dummy) = gen_ckb_tx(
+        gen_always_success_cells(3),
+        gen_always_success_deps(),
+        Vec::new(),
+    );
+    let verifier = TransactionScriptsVerifier::new(&tx, &dummy);
+    let bin_path = write_temp_file("always_success", ckb_always_success_script::ALWAYS_SUCCESS);
+
+    let expected_json = write_temp_file("groups_verifier.json", b"");
+    let expected_cmd =
+        ckb_debugger_dumper::gen_json(&verifier, &tx, None, 1, &bin_path, &expected_json, None);
+
+    // Cell data that is not carried by the resolved transaction comes from the data loader.
+    for cell in tx.resolved_inputs.iter_mut() {
+        cell.mem_cell_data = None;
+        cell.mem_cell_data_hash = None;
+    }
+    let json_file = write_temp_file("groups_data_loader.json", b"");
+    let cmd =
+        ckb_debugger_dumper::gen_json_with_data_loader(&dummy, &tx, 1, &bin_path, &json_file, None);
+
+    assert_eq!(
+        std::fs::read_to_string(&expected_json).unwrap(),
+        std::fs::read_to_string(&json_file).unwrap()
+    );
+    assert_eq!(
+        expected_cmd.replace(&expected_json, ""),
+        cmd.replace(&json_file, "")
+    );
+}
+
+#[test]
+fn test_list_script_groups() {
+    let (tx, dummy) = gen_ckb_tx(
+        gen_always_success_cells(4),
+        gen_always_success_deps(),
+        Vec::new(),
+    );
+    let bin_path = write_temp_file(
+        "list_always_success",
+        ckb_always_success_script::ALWAYS_SUCCESS,
+    );
+
+    let groups = ckb_debugger_dumper::script_groups(&tx);
+    let infos = ckb_debugger_dumper::list_script_groups(&dummy, &tx, Some(&bin_path));
+    assert_eq!(groups.len(), infos.len());
+    for ((hash, group), info) in groups.iter().zip(infos.iter()) {
+        assert_eq!(*hash, info.script_hash);
+        assert_eq!(group.group_type, info.group_type);
+        assert_eq!(group.input_indices, info.input_indices);
+        assert_eq!(group.output_indices, info.output_indices);
+        assert_eq!(info.witness_indices, info.input_indices);
+
+        let code_dep = info.code_dep.as_ref().expect("code dep not found");
+        assert!(code_dep.is_bin);
+        assert_eq!(
+            tx.resolved_cell_deps[code_dep.index].out_point,
+            code_dep.out_point
+        );
+    }
+
+    let infos = ckb_debugger_dumper::list_script_groups(&dummy, &tx, None);
+    assert!(infos
+        .iter()
+        .all(|info| !info.code_dep.as_ref().unwrap().is_bin));
+}
@@ -0,0 +1,91 @@
+use ckb_debugger_dumper::{DumpDir, DumpingVerifier, Labels, Manifest, Report};
+use ckb_types::{packed::Byte32, prelude::*};
+
+mod misc;
+use misc::*;
+
+#[test]
+fn test_labels() {
+    let (mut tx, dummy) = gen_ckb_tx(
+        gen_always_success_cells(2),
+        gen_always_success_deps(),
+        Vec::new(),
+    );
+    let bad_lock = tx.resolved_inputs[1]
+        .cell_output
+        .lock()
+        .as_builder()
+        .code_hash(Byte32::new([0xAA; 32]))
+        .build();
+    tx.resolved_inputs[1].cell_output = tx.resolved_inputs[1]
+        .cell_output
+        .clone()
+        .as_builder()
+        .lock(bad_lock.clone())
+        .build();
+    let labels = Labels::new()
+        .out_point(tx.resolved_inputs[1].out_point.clone(), "alice's sUDT cell")
+        .out_point(tx.resolved_cell_deps[0].out_point.clone(), "always_success")
+        .script(&bad_lock, "broken lock");
+    assert_eq!(
+        labels.get_script(&bad_lock.calc_script_hash()),
+        Some("broken lock")
+    );
+
+    let dir = temp_dir("labels");
+    let err = DumpingVerifier::new(&tx, &dummy, DumpDir::new(&dir))
+        .labels(labels.clone())
+        .report(true)
+        .verify(0xFFFFFFFF)
+        .unwrap_err();
+    let dump = err.dump.as_ref().unwrap().as_ref().unwrap();
+    assert!(
+        dump.command.contains(" # broken lock, failed: "),
+        "{}",
+        dump.command
+    );
+    assert!(err.to_string().contains(&dump.command));
+
+    assert_eq!(Labels::read(&dump.tx_file).unwrap(), labels);
+    let manifest =
+        json::parse(&std::fs::read_to_string(Manifest::path_for(&dump.tx_file)).unwrap()).unwrap();
+    assert_eq!(
+        manifest["groups"][err.group_index]["label"].as_str(),
+        Some("broken lock")
+    );
+    for (i, group) in manifest["groups"].members().enumerate() {
+        assert_eq!(group["label"].is_null(), i != err.group_index);
+    }
+
+    let report = std::fs::read_to_string(Report::path_for(&dump.tx_file)).unwrap();
+    assert_eq!(report, Report::with_labels(&dummy, &tx, &labels).markdown);
+    assert!(report.contains("\n### Input 1: alice's sUDT cell\n"));
+    assert!(report.contains("`): always_success\n"));
+    assert!(report.contains("| lock | broken lock |"));
+    assert!(report.contains(&format!(
+        "- lock: `0x{}`: broken lock",
+        hex(bad_lock.calc_script_hash().as_slice())
+    )));
+}
+
+#[test]
+fn test_labels_without_sidecar() {
+    let tx_file = temp_dir("labels_missing").join("tx.json");
+    assert!(Labels::read(&tx_file).unwrap().is_empty());
+
+    let (tx, dummy) = gen_ckb_tx(
+        gen_always_success_cells(1),
+        gen_always_success_deps(),
+        Vec::new(),
+    );
+    let dir = temp_dir("labels_empty");
+    let manifest = DumpingVerifier::new(&tx, &dummy, DumpDir::new(&dir))
+        .dump()
+        .unwrap();
+    assert!(!Labels::path_for(&manifest.tx_file).exists());
+    assert!(manifest.groups.iter().all(|group| group.label.is_none()));
+}
+
+fn hex(data: &[u8]) -> String {
+    data.iter().map(|b| format!("{:02x}", b)).collect()
+}
@@ -0,0 +1,97 @@
+use ckb_debugger_dumper::{testkit::*, RecordingDataLoader, Undeclared};
+use ckb_script::TransactionScriptsVerifier;
+use ckb_traits::{CellDataProvider, HeaderProvider};
+use ckb_types::{
+    bytes::Bytes,
+    core::{cell::CellMetaBuilder, EpochNumberWithFraction, HeaderBuilder, ScriptHashType},
+    prelude::*,
+};
+
+fn gen_header(number: u64) -> ckb_types::core::HeaderView {
+    HeaderBuilder::default()
+        .compact_target(u32_to_uint32(1))
+        .number(u64_to_uint64(number))
+        .epoch(EpochNumberWithFraction::new(1, 0, 10).full_value().pack())
+        .build()
+}
+
+#[test]
+fn test_recording_data_loader() {
+    let declared = gen_header(100);
+    let extra = gen_header(200);
+
+    let mut builder = TxBuilder::new();
+    let code = Bytes::from(ckb_always_success_script::ALWAYS_SUCCESS.to_vec());
+    let lock = builder
+        .deploy(code, ScriptHashType::Data1)
+        .script(Bytes::new());
+    // A dep of the transaction that no script loads, and a cell outside of it.
+    let unloaded = builder.deploy(Bytes::from(vec![2; 8]), ScriptHashType::Data1);
+    let unused = builder.deploy_code(Bytes::from(vec![1; 8]), ScriptHashType::Data1);
+    let (tx, mut data_loader) = builder
+        .input(lock.clone(), None, Bytes::new())
+        .output(lock, None, Bytes::new())
+        .header_dep(declared.clone())
+        .build();
+    data_loader.headers.insert(extra.hash(), extra.clone());
+
+    let recording = RecordingDataLoader::new(&data_loader);
+    // Creating a verifier hashes every cell dep, loaded by a script or not.
+    TransactionScriptsVerifier::new(&tx, &recording);
+    assert_eq!(
+        recording.accessed().cells.len(),
+        tx.resolved_cell_deps.len()
+    );
+
+    recording.verify(&tx, 0xFFFFFFFF).unwrap();
+    let accessed = recording.accessed();
+    let cells: Vec<_> = accessed
+        .cells
+        .iter()
+        .map(|(out_point, _)| out_point.clone())
+        .collect();
+    assert_eq!(cells, vec![tx.resolved_cell_deps[0].out_point.clone()]);
+    assert!(!cells.contains(&unloaded.out_point));
+    assert!(recording.undeclared(&tx).is_empty());
+
+    // Loading an output of the transaction is not undeclared.
+    let output = tx.transaction.output_pts()[0].clone();
+    let output_cell =
+        CellMetaBuilder::from_cell_output(tx.transaction.outputs().get(0).unwrap(), Bytes::new())
+            .out_point(output)
+            .build();
+    recording.load_cell_data(&output_cell).unwrap();
+    assert!(recording.undeclared(&tx).is_empty());
+
+    // What a script reading outside the transaction would do.
+    recording.get_header(&extra.hash()).unwrap();
+    recording.get_cell_data(&unused.out_point).unwrap();
+    assert_eq!(
+        recording.undeclared(&tx),
+        vec![
+            Undeclared::Cell(unused.out_point.clone()),
+            Undeclared::Header(extra.hash()),
+        ]
+    );
+
+    let mock_tx = recording.mock_transaction(&tx);
+    let header_deps: Vec<_> = mock_tx
+        .mock_info
+        .header_deps
+        .iter()
+        .map(|header| header.hash())
+        .collect();
+    assert_eq!(header_deps, vec![declared.hash(), extra.hash()]);
+    // The undeclared cell is served from mock_info, but not added to the transaction.
+    let last = mock_tx.mock_info.cell_deps.last().unwrap();
+    assert_eq!(last.cell_dep.out_point(), unused.out_point);
+    assert_eq!(last.data, Bytes::from(vec![1; 8]));
+    assert_eq!(
+        mock_tx.mock_info.cell_deps.len(),
+        tx.transaction.cell_deps().len() + 1
+    );
+    assert_eq!(mock_tx.tx.hash(), tx.transaction.hash());
+
+    recording.clear();
+    assert!(recording.accessed().cells.is_empty());
+}
@@ -0,0 +1,114 @@
+use ckb_debugger_dumper::{script_groups, Error, Minimizer, MockTransaction};
+use ckb_script::ScriptGroupType;
+use ckb_types::{
+    bytes::Bytes,
+    core::{EpochNumberWithFraction, HeaderBuilder, ScriptHashType},
+    prelude::*,
+};
+
+mod misc;
+use misc::*;
+
+fn always_success() -> Bytes {
+    Bytes::from(ckb_always_success_script::ALWAYS_SUCCESS.to_vec())
+}
+
+fn gen_mock_tx() -> MockTransaction {
+    let header = HeaderBuilder::default()
+        .compact_target(u32_to_uint32(1))
+        .number(u64_to_uint64(100))
+        .epoch(EpochNumberWithFraction::new(1, 0, 10).full_value().pack())
+        .build();
+
+    let mut builder = TxBuilder::new();
+    let code = builder.deploy(always_success(), ScriptHashType::Data1);
+    let broken = builder.deploy(Bytes::from(vec![0; 64]), ScriptHashType::Data1);
+    builder.deploy(Bytes::from(vec![1; 8]), ScriptHashType::Data1);
+    let (tx, data_loader) = builder
+        .input(code.script(Bytes::from(vec![1])), None, Bytes::new())
+        .input(code.script(Bytes::from(vec![2])), None, Bytes::new())
+        .input(broken.script(Bytes::new()), None, Bytes::new())
+        .output(code.script(Bytes::from(vec![1])), None, Bytes::new())
+        .output(code.script(Bytes::from(vec![2])), None, Bytes::new())
+        .witness(Bytes::from(vec![0; 65]))
+        .witness(Bytes::from(vec![0; 65]))
+        .witness(Bytes::from(vec![0; 65]))
+        .header_dep(header)
+        .build();
+    MockTransaction::from_resolved_tx_with_data_loader(&data_loader, &tx)
+}
+
+fn group_index(mock_tx: &MockTransaction, args: &[u8]) -> usize {
+    let resolved_tx = mock_tx.resolve().unwrap();
+    script_groups(&resolved_tx)
+        .iter()
+        .position(|(_, group)| group.script.args().raw_data().as_ref() == args)
+        .unwrap()
+}
+
+#[test]
+fn test_minimize_passing_group() {
+    let mock_tx = gen_mock_tx();
+    let minimized = Minimizer::new(&mock_tx, group_index(&mock_tx, &[2]))
+        .run()
+        .unwrap();
+
+    let tx = &minimized.mock_tx.tx;
+    assert_eq!(tx.inputs().len(), 1);
+    assert_eq!(tx.outputs().len(), 0);
+    assert_eq!(tx.witnesses().len(), 0);
+    assert_eq!(tx.header_deps().len(), 0);
+    assert_eq!(tx.cell_deps().len(), 1);
+    assert_eq!(minimized.mock_tx.mock_info.inputs.len(), 1);
+    assert_eq!(minimized.mock_tx.mock_info.cell_deps.len(), 1);
+    assert!(minimized.mock_tx.mock_info.header_deps.is_empty());
+    assert_eq!(minimized.removed, 2 + 2 + 3 + 2 + 1);
+
+    assert_eq!(minimized.group_index, 0);
+    assert_eq!(minimized.group.group_type, ScriptGroupType::Lock);
+    assert_eq!(minimized.group.input_indices, vec![0]);
+    assert!(minimized.group.code_dep.is_some());
+    assert!(minimized.cycles.is_some());
+    assert!(minimized.error.is_none());
+
+    // The file round-trips and still runs the group.
+    let tx_file = write_temp_file(
+        "minimized.json",
+        minimized
+            .mock_tx
+            .to_json_string(ckb_debugger_dumper::JsonFormat::Pretty)
+            .as_bytes(),
+    );
+    assert!(minimized
+        .command(std::path::Path::new(&tx_file), None)
+        .contains("--script-group-type lock"));
+    let parsed = MockTransaction::read_from_file(&tx_file).unwrap();
+    assert_eq!(group_index(&parsed, &[2]), 0);
+}
+
+#[test]
+fn test_minimize_failing_group() {
+    let mock_tx = gen_mock_tx();
+    let minimized = Minimizer::new(&mock_tx, group_index(&mock_tx, &[]))
+        .run()
+        .unwrap();
+
+    assert!(minimized.error.is_some());
+    assert!(minimized.cycles.is_none());
+    assert_eq!(minimized.mock_tx.tx.inputs().len(), 1);
+    // The broken code is what the group fails on, so it has to stay.
+    assert_eq!(minimized.mock_tx.mock_info.cell_deps.len(), 1);
+    assert_eq!(
+        minimized.mock_tx.mock_info.cell_deps[0].data,
+        Bytes::from(vec![0; 64])
+    );
+}
+
+#[test]
+fn test_minimize_unknown_group() {
+    let mock_tx = gen_mock_tx();
+    assert!(matches!(
+        Minimizer::new(&mock_tx, 10).run(),
+        Err(Error::UnknownScriptGroup(10))
+    ));
+}
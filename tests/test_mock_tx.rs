@@ -0,0 +1,152 @@
+use ckb_debugger_dumper::{JsonFormat, MockTransaction};
+
+mod misc;
+use misc::*;
+
+#[test]
+fn test_mock_tx_in_memory() {
+    let (tx, dummy) = gen_ckb_tx(
+        gen_always_success_cells(3),
+        gen_always_success_deps(),
+        Vec::new(),
+    );
+    let bin_path = write_temp_file(
+        "mock_tx_always_success",
+        ckb_always_success_script::ALWAYS_SUCCESS,
+    );
+
+    let json_file = write_temp_file("mock_tx.json", b"");
+    ckb_debugger_dumper::gen_json_with_data_loader(&dummy, &tx, 0, &bin_path, &json_file, None);
+
+    let mut mock_tx = MockTransaction::from_resolved_tx_with_data_loader(&dummy, &tx);
+    assert_eq!(mock_tx.mock_info.inputs.len(), 3);
+    assert_eq!(
+        mock_tx.mock_info.cell_deps.len(),
+        tx.resolved_cell_deps.len()
+    );
+    assert!(mock_tx
+        .mock_info
+        .cell_deps
+        .iter()
+        .all(|dep| !dep.data.is_empty()));
+
+    mock_tx.blank_bin(&bin_path);
+    assert!(mock_tx
+        .mock_info
+        .cell_deps
+        .iter()
+        .all(|dep| dep.data.is_empty()));
+    assert_eq!(
+        mock_tx.to_json_string(JsonFormat::Pretty),
+        std::fs::read_to_string(&json_file).unwrap()
+    );
+
+    let mut buf: Vec<u8> = Vec::new();
+    mock_tx.write_to(&mut buf, JsonFormat::Compact).unwrap();
+    let compact = String::from_utf8(buf).unwrap();
+    assert!(!compact.contains('\n'));
+    assert_eq!(compact, mock_tx.to_json_string(JsonFormat::Compact));
+    assert_eq!(json::parse(&compact).unwrap(), mock_tx.to_json());
+}
+
+#[test]
+fn test_mock_tx_from_json() {
+    use ckb_types::{
+        bytes::Bytes,
+        core::{EpochNumberWithFraction, HeaderBuilder, ScriptHashType},
+        prelude::*,
+    };
+
+    let header = HeaderBuilder::default()
+        .compact_target(u32_to_uint32(1))
+        .number(u64_to_uint64(100))
+        .timestamp(u64_to_uint64(1_600_000_000_000))
+        .epoch(EpochNumberWithFraction::new(10, 3, 100).full_value().pack())
+        .nonce(u128_to_uint128(0x1234_5678_9abc_def0))
+        .build();
+    let mut builder = TxBuilder::new();
+    let code = Bytes::from(ckb_always_success_script::ALWAYS_SUCCESS.to_vec());
+    let lock_code = builder.deploy_code(code.clone(), ScriptHashType::Data1);
+    builder.dep_group(std::slice::from_ref(&lock_code.out_point));
+    let lock = lock_code.script(Bytes::from(vec![1; 20]));
+    let type_ = builder
+        .deploy(code, ScriptHashType::Type)
+        .script(Bytes::new());
+    let (tx, dummy) = builder
+        .input_with_since(lock.clone(), Some(type_), Bytes::from(vec![2; 8]), 0x64)
+        .output(lock, None, Bytes::new())
+        .witness_args(Some(Bytes::from(vec![4; 65])), None, None)
+        .header_dep(header)
+        .build();
+
+    let mock_tx = MockTransaction::from_resolved_tx_with_data_loader(&dummy, &tx);
+    let parsed =
+        MockTransaction::from_json_str(&mock_tx.to_json_string(JsonFormat::Pretty)).unwrap();
+    assert_eq!(parsed.to_json(), mock_tx.to_json());
+    assert_eq!(parsed.tx.hash(), mock_tx.tx.hash());
+
+    let resolved = parsed.resolve().unwrap();
+    assert_eq!(resolved.resolved_inputs.len(), 1);
+    assert_eq!(resolved.resolved_dep_groups.len(), 1);
+    ckb_script::TransactionScriptsVerifier::new(&resolved, &parsed.mock_info)
+        .verify(0xFFFFFFFF)
+        .unwrap();
+
+    assert!(MockTransaction::from_json_str("{}").is_err());
+    assert!(MockTransaction::from_json_str("not json").is_err());
+}
+
+#[test]
+fn test_mock_tx_header_format() {
+    use ckb_types::{
+        bytes::Bytes,
+        core::{EpochNumberWithFraction, HeaderBuilder, ScriptHashType},
+        prelude::*,
+    };
+
+    let epoch = EpochNumberWithFraction::new(10, 3, 100);
+    let header = HeaderBuilder::default()
+        .number(u64_to_uint64(1000))
+        .timestamp(u64_to_uint64(1_600_000_000_000))
+        .epoch(epoch.full_value().pack())
+        .nonce(u128_to_uint128(0x1234_5678_9abc_def0))
+        .build();
+    let mut builder = TxBuilder::new();
+    let lock = builder
+        .deploy(
+            Bytes::from(ckb_always_success_script::ALWAYS_SUCCESS.to_vec()),
+            ScriptHashType::Data1,
+        )
+        .script(Bytes::new());
+    let (tx, dummy) = builder
+        .input(lock, None, Bytes::new())
+        .header_dep(header.clone())
+        .build();
+
+    // Numbers are written as in the ckb JSON format, the epoch as its full value.
+    let mut js = MockTransaction::from_resolved_tx_with_data_loader(&dummy, &tx).to_json();
+    let js_header = &js["mock_info"]["header_deps"][0];
+    assert_eq!(
+        js_header["hash"].as_str(),
+        Some(format!("{:#x}", header.hash()).as_str())
+    );
+    assert_eq!(js_header["timestamp"].as_str(), Some("0x174876e8000"));
+    assert_eq!(
+        js_header["epoch"].as_str(),
+        Some(format!("{:#x}", epoch.full_value()).as_str())
+    );
+    assert_eq!(js_header["nonce"].as_str(), Some("0x123456789abcdef0"));
+
+    let parsed = MockTransaction::from_json(&js).unwrap();
+    let parsed_header = &parsed.mock_info.header_deps[0];
+    assert_eq!(parsed_header.hash(), header.hash());
+    assert_eq!(parsed_header.epoch(), epoch);
+    assert_eq!(parsed_header.epoch().index(), 3);
+    assert_eq!(parsed_header.epoch().length(), 100);
+    assert_eq!(parsed_header.timestamp(), 1_600_000_000_000);
+
+    // Earlier dumps wrote the epoch index alone, which no longer matches the header hash.
+    js["mock_info"]["header_deps"][0]["epoch"] = "0x3".into();
+    let err = MockTransaction::from_json(&js).unwrap_err();
+    assert!(err.to_string().contains("hash does not match"), "{}", err);
+}
@@ -0,0 +1,164 @@
+use ckb_debugger_dumper::{DumpDir, DumpOptions, Error, MockTransaction};
+use ckb_script::TransactionScriptsVerifier;
+use std::collections::HashSet;
+
+mod misc;
+use misc::*;
+
+#[test]
+fn test_gen_json_to_dir() {
+    let (tx, dummy) = gen_ckb_tx(
+        gen_always_success_cells(3),
+        gen_always_success_deps(),
+        Vec::new(),
+    );
+    let bin_path = write_temp_file(
+        "output_always_success",
+        ckb_always_success_script::ALWAYS_SUCCESS,
+    );
+    let dir = temp_dir("output").join("nested");
+    let dump_dir = DumpDir::new(&dir);
+
+    let groups = ckb_debugger_dumper::script_groups(&tx);
+    let mut files = HashSet::new();
+    for group_index in 0..groups.len() {
+        for _ in 0..2 {
+            let dump = ckb_debugger_dumper::gen_json_to_dir(
+                &dummy,
+                &tx,
+                group_index,
+                &bin_path,
+                &dump_dir,
+                None,
+            )
+            .unwrap();
+            assert!(dump.tx_file.exists());
+            assert!(dump
+                .command
+                .contains(&format!("--tx-file {}", dump.tx_file.to_str().unwrap())));
+            let file_name = dump.tx_file.file_name().unwrap().to_str().unwrap();
+            assert!(file_name.starts_with("test_gen_json_to_dir-"));
+            assert!(files.insert(dump.tx_file));
+        }
+    }
+
+    let entries: Vec<_> = std::fs::read_dir(&dir).unwrap().collect();
+    assert_eq!(entries.len(), files.len());
+
+    let err =
+        ckb_debugger_dumper::gen_json_to_dir(&dummy, &tx, groups.len(), &bin_path, &dump_dir, None)
+            .unwrap_err();
+    assert!(matches!(err, Error::UnknownScriptGroup(index) if index == groups.len()));
+}
+
+#[test]
+fn test_dump_dir_unique_in_parallel() {
+    let dump_dir = DumpDir::new(temp_dir("parallel")).name("same::test name");
+    let handles: Vec<_> = (0..8)
+        .map(|_| {
+            let dump_dir = dump_dir.clone();
+            std::thread::spawn(move || {
+                (0..16)
+                    .map(|_| {
+                        let path = dump_dir.unique_path(None, "json");
+                        ckb_debugger_dumper::write_atomic(&path, b"{}").unwrap();
+                        path
+                    })
+                    .collect::<Vec<_>>()
+            })
+        })
+        .collect();
+
+    let mut files = HashSet::new();
+    for handle in handles {
+        for path in handle.join().unwrap() {
+            assert!(path
+                .file_name()
+                .unwrap()
+                .to_str()
+                .unwrap()
+                .starts_with("same__test_name-"));
+            assert!(files.insert(path));
+        }
+    }
+    assert_eq!(std::fs::read_dir(dump_dir.dir()).unwrap().count(), 8 * 16);
+}
+
+#[test]
+fn test_write_atomic_replaces_file() {
+    let path = temp_dir("atomic").join("a").join("tx.json");
+    ckb_debugger_dumper::write_atomic(&path, b"first").unwrap();
+    ckb_debugger_dumper::write_atomic(&path, b"second").unwrap();
+    assert_eq!(std::fs::read(&path).unwrap(), b"second");
+    assert_eq!(
+        std::fs::read_dir(path.parent().unwrap()).unwrap().count(),
+        1
+    );
+}
+
+#[test]
+fn test_gen_json_options() {
+    let (tx, dummy) = gen_ckb_tx(
+        gen_always_success_cells(1),
+        gen_always_success_deps(),
+        Vec::new(),
+    );
+    let bin_path = write_temp_file(
+        "options_always_success",
+        ckb_always_success_script::ALWAYS_SUCCESS,
+    );
+    let verifier = TransactionScriptsVerifier::new(&tx, &dummy);
+    let options = DumpOptions::new()
+        .environment(gen_environment())
+        .max_cycles(1000)
+        .dbg_addr("127.0.0.1:9999");
+    let json_file = write_temp_file("options.json", b"");
+    let command = ckb_debugger_dumper::gen_json_with_options(
+        &verifier, &tx, None, 0, &bin_path, &json_file, &options,
+    );
+    assert!(
+        command.ends_with(
+            " --script-version 1 --max-cycles 1000 --mode gdb --gdb-listen 127.0.0.1:9999"
+        ),
+        "{}",
+        command
+    );
+    let mock_tx = MockTransaction::read_from_file(&json_file).unwrap();
+    assert_eq!(
+        mock_tx.environment.unwrap().to_json(),
+        gen_environment().to_json()
+    );
+
+    // gen_json is the same without a cycle limit and environment.
+    let plain_file = write_temp_file("options_plain.json", b"");
+    let command = ckb_debugger_dumper::gen_json(
+        &verifier,
+        &tx,
+        None,
+        0,
+        &bin_path,
+        &plain_file,
+        Some("127.0.0.1:9999"),
+    );
+    assert!(
+        command.ends_with(" --cell-type input --mode gdb --gdb-listen 127.0.0.1:9999"),
+        "{}",
+        command
+    );
+    let mock_tx = MockTransaction::read_from_file(&plain_file).unwrap();
+    assert!(mock_tx.environment.is_none());
+}
+
+#[test]
+fn test_dump_name() {
+    let dump_dir = DumpDir::new(temp_dir("dump_name")).name("module::test");
+    let hash = ckb_types::packed::Byte32::new([0xab; 32]);
+    for path in &[
+        dump_dir.unique_path(Some(&hash), "json"),
+        dump_dir.unique_path(None, "json"),
+    ] {
+        assert_eq!(DumpDir::dump_name(path), "module__test");
+    }
+    let fixture = std::path::Path::new("tests/dumps/udt-transfer-2.json");
+    assert_eq!(DumpDir::dump_name(fixture), "udt-transfer-2");
+}
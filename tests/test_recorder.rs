@@ -0,0 +1,108 @@
+use ckb_debugger_dumper::{dump_on_failure, verify_and_dump, DumpDir, DumpingVerifier, Recording};
+use ckb_types::{packed::Byte32, prelude::*};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+mod misc;
+use misc::*;
+
+fn json_files(dir: &std::path::Path) -> Vec<String> {
+    let mut files: Vec<String> = std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+                .filter(|name| {
+                    name.ends_with(".json")
+                        && !name.ends_with(".manifest.json")
+                        && !name.ends_with(".expected.json")
+                        && !name.ends_with(".witnesses.json")
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    files.sort();
+    files
+}
+
+#[test]
+fn test_recording_dumps_on_panic() {
+    let (tx, dummy) = gen_ckb_tx(
+        gen_always_success_cells(2),
+        gen_always_success_deps(),
+        Vec::new(),
+    );
+    let (mut bad_tx, bad_dummy) = gen_ckb_tx(
+        gen_always_success_cells(2),
+        gen_always_success_deps(),
+        Vec::new(),
+    );
+    let bad_lock = bad_tx.resolved_inputs[0]
+        .cell_output
+        .lock()
+        .as_builder()
+        .code_hash(Byte32::new([0xAA; 32]))
+        .build();
+    bad_tx.resolved_inputs[0].cell_output = bad_tx.resolved_inputs[0]
+        .cell_output
+        .clone()
+        .as_builder()
+        .lock(bad_lock)
+        .build();
+    let dir = temp_dir("recording_panic");
+    let unused_dir = temp_dir("recording_panic_unused");
+
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        let _recording = Recording::start("tests::recording_panic").dir(&dir);
+        DumpingVerifier::new(&tx, &dummy, DumpDir::new(&unused_dir))
+            .verify(0xFFFFFFFF)
+            .unwrap();
+        DumpingVerifier::new(&bad_tx, &bad_dummy, DumpDir::new(&unused_dir))
+            .verify(0xFFFFFFFF)
+            .unwrap();
+    }));
+    assert!(result.is_err());
+
+    let files = json_files(&dir);
+    assert_eq!(files.len(), 2);
+    assert!(files
+        .iter()
+        .all(|name| name.starts_with("tests-recording_panic-")));
+}
+
+#[test]
+fn test_recording_without_panic() {
+    let (tx, dummy) = gen_ckb_tx(
+        gen_always_success_cells(2),
+        gen_always_success_deps(),
+        Vec::new(),
+    );
+    let dir = temp_dir("recording_no_panic");
+    {
+        let _recording = Recording::start("tests::recording_no_panic").dir(&dir);
+        verify_and_dump(&tx, &dummy, 0xFFFFFFFF).unwrap();
+    }
+    assert!(!dir.exists());
+}
+
+#[test]
+#[dump_on_failure]
+fn test_dump_on_failure_passing() {
+    let (tx, dummy) = gen_ckb_tx(
+        gen_always_success_cells(2),
+        gen_always_success_deps(),
+        Vec::new(),
+    );
+    verify_and_dump(&tx, &dummy, 0xFFFFFFFF).unwrap();
+}
+
+#[test]
+#[should_panic(expected = "boom")]
+#[dump_on_failure]
+fn test_dump_on_failure_panicking() {
+    let (tx, dummy) = gen_ckb_tx(
+        gen_always_success_cells(2),
+        gen_always_success_deps(),
+        Vec::new(),
+    );
+    verify_and_dump(&tx, &dummy, 0xFFFFFFFF).unwrap();
+    panic!("boom");
+}
@@ -0,0 +1,127 @@
+use ckb_debugger_dumper::{Error, MockTransaction, Rehash};
+use ckb_script::TransactionScriptsVerifier;
+use ckb_types::{
+    bytes::Bytes,
+    core::ScriptHashType,
+    packed::{CellOutput, Script},
+    prelude::*,
+};
+
+mod misc;
+use misc::*;
+
+/// A UDT-like transaction: the type script's args are the hash of the owner lock, and both run
+/// the same `data1` binary.
+fn gen_mock_tx() -> (MockTransaction, Script, Script) {
+    let mut builder = TxBuilder::new();
+    let code = builder.deploy(
+        Bytes::from(ckb_always_success_script::ALWAYS_SUCCESS.to_vec()),
+        ScriptHashType::Data1,
+    );
+    let lock = code.script(Bytes::from(vec![1]));
+    let type_ = code.script(lock.calc_script_hash().as_bytes());
+    let (tx, data_loader) = builder
+        .input(lock.clone(), Some(type_.clone()), Bytes::from(vec![0; 16]))
+        .output(lock.clone(), Some(type_.clone()), Bytes::from(vec![0; 16]))
+        .build();
+    (
+        MockTransaction::from_resolved_tx_with_data_loader(&data_loader, &tx),
+        lock,
+        type_,
+    )
+}
+
+/// The always-success binary with a trailing byte, which runs the same but hashes differently.
+fn rebuilt() -> Bytes {
+    let mut code = ckb_always_success_script::ALWAYS_SUCCESS.to_vec();
+    code.push(0);
+    Bytes::from(code)
+}
+
+fn verify(mock_tx: &MockTransaction) {
+    let resolved_tx = mock_tx.resolve().unwrap();
+    TransactionScriptsVerifier::new(&resolved_tx, &mock_tx.mock_info)
+        .verify(u64::MAX)
+        .unwrap();
+}
+
+fn scripts(mock_tx: &MockTransaction) -> Vec<Script> {
+    mock_tx
+        .mock_info
+        .inputs
+        .iter()
+        .map(|input| input.output.clone())
+        .chain(mock_tx.tx.outputs())
+        .flat_map(|output| vec![output.lock(), output.type_().to_opt().unwrap()])
+        .collect()
+}
+
+#[test]
+fn test_rehash() {
+    let (mut mock_tx, lock, type_) = gen_mock_tx();
+    let old_code_hash = lock.code_hash();
+    let new_code = rebuilt();
+    let new_code_hash = CellOutput::calc_data_hash(&new_code);
+
+    let rehashed = Rehash::new(old_code_hash, new_code.clone())
+        .rewrite_args(true)
+        .apply(&mut mock_tx)
+        .unwrap();
+    assert_eq!(rehashed.new_code_hash, new_code_hash);
+    assert_eq!(rehashed.deps, vec![0]);
+    assert_eq!(mock_tx.mock_info.cell_deps[0].data, new_code);
+
+    let new_lock = lock.as_builder().code_hash(new_code_hash.clone()).build();
+    let new_type = type_
+        .as_builder()
+        .code_hash(new_code_hash)
+        .args(new_lock.calc_script_hash().as_bytes().pack())
+        .build();
+    for script in scripts(&mock_tx) {
+        assert!(script == new_lock || script == new_type);
+    }
+    assert_eq!(rehashed.script_hashes.len(), 3);
+    verify(&mock_tx);
+}
+
+#[test]
+fn test_rehash_blank_dep() {
+    let (mut mock_tx, lock, type_) = gen_mock_tx();
+    mock_tx.mock_info.cell_deps[0].data = Bytes::new();
+
+    let rehashed = Rehash::new(lock.code_hash(), rebuilt())
+        .apply(&mut mock_tx)
+        .unwrap();
+    assert!(rehashed.deps.is_empty());
+    assert_eq!(rehashed.script_hashes.len(), 2);
+    // Without rewriting args, the type script still names the old owner lock.
+    for script in scripts(&mock_tx) {
+        assert_eq!(script.code_hash(), rehashed.new_code_hash);
+    }
+    let output_type = mock_tx
+        .tx
+        .outputs()
+        .get(0)
+        .unwrap()
+        .type_()
+        .to_opt()
+        .unwrap();
+    assert_eq!(output_type.args().raw_data(), type_.args().raw_data());
+
+    let rehashed = Rehash::new(lock.code_hash(), rebuilt())
+        .dep(0)
+        .apply(&mut mock_tx)
+        .unwrap();
+    assert_eq!(rehashed.deps, vec![0]);
+    verify(&mock_tx);
+
+    match Rehash::new(lock.code_hash(), rebuilt())
+        .dep(3)
+        .apply(&mut mock_tx)
+    {
+        Err(Error::InvalidEdit(e)) => {
+            assert_eq!(e, "cell_deps[3] out of range, the transaction has 1")
+        }
+        r => panic!("unexpected {:?}", r),
+    }
+}
@@ -0,0 +1,83 @@
+use ckb_debugger_dumper::{DumpDir, DumpingVerifier, Report};
+use ckb_types::{
+    bytes::Bytes,
+    core::{EpochNumberWithFraction, HeaderBuilder, ScriptHashType},
+    prelude::*,
+};
+
+mod misc;
+use misc::*;
+
+#[test]
+fn test_report() {
+    let header = HeaderBuilder::default()
+        .compact_target(u32_to_uint32(1))
+        .number(u64_to_uint64(100))
+        .epoch(EpochNumberWithFraction::new(1, 0, 10).full_value().pack())
+        .build();
+    let mut builder = TxBuilder::new();
+    let code = Bytes::from(ckb_always_success_script::ALWAYS_SUCCESS.to_vec());
+    let lock = builder
+        .deploy(code.clone(), ScriptHashType::Data1)
+        .script(Bytes::from(vec![1; 20]));
+    let type_ = builder
+        .deploy(code, ScriptHashType::Type)
+        .script(Bytes::new());
+    let relative_epoch =
+        0xa000_0000_0000_0000 | EpochNumberWithFraction::new(1, 2, 10).full_value();
+    let (tx, data_loader) = builder
+        .input_with_since(lock.clone(), None, Bytes::from(vec![2; 8]), relative_epoch)
+        .input_with_since(lock.clone(), None, Bytes::new(), 1000)
+        .input_with_since(lock.clone(), None, Bytes::new(), 0x4000_0000_5f5e_1000)
+        .input_with_since(lock.clone(), None, Bytes::new(), 0x0100_0000_0000_03e8)
+        .output(lock.clone(), Some(type_.clone()), Bytes::new())
+        .witness_args(Some(Bytes::from(vec![0; 65])), None, None)
+        .header_dep(header.clone())
+        .build();
+
+    let report = Report::new(&data_loader, &tx).markdown;
+    assert!(report.starts_with(&format!(
+        "# Transaction `{}`",
+        fmt_hash(&tx.transaction.hash())
+    )));
+    assert!(report.contains("(relative epoch 1 + 2/10)"));
+    assert!(report.contains("(absolute block 1000)"));
+    assert!(report.contains("(absolute median time 1600000000 (unix seconds))"));
+    assert!(report.contains("  - warning: reserved bits 0x100000000000000 are set"));
+    assert!(report.contains(&format!("- lock: `{}`", fmt_hash(&lock.calc_script_hash()))));
+    assert!(report.contains(&format!(
+        "- type: `{}`",
+        fmt_hash(&type_.calc_script_hash())
+    )));
+    assert!(report.contains(&format!("- type hash: `{}`", fmt_hash(&type_.code_hash()))));
+    assert!(report.contains("- data: 8 bytes, blake2b `0x"));
+    assert!(report.contains("- groups: lock #0"));
+    assert!(report.contains("- groups: type #1"));
+    assert!(report.contains("- code of groups: lock #0"));
+    assert!(report.contains("- code of groups: type #1"));
+    assert!(report.contains(", number 100, epoch 1 + 0/10"));
+    assert!(report.contains("- 0: 85 bytes, blake2b `0x"));
+    assert!(report.contains("read by lock #0, type #1"));
+
+    let dir = temp_dir("report");
+    let manifest = DumpingVerifier::new(&tx, &data_loader, DumpDir::new(&dir))
+        .dump()
+        .unwrap();
+    assert!(!Report::path_for(&manifest.tx_file).exists());
+    let manifest = DumpingVerifier::new(&tx, &data_loader, DumpDir::new(&dir))
+        .report(true)
+        .dump()
+        .unwrap();
+    assert_eq!(
+        std::fs::read_to_string(Report::path_for(&manifest.tx_file)).unwrap(),
+        report
+    );
+}
+
+fn fmt_hash(hash: &ckb_types::packed::Byte32) -> String {
+    let mut s = String::from("0x");
+    for b in hash.as_slice() {
+        s.push_str(&format!("{:02x}", b));
+    }
+    s
+}
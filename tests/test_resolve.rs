@@ -0,0 +1,206 @@
+use ckb_debugger_dumper::Error;
+use ckb_types::{
+    core::{Capacity, DepType},
+    packed::{Byte32, CellDep, CellOutput, OutPoint, OutPointVec},
+    prelude::*,
+};
+
+mod misc;
+use misc::*;
+
+#[test]
+fn test_gen_json_from_tx() {
+    let (tx, dummy) = gen_ckb_tx(
+        gen_always_success_cells(3),
+        gen_always_success_deps(),
+        Vec::new(),
+    );
+    let bin_path = write_temp_file(
+        "resolve_always_success",
+        ckb_always_success_script::ALWAYS_SUCCESS,
+    );
+
+    let resolved_tx =
+        ckb_debugger_dumper::resolve_transaction(&tx.transaction, &dummy.cells, None).unwrap();
+    let out_points = |cells: &[ckb_types::core::cell::CellMeta]| -> Vec<OutPoint> {
+        cells.iter().map(|c| c.out_point.clone()).collect()
+    };
+    assert_eq!(
+        out_points(&resolved_tx.resolved_inputs),
+        out_points(&tx.resolved_inputs)
+    );
+    assert_eq!(
+        out_points(&resolved_tx.resolved_cell_deps),
+        out_points(&tx.resolved_cell_deps)
+    );
+
+    let expected_json = write_temp_file("resolve_expected.json", b"");
+    ckb_debugger_dumper::gen_json_with_data_loader(&dummy, &tx, 0, &bin_path, &expected_json, None);
+    let json_file = write_temp_file("resolve.json", b"");
+    ckb_debugger_dumper::gen_json_from_tx(
+        &tx.transaction,
+        &dummy.cells,
+        None,
+        0,
+        &bin_path,
+        &json_file,
+        None,
+    )
+    .unwrap();
+    assert_eq!(
+        std::fs::read_to_string(&expected_json).unwrap(),
+        std::fs::read_to_string(&json_file).unwrap()
+    );
+}
+
+#[test]
+fn test_gen_json_from_tx_errors() {
+    let (tx, dummy) = gen_ckb_tx(
+        gen_always_success_cells(1),
+        gen_always_success_deps(),
+        Vec::new(),
+    );
+    let bin_path = write_temp_file(
+        "resolve_errors_always_success",
+        ckb_always_success_script::ALWAYS_SUCCESS,
+    );
+    let json_file = write_temp_file("resolve_errors.json", b"");
+    let gen = |group_index: usize, json_file: &str| {
+        ckb_debugger_dumper::gen_json_from_tx(
+            &tx.transaction,
+            &dummy.cells,
+            None,
+            group_index,
+            &bin_path,
+            json_file,
+            None,
+        )
+    };
+    assert!(matches!(
+        gen(10, &json_file),
+        Err(Error::UnknownScriptGroup(10))
+    ));
+    // The parent of the json file is a file, so it cannot be written.
+    let bad_json_file = format!("{}/dump.json", json_file);
+    assert!(matches!(gen(0, &bad_json_file), Err(Error::Io(_))));
+
+    let empty_bin = write_temp_file("resolve_errors_empty_bin", b"");
+    assert!(matches!(
+        ckb_debugger_dumper::gen_json_from_tx(
+            &tx.transaction,
+            &dummy.cells,
+            None,
+            0,
+            &empty_bin,
+            &json_file,
+            None,
+        ),
+        Err(Error::EmptyBin(path)) if path.ends_with("resolve_errors_empty_bin")
+    ));
+}
+
+#[test]
+fn test_resolve_dep_group() {
+    let (tx, mut dummy) = gen_ckb_tx(
+        gen_always_success_cells(2),
+        gen_always_success_deps(),
+        Vec::new(),
+    );
+
+    let members: Vec<OutPoint> = tx
+        .transaction
+        .cell_deps()
+        .into_iter()
+        .map(|dep| dep.out_point())
+        .collect();
+    let group_data = OutPointVec::new_builder().set(members.clone()).build();
+    let group_out_point = OutPoint::new(Byte32::new([9; 32]), 0);
+    dummy.cells.insert(
+        group_out_point.clone(),
+        (
+            CellOutput::new_builder()
+                .capacity(Capacity::bytes(group_data.as_slice().len()).unwrap().pack())
+                .build(),
+            group_data.as_bytes(),
+        ),
+    );
+    let tx_view = tx
+        .transaction
+        .as_advanced_builder()
+        .set_cell_deps(vec![CellDep::new_builder()
+            .out_point(group_out_point.clone())
+            .dep_type(DepType::DepGroup.into())
+            .build()])
+        .build();
+
+    let resolved_tx =
+        ckb_debugger_dumper::resolve_transaction(&tx_view, &dummy.cells, None).unwrap();
+    assert_eq!(resolved_tx.resolved_dep_groups.len(), 1);
+    assert_eq!(
+        resolved_tx.resolved_dep_groups[0].out_point,
+        group_out_point
+    );
+    let deps: Vec<OutPoint> = resolved_tx
+        .resolved_cell_deps
+        .iter()
+        .map(|c| c.out_point.clone())
+        .collect();
+    assert_eq!(deps, members);
+}
+
+#[test]
+fn test_resolve_unresolved_out_points() {
+    let (tx, mut dummy) = gen_ckb_tx(
+        gen_always_success_cells(3),
+        gen_always_success_deps(),
+        Vec::new(),
+    );
+    let input = tx.transaction.inputs().get(1).unwrap().previous_output();
+    let dep = tx.transaction.cell_deps().get(0).unwrap().out_point();
+    dummy.cells.remove(&input);
+    dummy.cells.remove(&dep);
+
+    match ckb_debugger_dumper::resolve_transaction(&tx.transaction, &dummy.cells, None) {
+        Err(Error::UnresolvedOutPoints(out_points)) => assert_eq!(out_points, vec![input, dep]),
+        _ => panic!("unresolved out points are not reported"),
+    }
+}
+
+#[test]
+fn test_resolve_unknown_header() {
+    let (tx, dummy) = gen_ckb_tx(
+        gen_always_success_cells(1),
+        gen_always_success_deps(),
+        Vec::new(),
+    );
+    let tx_view = tx
+        .transaction
+        .as_advanced_builder()
+        .header_dep(Byte32::new([7; 32]))
+        .build();
+    assert!(matches!(
+        ckb_debugger_dumper::resolve_transaction(&tx_view, &dummy.cells, None),
+        Err(Error::UnknownHeader(_))
+    ));
+}
+
+#[test]
+fn test_resolve_duplicated_input() {
+    let (tx, dummy) = gen_ckb_tx(
+        gen_always_success_cells(2),
+        gen_always_success_deps(),
+        Vec::new(),
+    );
+    let input = tx.transaction.inputs().get(0).unwrap();
+    let duplicated = tx
+        .transaction
+        .as_advanced_builder()
+        .input(input.clone())
+        .build();
+    match ckb_debugger_dumper::resolve_transaction(&duplicated, &dummy.cells, None) {
+        Err(Error::DuplicatedInput(out_point)) => {
+            assert_eq!(out_point, input.previous_output())
+        }
+        r => panic!("duplicated input is not reported: {:?}", r.err()),
+    }
+}
@@ -0,0 +1,56 @@
+use ckb_debugger_dumper::{Since, SinceMetric, SinceWarning};
+use ckb_types::core::EpochNumberWithFraction;
+
+#[test]
+fn test_since() {
+    assert!(Since(0).is_none());
+    assert_eq!(Since(0).metric(), None);
+    assert_eq!(Since(0).to_string(), "none");
+
+    let block = Since(1000);
+    assert!(!block.is_relative());
+    assert_eq!(block.metric(), Some(SinceMetric::BlockNumber(1000)));
+    assert_eq!(block.to_string(), "absolute block 1000");
+
+    let epoch = EpochNumberWithFraction::new(1, 2, 10);
+    let relative_epoch = Since(0xa000_0000_0000_0000 | epoch.full_value());
+    assert!(relative_epoch.is_relative());
+    assert_eq!(relative_epoch.metric(), Some(SinceMetric::Epoch(epoch)));
+    assert_eq!(relative_epoch.to_string(), "relative epoch 1 + 2/10");
+
+    assert_eq!(
+        Since(0xc000_0000_0000_0e10).to_string(),
+        "relative 3600 seconds"
+    );
+    assert_eq!(
+        Since(0x4000_0000_5f5e_1000).to_string(),
+        "absolute median time 1600000000 (unix seconds)"
+    );
+
+    for since in [0, 1000, relative_epoch.0, 0x4000_0000_5f5e_1000] {
+        assert!(Since(since).warnings().is_empty(), "{:#x}", since);
+    }
+}
+
+#[test]
+fn test_since_warnings() {
+    assert_eq!(
+        Since(0x0100_0000_0000_03e8).warnings(),
+        vec![SinceWarning::ReservedBits(0x0100_0000_0000_0000)]
+    );
+
+    let invalid = Since(0x6000_0000_0000_0001);
+    assert_eq!(invalid.metric(), None);
+    assert_eq!(invalid.to_string(), "absolute invalid metric flag");
+    assert_eq!(invalid.warnings(), vec![SinceWarning::InvalidMetric]);
+
+    // Index 5 of an epoch of length 3.
+    let malformed = Since(0x2000_0000_0000_0000 | (3 << 40) | (5 << 24) | 1);
+    assert_eq!(malformed.warnings(), vec![SinceWarning::MalformedEpoch]);
+    // A zero length is only accepted for relative epochs, as 0/0.
+    assert!(Since(0xa000_0000_0000_0001).warnings().is_empty());
+    assert_eq!(
+        Since(0x2000_0000_0000_0001).warnings(),
+        vec![SinceWarning::MalformedEpoch]
+    );
+}
@@ -0,0 +1,72 @@
+use ckb_debugger_dumper::{list_script_groups, testkit::*};
+use ckb_script::TransactionScriptsVerifier;
+use ckb_traits::{CellDataProvider, HeaderProvider};
+use ckb_types::{
+    bytes::Bytes,
+    core::{EpochNumberWithFraction, HeaderBuilder, ScriptHashType},
+    prelude::*,
+};
+
+fn always_success() -> Bytes {
+    Bytes::from(ckb_always_success_script::ALWAYS_SUCCESS.to_vec())
+}
+
+#[test]
+fn test_tx_builder() {
+    let header = HeaderBuilder::default()
+        .compact_target(u32_to_uint32(1))
+        .number(u64_to_uint64(100))
+        .epoch(EpochNumberWithFraction::new(1, 0, 10).full_value().pack())
+        .build();
+
+    let mut builder = TxBuilder::new();
+    let lock = builder
+        .deploy(always_success(), ScriptHashType::Data1)
+        .script(Bytes::from(vec![1; 20]));
+    let type_code = builder.deploy_code(always_success(), ScriptHashType::Type);
+    builder.dep_group(std::slice::from_ref(&type_code.out_point));
+    let type_ = type_code.script(Bytes::new());
+
+    let (tx, data_loader) = builder
+        .input(lock.clone(), None, Bytes::from(vec![2; 8]))
+        .input_with_since(
+            lock.clone(),
+            Some(type_.clone()),
+            Bytes::new(),
+            0x2000_0000_0000_0064,
+        )
+        .output(lock, Some(type_), Bytes::from(vec![3; 8]))
+        .witness_args(Some(Bytes::from(vec![4; 65])), None, None)
+        .witness(Bytes::new())
+        .header_dep(header.clone())
+        .build();
+
+    assert_eq!(tx.resolved_inputs.len(), 2);
+    assert_eq!(tx.resolved_cell_deps.len(), 2);
+    assert_eq!(tx.resolved_dep_groups.len(), 1);
+    assert_eq!(
+        tx.transaction
+            .inputs()
+            .get(1)
+            .map(|input| Unpack::<u64>::unpack(&input.since())),
+        Some(0x2000_0000_0000_0064)
+    );
+
+    let out_point = tx.resolved_inputs[0].out_point.clone();
+    assert_eq!(
+        data_loader.get_cell_data(&out_point),
+        Some(Bytes::from(vec![2; 8]))
+    );
+    assert!(data_loader.get_cell_data_hash(&out_point).is_some());
+    assert_eq!(
+        HeaderProvider::get_header(&data_loader, &header.hash()).map(|h| h.hash()),
+        Some(header.hash())
+    );
+
+    let groups = list_script_groups(&data_loader, &tx, None);
+    assert_eq!(groups.len(), 2);
+    assert!(groups.iter().all(|group| group.code_dep.is_some()));
+
+    let verifier = TransactionScriptsVerifier::new(&tx, &data_loader);
+    verifier.verify(0xFFFFFFFF).unwrap();
+}
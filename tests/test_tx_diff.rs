@@ -0,0 +1,168 @@
+use ckb_debugger_dumper::{diff_mock_tx, ChangeKind, MockTransaction};
+use ckb_types::{
+    bytes::Bytes,
+    core::{DepType, EpochNumberWithFraction, HeaderBuilder, ScriptHashType},
+    packed::{CellOutput, ScriptOpt, WitnessArgs},
+    prelude::*,
+};
+
+mod misc;
+use misc::*;
+
+fn gen_mock_tx() -> MockTransaction {
+    let mut builder = TxBuilder::new();
+    let code = Bytes::from(ckb_always_success_script::ALWAYS_SUCCESS.to_vec());
+    let lock = builder
+        .deploy(code, ScriptHashType::Data1)
+        .script(Bytes::from(vec![1; 20]));
+    let (tx, data_loader) = builder
+        .input(lock.clone(), None, Bytes::from(vec![2; 8]))
+        .input(lock.clone(), None, Bytes::new())
+        .output(lock, None, Bytes::new())
+        .witness_args(Some(Bytes::from(vec![0; 65])), None, None)
+        .build();
+    MockTransaction::from_resolved_tx_with_data_loader(&data_loader, &tx)
+}
+
+fn kind<'a>(diff: &'a ckb_debugger_dumper::TxDiff, suffix: &str) -> &'a ChangeKind {
+    &diff
+        .changes
+        .iter()
+        .find(|change| change.path.ends_with(suffix))
+        .unwrap_or_else(|| panic!("no change at {} in\n{}", suffix, diff))
+        .kind
+}
+
+#[test]
+fn test_diff_mock_tx() {
+    let before = gen_mock_tx();
+    assert!(diff_mock_tx(&before, &before).is_empty());
+
+    let mut after = before.clone();
+    after.mock_info.inputs[0].data = Bytes::from(vec![3; 40]);
+    after.mock_info.inputs.swap(0, 1);
+    let output: CellOutput = after.tx.outputs().get(0).unwrap();
+    let lock = output
+        .lock()
+        .as_builder()
+        .args(Bytes::from(vec![9; 20]).pack())
+        .build();
+    let output = output
+        .as_builder()
+        .capacity(u64_to_uint64(1000))
+        .lock(lock)
+        .build();
+    let witness = WitnessArgs::new_builder()
+        .lock(Some(Bytes::from(vec![1; 65])).pack())
+        .build();
+    let header = HeaderBuilder::default()
+        .compact_target(u32_to_uint32(1))
+        .epoch(EpochNumberWithFraction::new(1, 0, 10).full_value().pack())
+        .build();
+    after.tx = after
+        .tx
+        .as_advanced_builder()
+        .set_outputs(vec![output])
+        .set_witnesses(vec![witness.as_bytes().pack(), Bytes::new().pack()])
+        .header_dep(header.hash())
+        .build();
+    after.mock_info.header_deps.push(header);
+
+    let diff = diff_mock_tx(&before, &after);
+    // Inputs are aligned by out point, so reordering them is not a change.
+    assert_eq!(
+        diff.changes
+            .iter()
+            .filter(|change| change.path.starts_with("inputs"))
+            .count(),
+        1
+    );
+    match kind(&diff, "].data") {
+        ChangeKind::Changed { before, after } => {
+            assert_eq!(before, "0x0202020202020202");
+            assert!(after.starts_with("40 bytes 0x"));
+        }
+        kind => panic!("{:?}", kind),
+    }
+    assert_eq!(
+        kind(&diff, "outputs[0].capacity"),
+        &ChangeKind::Changed {
+            before: Unpack::<u64>::unpack(&before.tx.outputs().get(0).unwrap().capacity())
+                .to_string(),
+            after: String::from("1000"),
+        }
+    );
+    assert!(matches!(
+        kind(&diff, "outputs[0].lock.args"),
+        ChangeKind::Changed { .. }
+    ));
+    assert!(diff
+        .changes
+        .iter()
+        .all(|change| !change.path.ends_with("lock.code_hash")));
+    assert!(matches!(
+        kind(&diff, "witnesses[0].lock"),
+        ChangeKind::Changed { .. }
+    ));
+    assert!(diff
+        .changes
+        .iter()
+        .all(|change| change.path != "witnesses[0].input_type"));
+    assert!(matches!(kind(&diff, "witnesses[1]"), ChangeKind::Added(_)));
+    assert!(matches!(
+        kind(&diff, "header_deps[0]"),
+        ChangeKind::Added(_)
+    ));
+
+    let text = diff.to_string();
+    assert!(text.contains("~ outputs[0].capacity: "));
+    assert!(text.contains("+ header_deps[0]: 0x"));
+}
+
+#[test]
+fn test_diff_mock_tx_cells() {
+    let before = gen_mock_tx();
+    let mut after = before.clone();
+    let dep = &mut after.mock_info.cell_deps[0];
+    dep.cell_dep = dep
+        .cell_dep
+        .clone()
+        .as_builder()
+        .dep_type(DepType::DepGroup.into())
+        .build();
+    let output: CellOutput = after.tx.outputs().get(0).unwrap();
+    let output = output
+        .clone()
+        .as_builder()
+        .type_(ScriptOpt::new_builder().set(Some(output.lock())).build())
+        .build();
+    after.tx = after
+        .tx
+        .as_advanced_builder()
+        .output(output)
+        .output_data(Bytes::new().pack())
+        .build();
+
+    let diff = diff_mock_tx(&before, &after);
+    assert_eq!(
+        kind(&diff, "].dep_type"),
+        &ChangeKind::Changed {
+            before: String::from("code"),
+            after: String::from("dep_group"),
+        }
+    );
+    match kind(&diff, "outputs[1]") {
+        ChangeKind::Added(cell) => assert!(cell.contains(", type 0x")),
+        kind => panic!("{:?}", kind),
+    }
+
+    let mut removed = before.clone();
+    removed.mock_info.cell_deps.clear();
+    match kind(&diff_mock_tx(&before, &removed), "]") {
+        ChangeKind::Removed(cell) => {
+            assert!(cell.starts_with("dep_type code, "));
+            assert!(cell.contains(", type none, "));
+        }
+        kind => panic!("{:?}", kind),
+    }
+}
@@ -0,0 +1,289 @@
+use ckb_debugger_dumper::{
+    verify_and_dump, DebugCapture, DumpDir, DumpMode, DumpingVerifier, Error, Expected, Manifest,
+    DUMP_DIR_VAR, DUMP_MODE_VAR,
+};
+use ckb_script::{ScriptGroupType, TransactionScriptsVerifier};
+use ckb_types::{
+    core::cell::ResolvedTransaction,
+    packed::{Byte32, Script},
+    prelude::*,
+};
+
+mod misc;
+use misc::*;
+
+fn gen_failing_tx() -> (ResolvedTransaction, DummyDataLoader, Script) {
+    let (mut tx, dummy) = gen_ckb_tx(
+        gen_always_success_cells(4),
+        gen_always_success_deps(),
+        Vec::new(),
+    );
+    let bad_lock = tx.resolved_inputs[1]
+        .cell_output
+        .lock()
+        .as_builder()
+        .code_hash(Byte32::new([0xAA; 32]))
+        .build();
+    tx.resolved_inputs[1].cell_output = tx.resolved_inputs[1]
+        .cell_output
+        .clone()
+        .as_builder()
+        .lock(bad_lock.clone())
+        .build();
+    (tx, dummy, bad_lock)
+}
+
+fn count_files(dir: &std::path::Path, suffix: &str) -> usize {
+    std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter(|e| {
+                    e.as_ref()
+                        .unwrap()
+                        .file_name()
+                        .to_string_lossy()
+                        .ends_with(suffix)
+                })
+                .count()
+        })
+        .unwrap_or(0)
+}
+
+#[test]
+fn test_dumping_verifier_success() {
+    let (tx, dummy) = gen_ckb_tx(
+        gen_always_success_cells(4),
+        gen_always_success_deps(),
+        Vec::new(),
+    );
+    let dir = temp_dir("verifier_success");
+
+    let verifier = DumpingVerifier::new(&tx, &dummy, DumpDir::new(&dir));
+    let cycles = verifier.verify(0xFFFFFFFF).unwrap();
+    let expected = TransactionScriptsVerifier::new(&tx, &dummy)
+        .verify(0xFFFFFFFF)
+        .unwrap();
+    assert_eq!(cycles, expected);
+    assert!(!dir.exists());
+}
+
+#[test]
+fn test_dumping_verifier_failure() {
+    let (tx, dummy, bad_lock) = gen_failing_tx();
+    let bin_path = write_temp_file(
+        "verifier_always_success",
+        ckb_always_success_script::ALWAYS_SUCCESS,
+    );
+    let dir = temp_dir("verifier_failure");
+
+    let verifier = DumpingVerifier::new(&tx, &dummy, DumpDir::new(&dir)).bin_path(&bin_path);
+    let err = verifier.verify(0xFFFFFFFF).unwrap_err();
+
+    let groups = ckb_debugger_dumper::script_groups(&tx);
+    assert_eq!(groups[err.group_index].0, bad_lock.calc_script_hash());
+    assert_eq!(groups[err.group_index].1.input_indices, vec![1]);
+
+    let dump = err.dump.as_ref().unwrap().as_ref().unwrap();
+    assert!(dump
+        .tx_file
+        .starts_with(std::fs::canonicalize(&dir).unwrap()));
+    assert!(dump
+        .command
+        .contains("--cell-index 1 --script-group-type lock"));
+    assert!(dump.command.contains("--bin "));
+    assert!(err.to_string().contains(&dump.command));
+
+    let manifest =
+        json::parse(&std::fs::read_to_string(Manifest::path_for(&dump.tx_file)).unwrap()).unwrap();
+    assert_eq!(manifest["groups"].len(), groups.len());
+    for (i, group) in manifest["groups"].members().enumerate() {
+        assert_eq!(group["error"].is_null(), i != err.group_index);
+    }
+    let group = &manifest["groups"][err.group_index];
+    assert_eq!(
+        dump.command,
+        format!(
+            "{} # failed: {}",
+            group["command"].as_str().unwrap(),
+            group["error"].as_str().unwrap()
+        )
+    );
+}
+
+#[test]
+fn test_dumping_verifier_modes() {
+    let (tx, dummy) = gen_ckb_tx(
+        gen_always_success_cells(2),
+        gen_always_success_deps(),
+        Vec::new(),
+    );
+    let dir = temp_dir("verifier_always");
+    let verifier = DumpingVerifier::new(&tx, &dummy, DumpDir::new(&dir)).mode(DumpMode::Always);
+    verifier.verify(0xFFFFFFFF).unwrap();
+    assert_eq!(count_files(&dir, ".manifest.json"), 1);
+    let manifest = verifier.take_dump().unwrap().unwrap();
+    assert!(manifest.tx_file.exists());
+    assert!(verifier.take_dump().is_none());
+
+    // A dump that cannot be written is returned instead of printed.
+    let file = write_temp_file("verifier_always_file", b"");
+    let verifier = DumpingVerifier::new(&tx, &dummy, DumpDir::new(&file)).mode(DumpMode::Always);
+    verifier.verify(0xFFFFFFFF).unwrap();
+    assert!(matches!(verifier.take_dump(), Some(Err(Error::Io(_)))));
+
+    let (tx, dummy, _) = gen_failing_tx();
+    let dir = temp_dir("verifier_never");
+    let err = DumpingVerifier::new(&tx, &dummy, DumpDir::new(&dir))
+        .mode(DumpMode::Never)
+        .verify(0xFFFFFFFF)
+        .unwrap_err();
+    assert!(err.dump.is_none());
+    assert!(!dir.exists());
+
+    assert_eq!("on-failure".parse(), Ok(DumpMode::OnFailure));
+    assert!("sometimes".parse::<DumpMode>().is_err());
+}
+
+// The only test of this file that touches the environment, the others are not affected by it.
+#[test]
+fn test_verify_and_dump_env() {
+    let (tx, dummy, _) = gen_failing_tx();
+    let dir = temp_dir("verifier_env");
+
+    std::env::remove_var(DUMP_DIR_VAR);
+    std::env::remove_var(DUMP_MODE_VAR);
+    let err = verify_and_dump(&tx, &dummy, 0xFFFFFFFF).unwrap_err();
+    assert!(err.dump.is_none());
+
+    std::env::set_var(DUMP_DIR_VAR, &dir);
+    let err = verify_and_dump(&tx, &dummy, 0xFFFFFFFF).unwrap_err();
+    assert!(err.dump.unwrap().is_ok());
+    assert_eq!(count_files(&dir, ".manifest.json"), 1);
+
+    let (tx, dummy) = gen_ckb_tx(
+        gen_always_success_cells(2),
+        gen_always_success_deps(),
+        Vec::new(),
+    );
+    verify_and_dump(&tx, &dummy, 0xFFFFFFFF).unwrap();
+    assert_eq!(count_files(&dir, ".manifest.json"), 1);
+    std::env::set_var(DUMP_MODE_VAR, "always");
+    verify_and_dump(&tx, &dummy, 0xFFFFFFFF).unwrap();
+    assert_eq!(count_files(&dir, ".manifest.json"), 2);
+
+    std::env::remove_var(DUMP_DIR_VAR);
+    std::env::remove_var(DUMP_MODE_VAR);
+}
+
+#[test]
+fn test_debug_capture() {
+    let capture = DebugCapture::new();
+    let printer = capture.printer();
+    let hash = Byte32::new([1; 32]);
+    printer(&hash, "hello ");
+    printer(&hash, "world");
+    printer(&Byte32::new([2; 32]), "other");
+    assert_eq!(capture.output(ScriptGroupType::Lock, &hash), "hello world");
+    assert_eq!(capture.output(ScriptGroupType::Type, &hash), "hello world");
+    capture.clear();
+    assert_eq!(capture.output(ScriptGroupType::Lock, &hash), "");
+
+    // A script used as a lock and as a type prints into two groups.
+    capture.set_group_type(ScriptGroupType::Lock);
+    printer(&hash, "as lock");
+    capture.set_group_type(ScriptGroupType::Type);
+    printer(&hash, "as type");
+    assert_eq!(capture.output(ScriptGroupType::Lock, &hash), "as lock");
+    assert_eq!(capture.output(ScriptGroupType::Type, &hash), "as type");
+}
+
+#[test]
+fn test_dumping_verifier_expected() {
+    let (tx, dummy, _) = gen_failing_tx();
+    let dir = temp_dir("verifier_expected");
+
+    let verifier = DumpingVerifier::new(&tx, &dummy, DumpDir::new(&dir));
+    let err = verifier.verify(0xFFFFFFFF).unwrap_err();
+    let expected = verifier.expected().unwrap();
+    for (i, group) in expected.groups.iter().enumerate() {
+        assert_eq!(group.error.is_some(), i == err.group_index);
+        assert_eq!(group.cycles.is_some(), i < err.group_index);
+    }
+
+    let tx_file = err.dump.unwrap().unwrap().tx_file;
+    let sidecar =
+        json::parse(&std::fs::read_to_string(Expected::path_for(&tx_file)).unwrap()).unwrap();
+    assert_eq!(sidecar["groups"].len(), expected.groups.len());
+    assert!(sidecar["groups"][err.group_index]["cycles"].is_null());
+    assert!(sidecar["groups"][err.group_index]["error"].is_string());
+
+    let (tx, dummy) = gen_ckb_tx(
+        gen_always_success_cells(4),
+        gen_always_success_deps(),
+        Vec::new(),
+    );
+    let verifier = DumpingVerifier::new(&tx, &dummy, DumpDir::new(&dir));
+    let cycles = verifier.verify(0xFFFFFFFF).unwrap();
+    let expected = verifier.expected().unwrap();
+    assert_eq!(
+        expected
+            .groups
+            .iter()
+            .map(|g| g.cycles.unwrap())
+            .sum::<u64>(),
+        cycles
+    );
+}
+
+#[test]
+fn test_dumping_verifier_cycles() {
+    let (tx, dummy) = gen_ckb_tx(
+        gen_always_success_cells(2),
+        gen_always_success_deps(),
+        Vec::new(),
+    );
+    let dir = temp_dir("verifier_cycles");
+    let verifier = DumpingVerifier::new(&tx, &dummy, DumpDir::new(&dir))
+        .mode(DumpMode::Always)
+        .max_cycles(0xFFFFFFFF);
+    let total = verifier.verify(0xFFFFFFFF).unwrap();
+    let manifest = verifier.dump().unwrap();
+    let mut left = 0xFFFFFFFF;
+    for group in &manifest.groups {
+        let cycles = group.cycles.unwrap();
+        assert!(cycles > 0);
+        assert_eq!(group.max_cycles, Some(left));
+        assert!(group.command.ends_with(&format!(" --max-cycles {}", left)));
+        left -= cycles;
+    }
+    assert_eq!(0xFFFFFFFF - left, total);
+    let js = json::parse(&std::fs::read_to_string(Manifest::path_for(&manifest.tx_file)).unwrap())
+        .unwrap();
+    assert_eq!(
+        js["groups"][0]["cycles"].as_u64(),
+        manifest.groups[0].cycles
+    );
+
+    // Just short of what the last group needs.
+    let dir = temp_dir("verifier_cycles_exceeded");
+    let err = DumpingVerifier::new(&tx, &dummy, DumpDir::new(&dir))
+        .verify(total - 1)
+        .unwrap_err();
+    assert_eq!(err.group_index, manifest.groups.len() - 1);
+    let last = manifest.groups.last().unwrap();
+    let dump = err.dump.as_ref().unwrap().as_ref().unwrap();
+    assert!(dump.command.contains(&format!(
+        " --max-cycles {} # failed: ",
+        last.cycles.unwrap() - 1
+    )));
+
+    let dir = temp_dir("verifier_cycles_unverified");
+    let manifest = DumpingVerifier::new(&tx, &dummy, DumpDir::new(&dir))
+        .max_cycles(1000)
+        .dump()
+        .unwrap();
+    for group in &manifest.groups {
+        assert_eq!(group.cycles, None);
+        assert!(group.command.ends_with(" --max-cycles 1000"));
+    }
+}
@@ -0,0 +1,91 @@
+use ckb_debugger_dumper::{
+    annotate_witnesses, script_groups, DumpDir, DumpingVerifier, WitnessAnnotations, WitnessField,
+    WitnessProblem,
+};
+use ckb_script::ScriptGroupType;
+use ckb_types::{bytes::Bytes, core::ScriptHashType};
+
+mod misc;
+use misc::*;
+
+#[test]
+fn test_annotate_witnesses() {
+    let mut builder = TxBuilder::new();
+    let code = builder.deploy(
+        Bytes::from(ckb_always_success_script::ALWAYS_SUCCESS.to_vec()),
+        ScriptHashType::Data1,
+    );
+    let (lock_a, lock_b, lock_c) = (
+        code.script(Bytes::from(vec![1])),
+        code.script(Bytes::from(vec![2])),
+        code.script(Bytes::from(vec![3])),
+    );
+    let type_ = code.script(Bytes::from(vec![4]));
+    let (tx, data_loader) = builder
+        .input(lock_a.clone(), None, Bytes::new())
+        .input(lock_a.clone(), None, Bytes::new())
+        .input(lock_b, None, Bytes::new())
+        .input(lock_c, None, Bytes::new())
+        .output(lock_a, Some(type_), Bytes::new())
+        .witness_args(
+            Some(Bytes::from(vec![0; 65])),
+            None,
+            Some(Bytes::from(vec![5])),
+        )
+        .witness(Bytes::new())
+        .witness(Bytes::from(vec![0xff; 3]))
+        .build();
+
+    let groups = script_groups(&tx);
+    let index_of = |args: u8| {
+        groups
+            .iter()
+            .position(|(_, group)| group.script.args().raw_data().as_ref() == [args])
+            .unwrap()
+    };
+    let annotations = annotate_witnesses(&tx);
+    assert_eq!(annotations.witnesses.len(), 3);
+
+    let first = &annotations.witnesses[0];
+    let args = first.witness_args.as_ref().unwrap();
+    assert_eq!(args.lock().to_opt().unwrap().raw_data().len(), 65);
+    assert!(args.input_type().to_opt().is_none());
+    assert_eq!(first.consumers.len(), 2);
+    assert!(first
+        .consumers
+        .iter()
+        .any(|c| c.group_index == index_of(1) && c.field == WitnessField::Lock && c.first));
+    assert!(first.consumers.iter().any(|c| c.group_index == index_of(4)
+        && c.group_type == ScriptGroupType::Type
+        && c.field == WitnessField::OutputType));
+
+    let second = &annotations.witnesses[1];
+    assert!(second.witness_args.is_none());
+    assert_eq!(second.consumers.len(), 1);
+    assert!(!second.consumers[0].first);
+
+    assert!(annotations.problems.contains(&WitnessProblem::Malformed {
+        group_index: index_of(2),
+        witness_index: 2,
+    }));
+    assert!(annotations.problems.contains(&WitnessProblem::Missing {
+        group_index: index_of(3),
+        witness_index: 3,
+    }));
+    assert_eq!(annotations.problems.len(), 2);
+
+    let js = annotations.to_json();
+    assert_eq!(js["witnesses"][0]["witness_args"]["output_type"], "0x05");
+    assert!(js["witnesses"][1]["witness_args"].is_null());
+    assert_eq!(js["problems"].len(), 2);
+
+    // Dumps carry the annotations as a sidecar.
+    let dir = temp_dir("witness_sidecar");
+    let manifest = DumpingVerifier::new(&tx, &data_loader, DumpDir::new(&dir))
+        .dump()
+        .unwrap();
+    let sidecar = WitnessAnnotations::path_for(&manifest.tx_file);
+    assert!(sidecar.to_string_lossy().ends_with(".witnesses.json"));
+    let written = json::parse(&std::fs::read_to_string(sidecar).unwrap()).unwrap();
+    assert_eq!(written, js);
+}